@@ -0,0 +1,114 @@
+//! Benchmark of the writer, which sends the messages through the DBus socket.
+//!
+//! Run it with `cargo bench --bench message_sink`.
+use bytes::{Buf, BytesMut};
+use dbus_message_parser::{message::Message, value::Value};
+use futures::{
+    executor::block_on,
+    io::{AllowStdIo, AsyncWriteExt},
+};
+use std::{
+    convert::TryInto,
+    io::Read,
+    os::unix::net::UnixStream,
+    thread::spawn,
+    time::{Duration, Instant},
+};
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/stream/frame.rs"]
+mod frame;
+
+/// The number of signals, which are sent in every benchmark.
+const MESSAGES: usize = 100_000;
+
+/// The number of times every benchmark is repeated.
+const ITERATIONS: u32 = 5;
+
+fn create_signals() -> Vec<Message> {
+    (0..MESSAGES)
+        .map(|i| {
+            let mut msg = Message::signal(
+                "/org/example".try_into().unwrap(),
+                "org.example.Interface".try_into().unwrap(),
+                "Changed".try_into().unwrap(),
+            );
+            msg.add_value(Value::Uint32(i as u32));
+            msg
+        })
+        .collect()
+}
+
+/// The previous writer, which encodes and writes one message at a time.
+async fn write_single<T>(sink: &mut T, msgs: Vec<Message>)
+where
+    T: AsyncWriteExt + Unpin,
+{
+    for msg in msgs {
+        let mut buffer = msg.encode().unwrap();
+        while !buffer.is_empty() {
+            let size = sink.write(buffer.as_mut()).await.unwrap();
+            buffer.advance(size);
+        }
+    }
+}
+
+/// The batched writer, which encodes up to `MAXIMUM_BATCH_SIZE` messages and writes them with
+/// vectored writes.
+async fn write_batched<T>(sink: &mut T, msgs: Vec<Message>)
+where
+    T: AsyncWriteExt + Unpin,
+{
+    let mut buffers: Vec<BytesMut> = Vec::with_capacity(frame::MAXIMUM_BATCH_SIZE);
+    for chunk in msgs.chunks(frame::MAXIMUM_BATCH_SIZE) {
+        for msg in chunk {
+            buffers.push(msg.encode().unwrap());
+        }
+        frame::write_buffers(sink, &mut buffers).await.unwrap();
+    }
+}
+
+/// Measure the time to send all messages through a socket pair, until the other side received
+/// all bytes.
+async fn measure(msgs: Vec<Message>, batched: bool) -> Duration {
+    let (sink, mut stream) = UnixStream::pair().unwrap();
+    let mut sink = AllowStdIo::new(sink);
+    let reader = spawn(move || {
+        let mut buffer = vec![0; 64 * 1024];
+        let mut total = 0;
+        loop {
+            let size = stream.read(&mut buffer).unwrap();
+            if size == 0 {
+                return total;
+            }
+            total += size;
+        }
+    });
+    let start = Instant::now();
+    if batched {
+        write_batched(&mut sink, msgs).await;
+    } else {
+        write_single(&mut sink, msgs).await;
+    }
+    drop(sink);
+    reader.join().unwrap();
+    start.elapsed()
+}
+
+fn main() {
+    let msgs = create_signals();
+    for (name, batched) in [("single", false), ("batched", true)] {
+        let mut total = Duration::default();
+        for _ in 0..ITERATIONS {
+            total += block_on(measure(msgs.clone(), batched));
+        }
+        let elapsed = total / ITERATIONS;
+        println!(
+            "{:<8} {} signals: {:>12.3?} ({:.0} messages/s)",
+            name,
+            MESSAGES,
+            elapsed,
+            MESSAGES as f64 / elapsed.as_secs_f64()
+        );
+    }
+}
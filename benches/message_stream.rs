@@ -0,0 +1,98 @@
+//! Benchmark of the reader, which decodes the messages received from the DBus socket.
+//!
+//! Run it with `cargo bench --bench message_stream`.
+use bytes::{Buf, BytesMut};
+use dbus_message_parser::{
+    decode::DecodeError,
+    message::Message,
+    value::{Array, Type, Value},
+};
+use futures::{executor::block_on, io::AsyncReadExt};
+use std::{
+    convert::TryInto,
+    time::{Duration, Instant},
+};
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/stream/frame.rs"]
+mod frame;
+
+/// The number of times every benchmark is repeated.
+const ITERATIONS: u32 = 5;
+
+/// Create an encoded method return, which has approximately the given size.
+fn create_message(size: usize) -> Vec<u8> {
+    let item = "x".repeat(4096);
+    let items = (0..size / item.len())
+        .map(|_| Value::String(item.clone()))
+        .collect();
+    let mut msg = Message::signal(
+        "/org/example".try_into().unwrap(),
+        "org.example.Interface".try_into().unwrap(),
+        "Objects".try_into().unwrap(),
+    );
+    msg.set_serial(1);
+    msg.add_value(Value::Array(Array::new(items, Type::String).unwrap()));
+    msg.encode().unwrap().to_vec()
+}
+
+/// The previous reader, which reads 128 bytes at a time and tries to decode the whole buffer
+/// after every read.
+async fn read_message_chunked<T>(stream: &mut T) -> Message
+where
+    T: AsyncReadExt + Unpin,
+{
+    let mut buffer_msg = BytesMut::new();
+    let mut buffer: [u8; 128] = [0; 128];
+    loop {
+        let size = stream.read(&mut buffer[..]).await.unwrap();
+        assert_ne!(size, 0);
+        buffer_msg.extend_from_slice(&buffer[..size]);
+        match Message::decode(buffer_msg.clone().freeze()) {
+            Ok((msg, offset)) => {
+                buffer_msg.advance(offset);
+                return msg;
+            }
+            Err(DecodeError::NotEnoughBytes(_, _)) => continue,
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+}
+
+fn bench<F>(name: &str, bytes: &[u8], mut read: F)
+where
+    F: FnMut(&mut &[u8]) -> Message,
+{
+    let mut total = Duration::default();
+    for _ in 0..ITERATIONS {
+        let mut stream = bytes;
+        let start = Instant::now();
+        let msg = read(&mut stream);
+        total += start.elapsed();
+        assert!(stream.is_empty());
+        drop(msg);
+    }
+    println!(
+        "{:<10} {:>6} KiB: {:>12.3?} per message",
+        name,
+        bytes.len() / 1024,
+        total / ITERATIONS
+    );
+}
+
+fn main() {
+    for size in [1 << 20, 4 << 20, 16 << 20] {
+        let bytes = create_message(size);
+        bench("framed", &bytes, |stream| {
+            let (msg, _) =
+                block_on(frame::read_message(stream, frame::MAXIMUM_MESSAGE_SIZE)).unwrap();
+            msg
+        });
+        // The chunked reader is quadratic, so it is only measured for the smallest message.
+        if size == 1 << 20 {
+            bench("chunked", &bytes, |stream| {
+                block_on(read_message_chunked(stream))
+            });
+        }
+    }
+}
@@ -22,7 +22,7 @@ impl Handler for UserDefinedObject {
         if let Ok(mut msg) = msg.method_return() {
             msg.add_value(Value::String("Hello world".to_string()));
             println!("Response: Hello world");
-            dbus.send(msg)?;
+            dbus.send(msg).await?;
         }
         Ok(())
     }
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use dbus_async::{BindOptions, CallOrdering, ConcurrentBinder, DBus, DBusResult, SharedHandler};
+use dbus_message_parser::{message::Message, value::Value};
+use std::{
+    convert::TryInto,
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+use tokio::time::sleep;
+
+// This example handles multiple method calls at the same time, so a slow call does not block the
+// other callers. The calls of the same sender are still handled in the order of arrival.
+
+struct UserDefinedObject {
+    counter: AtomicU32,
+}
+
+impl UserDefinedObject {
+    fn new() -> UserDefinedObject {
+        UserDefinedObject {
+            counter: AtomicU32::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl SharedHandler for UserDefinedObject {
+    async fn handle(&self, dbus: &DBus, msg: Message) -> DBusResult<()> {
+        println!("Got message {:?}", msg);
+        // Simulate a slow method
+        sleep(Duration::from_secs(1)).await;
+        let counter = self.counter.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut msg) = msg.method_return() {
+            msg.add_value(Value::Uint32(counter));
+            println!("Response: {}", counter);
+            dbus.send(msg).await?;
+        }
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let (dbus, _connection_handle) = DBus::session(true, true)
+        .await
+        .expect("failed to get the DBus object");
+    // Create a object, which implement the `SharedHandler`
+    let dbus_object = UserDefinedObject::new();
+    // The object path
+    let object_path = "/object/path".try_into().unwrap();
+    // Handle up to 8 method calls at the same time
+    let options = BindOptions::new()
+        .concurrency_limit(8)
+        .ordering(CallOrdering::PerSender);
+    // Bind the object to the dedicated object path
+    dbus_object
+        .bind_concurrent_with_options(dbus, object_path, options)
+        .await
+        .expect("No more message to receive");
+}
@@ -0,0 +1,25 @@
+use dbus_async::blocking::DBus;
+use dbus_message_parser::message::Message;
+use std::convert::TryInto;
+
+fn main() {
+    // The connection runs in its own runtime thread.
+    let dbus = DBus::session(true, true).expect("failed to get the DBus object");
+
+    // Create a MethodCall
+    let msg = Message::method_call(
+        "org.freedesktop.DBus".try_into().unwrap(),
+        "/org/freedesktop/DBus".try_into().unwrap(),
+        "org.freedesktop.DBus.Peer".try_into().unwrap(),
+        "Ping".try_into().unwrap(),
+    );
+
+    // Send the message and get the return message
+    let return_msg = dbus.call(msg);
+
+    // Print the return message
+    println!("{:?}", return_msg);
+
+    // Close the connection and wait until all messages are sent
+    dbus.close().expect("failed to close the DBus object");
+}
@@ -17,6 +17,6 @@ async fn main() {
     );
 
     // Send the message
-    let result = dbus.send(msg);
+    let result = dbus.send(msg).await;
     println!("{}", result.is_ok());
 }
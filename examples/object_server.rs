@@ -0,0 +1,42 @@
+use dbus_async::{Binder, DBus, MethodTable, ObjectServer};
+use dbus_message_parser::value::{Type, Value};
+use std::convert::TryInto;
+
+// This example uses the ObjectServer, which checks the signature of the arguments and replies
+// automatically with an error, if the method does not exist.
+
+#[tokio::main]
+async fn main() {
+    let (dbus, _connection_handle) = DBus::session(true, true)
+        .await
+        .expect("failed to get the DBus object");
+    // Create the method table of the interface
+    let mut table = MethodTable::new("org.example.Calculator".try_into().unwrap());
+    table.add_method(
+        "Add".try_into().unwrap(),
+        Type::from_string_to_signature("ii").unwrap(),
+        |_dbus, _header, body| async move {
+            // The signature is already checked by the ObjectServer.
+            match body.as_slice() {
+                [Value::Int32(a), Value::Int32(b)] => match a.checked_add(*b) {
+                    Some(sum) => Ok(vec![Value::Int32(sum)]),
+                    None => Err((
+                        "org.example.Calculator.Overflow".try_into().unwrap(),
+                        "The sum is too large".to_string(),
+                    )),
+                },
+                _ => unreachable!(),
+            }
+        },
+    );
+    // Create the object server and add the interface
+    let mut object_server = ObjectServer::new();
+    object_server.add_interface(table);
+    // The object path
+    let object_path = "/org/example/Calculator".try_into().unwrap();
+    // Bind the object server to the dedicated object path
+    object_server
+        .bind(dbus, object_path)
+        .await
+        .expect("No more message to receive");
+}
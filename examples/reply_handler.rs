@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use dbus_async::{Binder, DBus, DBusErrorReply, ReplyHandler, ReplyResult};
+use dbus_message_parser::{message::MessageHeader, value::Value};
+use std::convert::TryInto;
+
+// This example defines a handler, which returns the reply instead of sending it. The reply is
+// sent automatically, so no branch can forget to reply.
+
+struct UserDefinedObject {}
+
+impl UserDefinedObject {
+    fn new() -> UserDefinedObject {
+        UserDefinedObject {}
+    }
+}
+
+#[async_trait]
+impl ReplyHandler for UserDefinedObject {
+    async fn handle(
+        &mut self,
+        _dbus: &DBus,
+        header: &MessageHeader,
+        body: Vec<Value>,
+    ) -> ReplyResult {
+        println!("Got method call {:?}", header.get_member());
+        match body.as_slice() {
+            [Value::String(name)] => Ok(vec![Value::String(format!("Hello {}", name))].into()),
+            _ => Err(DBusErrorReply::invalid_args(
+                "Expected a single string".to_string(),
+            )),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let (dbus, _connection_handle) = DBus::session(true, true)
+        .await
+        .expect("failed to get the DBus object");
+    // Create a object, which implement the `ReplyHandler`
+    let dbus_object = UserDefinedObject::new();
+    // The object path
+    let object_path = "/object/path".try_into().unwrap();
+    // Bind the object to the dedicated object path
+    dbus_object
+        .bind(dbus, object_path)
+        .await
+        .expect("No more message to receive");
+}
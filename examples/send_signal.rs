@@ -1,6 +1,6 @@
-use dbus_async::DBus;
-use dbus_message_parser::message::Message;
-use std::convert::TryInto;
+use dbus_async::{ConcurrentBinder, DBus, MethodTable, ObjectServer};
+use dbus_message_parser::value::Type;
+use std::{convert::TryInto, sync::Arc};
 
 #[tokio::main]
 async fn main() {
@@ -8,14 +8,31 @@ async fn main() {
         .await
         .expect("failed to get the DBus object");
 
-    // Now we have a DBus object, so create a message
-    let msg = Message::signal(
-        "/org/example/DBus".try_into().unwrap(),
-        "org.example.DBus.Peer".try_into().unwrap(),
+    // Declare the signal of the interface
+    let mut table = MethodTable::new("org.example.DBus.Peer".try_into().unwrap());
+    table.add_signal(
         "ExampleSignal".try_into().unwrap(),
+        Type::from_string_to_signature("s").unwrap(),
     );
+    let mut object_server = ObjectServer::new();
+    object_server.add_interface(table);
 
-    // Send the message
-    let result = dbus.send(msg);
+    // Bind the object, the server is shared, so it can create the emitter afterwards
+    let object_server = Arc::new(object_server);
+    let binding = object_server
+        .clone()
+        .spawn_concurrent(dbus, "/org/example/DBus".try_into().unwrap())
+        .expect("failed to bind the object");
+    let emitter = object_server
+        .signal_emitter(&binding, "org.example.DBus.Peer".try_into().unwrap())
+        .expect("the interface is not added");
+
+    // Send the signal with a string argument
+    let result = emitter
+        .emit(
+            "ExampleSignal".try_into().unwrap(),
+            ("Hello world".to_string(),),
+        )
+        .await;
     println!("{}", result.is_ok());
 }
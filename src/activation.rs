@@ -0,0 +1,234 @@
+//! Adopt the sockets, which are passed by the service manager through [socket activation].
+//!
+//! The service manager passes the sockets as the file descriptors `3`, `4`, ... and sets the
+//! `LISTEN_PID`, `LISTEN_FDS` and `LISTEN_FDNAMES` environment variables. A connected socket can
+//! be used as a client connection with [`DBusBuilder::build_with_unix_stream`]. A listening
+//! socket can accept peer-to-peer connections with [`DBusBuilder::listen_with_unix_listener`] or
+//! can be served by the `BusDaemon` of the `daemon` feature.
+//!
+//! ```no_run
+//! use dbus_async::{activation::listen_fds, DBusBuilder};
+//!
+//! # async fn connect() -> Result<(), Box<dyn std::error::Error>> {
+//! let listen_fd = listen_fds()?
+//!     .into_iter()
+//!     .find(|listen_fd| listen_fd.get_name() == Some("dbus"))
+//!     .ok_or("no socket was passed")?;
+//! let (dbus, _connection_handle) = DBusBuilder::new()
+//!     .hello(false)
+//!     .build_with_unix_stream(listen_fd.into_unix_stream())
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [socket activation]: https://www.freedesktop.org/software/systemd/man/sd_listen_fds.html
+//! [`DBusBuilder::build_with_unix_stream`]: crate::DBusBuilder::build_with_unix_stream
+//! [`DBusBuilder::listen_with_unix_listener`]: crate::DBusBuilder::listen_with_unix_listener
+use std::{
+    env::{remove_var, var},
+    io::Error as IoError,
+    num::ParseIntError,
+    os::unix::{
+        io::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+        net::{UnixListener, UnixStream},
+    },
+    process::id,
+};
+use thiserror::Error;
+
+/// The first file descriptor, which is passed by the service manager.
+pub const LISTEN_FDS_START: RawFd = 3;
+
+const LISTEN_PID: &str = "LISTEN_PID";
+const LISTEN_FDS: &str = "LISTEN_FDS";
+const LISTEN_FDNAMES: &str = "LISTEN_FDNAMES";
+
+/// An enum representing all errors, which can occur during the adoption of the passed sockets.
+#[derive(Debug, Error)]
+pub enum ActivationError {
+    #[error("Could not parse LISTEN_PID: {0}")]
+    ListenPidInvalid(ParseIntError),
+    #[error("LISTEN_PID does not match the process: {0} != {1}")]
+    ListenPidMismatch(u32, u32),
+    #[error("Could not parse LISTEN_FDS: {0}")]
+    ListenFdsInvalid(ParseIntError),
+    #[error("LISTEN_FDNAMES does not match LISTEN_FDS: {0} != {1}")]
+    ListenFdNamesMismatch(usize, usize),
+    #[error("IO Error: {0}")]
+    IoError(#[from] IoError),
+}
+
+/// A socket, which is passed by the service manager.
+#[derive(Debug)]
+pub struct ListenFd {
+    fd: OwnedFd,
+    name: Option<String>,
+}
+
+impl ListenFd {
+    /// Get the name of the socket, which is set by `LISTEN_FDNAMES` (for example by the
+    /// `FileDescriptorName=` option of systemd).
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Use the socket as a connected Unix Domain Stream socket.
+    pub fn into_unix_stream(self) -> UnixStream {
+        UnixStream::from(self.fd)
+    }
+
+    /// Use the socket as a listening Unix Domain Stream socket.
+    pub fn into_unix_listener(self) -> UnixListener {
+        UnixListener::from(self.fd)
+    }
+}
+
+impl AsRawFd for ListenFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+/// Parse the environment variables of the socket activation. Returns the names of the passed
+/// file descriptors.
+fn parse_environment(
+    listen_pid: Option<&str>,
+    listen_fds: Option<&str>,
+    listen_fdnames: Option<&str>,
+    pid: u32,
+) -> Result<Vec<Option<String>>, ActivationError> {
+    let (listen_pid, listen_fds) = match (listen_pid, listen_fds) {
+        (Some(listen_pid), Some(listen_fds)) => (listen_pid, listen_fds),
+        _ => return Ok(Vec::new()),
+    };
+    let listen_pid = listen_pid
+        .parse::<u32>()
+        .map_err(ActivationError::ListenPidInvalid)?;
+    if listen_pid != pid {
+        return Err(ActivationError::ListenPidMismatch(listen_pid, pid));
+    }
+    let listen_fds = listen_fds
+        .parse::<usize>()
+        .map_err(ActivationError::ListenFdsInvalid)?;
+    match listen_fdnames {
+        Some(listen_fdnames) => {
+            let names: Vec<Option<String>> = listen_fdnames
+                .split(':')
+                .map(|name| Some(name.to_string()))
+                .collect();
+            if names.len() == listen_fds {
+                Ok(names)
+            } else {
+                Err(ActivationError::ListenFdNamesMismatch(
+                    names.len(),
+                    listen_fds,
+                ))
+            }
+        }
+        None => Ok(vec![None; listen_fds]),
+    }
+}
+
+/// Set the `FD_CLOEXEC` flag, so the socket is not passed to the child processes.
+fn set_cloexec(fd: RawFd) -> Result<(), IoError> {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+    if flags < 0 {
+        return Err(IoError::last_os_error());
+    }
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, flags | libc::FD_CLOEXEC) } < 0 {
+        return Err(IoError::last_os_error());
+    }
+    Ok(())
+}
+
+/// Take the sockets, which are passed by the service manager like [`sd_listen_fds_with_names`].
+///
+/// If `LISTEN_PID` or `LISTEN_FDS` is not set then no socket was passed and an empty `Vec` is
+/// returned. If `LISTEN_PID` does not match the ID of the process then the sockets were passed
+/// to another process and an error is returned.
+///
+/// The environment variables are removed, after the sockets are taken, so the sockets can only be
+/// taken once and are not taken by the child processes. If one of the passed file descriptors is
+/// invalid then an error is returned, no socket is taken and the environment variables are kept.
+///
+/// [`sd_listen_fds_with_names`]: https://www.freedesktop.org/software/systemd/man/sd_listen_fds.html
+pub fn listen_fds() -> Result<Vec<ListenFd>, ActivationError> {
+    let listen_pid = var(LISTEN_PID).ok();
+    let listen_fds = var(LISTEN_FDS).ok();
+    let listen_fdnames = var(LISTEN_FDNAMES).ok();
+    let names = parse_environment(
+        listen_pid.as_deref(),
+        listen_fds.as_deref(),
+        listen_fdnames.as_deref(),
+        id(),
+    )?;
+
+    // Check all file descriptors first, so no file descriptor is closed and the environment
+    // variables are kept, if one of them is invalid.
+    for fd in (LISTEN_FDS_START..).take(names.len()) {
+        set_cloexec(fd)?;
+    }
+    remove_var(LISTEN_PID);
+    remove_var(LISTEN_FDS);
+    remove_var(LISTEN_FDNAMES);
+
+    let result = (LISTEN_FDS_START..)
+        .zip(names)
+        .map(|(fd, name)| {
+            // The file descriptor is owned by the process, because `LISTEN_PID` matches and the
+            // environment variables are removed.
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+            ListenFd { fd, name }
+        })
+        .collect();
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_environment_not_set() {
+        assert!(parse_environment(None, None, None, 1).unwrap().is_empty());
+        assert!(parse_environment(Some("1"), None, None, 1)
+            .unwrap()
+            .is_empty());
+        assert!(parse_environment(None, Some("1"), None, 1)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn parse_environment_names() {
+        assert_eq!(
+            parse_environment(Some("1"), Some("2"), None, 1).unwrap(),
+            vec![None, None]
+        );
+        assert_eq!(
+            parse_environment(Some("1"), Some("2"), Some("dbus:bus"), 1).unwrap(),
+            vec![Some("dbus".to_string()), Some("bus".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_environment_invalid() {
+        assert!(matches!(
+            parse_environment(Some("x"), Some("1"), None, 1),
+            Err(ActivationError::ListenPidInvalid(_))
+        ));
+        assert!(matches!(
+            parse_environment(Some("2"), Some("1"), None, 1),
+            Err(ActivationError::ListenPidMismatch(2, 1))
+        ));
+        assert!(matches!(
+            parse_environment(Some("1"), Some("x"), None, 1),
+            Err(ActivationError::ListenFdsInvalid(_))
+        ));
+        assert!(matches!(
+            parse_environment(Some("1"), Some("2"), Some("dbus"), 1),
+            Err(ActivationError::ListenFdNamesMismatch(1, 2))
+        ));
+    }
+}
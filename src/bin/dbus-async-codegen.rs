@@ -0,0 +1,33 @@
+use dbus_async::codegen::generate;
+use std::{
+    env::args,
+    fs::read_to_string,
+    io::{stdin, Read},
+    process::exit,
+};
+
+// Read the introspection data from the file (or from the standard input, if no file is given) and
+// print the generated code to the standard output.
+fn main() {
+    let xml = match args().nth(1) {
+        Some(path) => read_to_string(&path).unwrap_or_else(|e| {
+            eprintln!("Could not read {}: {}", path, e);
+            exit(1);
+        }),
+        None => {
+            let mut xml = String::new();
+            if let Err(e) = stdin().read_to_string(&mut xml) {
+                eprintln!("Could not read from stdin: {}", e);
+                exit(1);
+            }
+            xml
+        }
+    };
+    match generate(&xml) {
+        Ok(code) => print!("{}", code),
+        Err(e) => {
+            eprintln!("Could not generate code: {}", e);
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use dbus_async::{activation::listen_fds, daemon::BusDaemon};
+use futures::future::pending;
+use std::{env::args, env::temp_dir, process::exit};
+use tokio::runtime::Builder;
+
+// Listen on the given server addresses (or on the sockets, which are passed by the service
+// manager, or on a Unix Domain Stream socket in the temporary directory, if no address is given)
+// and print the addresses, which can be used by the clients.
+fn main() {
+    let runtime = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap_or_else(|e| {
+            eprintln!("Could not create the runtime: {}", e);
+            exit(1);
+        });
+    runtime.block_on(run());
+}
+
+async fn run() {
+    let mut daemon = BusDaemon::new();
+    let mut addresses: Vec<String> = args().skip(1).collect();
+    if addresses.is_empty() {
+        let listen_fds = listen_fds().unwrap_or_else(|e| {
+            eprintln!("Could not take the passed sockets: {}", e);
+            exit(1);
+        });
+        if listen_fds.is_empty() {
+            let path = temp_dir().join(format!("dbus-async-daemon-{}", std::process::id()));
+            addresses.push(format!("unix:path={}", path.display()));
+        }
+        for listen_fd in listen_fds {
+            if let Err(e) = daemon.listen_with_unix_listener(listen_fd.into_unix_listener()) {
+                eprintln!("Could not listen on the passed socket: {}", e);
+                exit(1);
+            }
+        }
+    }
+    for address in addresses {
+        match daemon.listen(&address).await {
+            Ok(address) => println!("{}", address),
+            Err(e) => {
+                eprintln!("Could not listen on {}: {}", address, e);
+                exit(1);
+            }
+        }
+    }
+    pending::<()>().await;
+}
@@ -0,0 +1,117 @@
+use crate::{
+    runtime::{spawn, JoinHandle},
+    DBus, DBusError, DBusResult, SignalEmitter,
+};
+use dbus_message_parser::{
+    message::{Message, MessageFlags},
+    value::{Interface, ObjectPath},
+};
+use futures::channel::mpsc::Sender;
+use std::future::Future;
+
+/// A handle of a binding, which runs in its own task (see [`Binder::spawn`] and
+/// [`ConcurrentBinder::spawn_concurrent`]).
+///
+/// If the handle is dropped or [`unbind`] is called then the [`ObjectPath`] is unregistered and
+/// the task is stopped. The [`ObjectPath`] is not listed by the introspection anymore.
+///
+/// [`Binder::spawn`]: crate::Binder::spawn
+/// [`ConcurrentBinder::spawn_concurrent`]: crate::ConcurrentBinder::spawn_concurrent
+/// [`unbind`]: #method.unbind
+/// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+pub struct Binding {
+    dbus: DBus,
+    object_path: ObjectPath,
+    // A clone of the registered sender, to unregister only this binding and not a binding, which
+    // replaced it.
+    sender: Option<Sender<(Message, MessageFlags)>>,
+    task: Option<JoinHandle<DBusResult<()>>>,
+}
+
+impl Binding {
+    pub(crate) fn new<F>(
+        dbus: DBus,
+        object_path: ObjectPath,
+        sender: Sender<(Message, MessageFlags)>,
+        binding: F,
+    ) -> Binding
+    where
+        F: Future<Output = DBusResult<()>> + Send + 'static,
+    {
+        let task = spawn(binding);
+        Binding {
+            dbus,
+            object_path,
+            sender: Some(sender),
+            task: Some(task),
+        }
+    }
+
+    /// Get the [`ObjectPath`] of the binding.
+    ///
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    pub fn get_object_path(&self) -> &ObjectPath {
+        &self.object_path
+    }
+
+    /// Create a [`SignalEmitter`] for the signals of the [`Interface`] of the bound object.
+    ///
+    /// The signals are not checked. An [`ObjectServer`] creates an emitter, which checks the
+    /// signals against the declared signals (see [`ObjectServer::signal_emitter`]).
+    ///
+    /// [`Interface`]: dbus_message_parser::value::Interface
+    /// [`ObjectServer`]: crate::ObjectServer
+    /// [`ObjectServer::signal_emitter`]: crate::ObjectServer::signal_emitter
+    pub fn signal_emitter(&self, interface: Interface) -> SignalEmitter {
+        SignalEmitter::new(self.dbus.clone(), self.object_path.clone(), interface)
+    }
+
+    /// Unregister the [`ObjectPath`] and stop the task of the binding.
+    ///
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    pub fn unbind(mut self) -> DBusResult<()> {
+        self.stop()
+    }
+
+    /// Wait until the binding ends by itself, for example because of an error or a
+    /// [`ShutdownHandle`], unregister the [`ObjectPath`] and return the result of the binding.
+    ///
+    /// If the returned future is dropped before the binding ends then the binding is stopped like
+    /// the handle is dropped.
+    ///
+    /// [`ShutdownHandle`]: crate::ShutdownHandle
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    pub async fn join(mut self) -> DBusResult<()> {
+        let result = match self.task.as_mut() {
+            Some(task) => match task.await {
+                Ok(result) => result,
+                Err(e) => Err(DBusError::HandlerPanic(e.to_string())),
+            },
+            None => Ok(()),
+        };
+        // The task is finished, so only the ObjectPath has to be unregistered.
+        self.task.take();
+        if let Err(e) = self.stop() {
+            error!("Binding: could not unregister {}: {}", self.object_path, e);
+        }
+        result
+    }
+
+    fn stop(&mut self) -> DBusResult<()> {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+        if let Some(sender) = self.sender.take() {
+            self.dbus.delete_method_call_sender_with_flags(sender)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Binding {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop() {
+            error!("Binding: could not unregister {}: {}", self.object_path, e);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+//! A blocking interface of the [`DBus`] object for synchronous code.
+//!
+//! The connection runs in its own runtime thread, so the functions can be called from any thread,
+//! which is not inside an async context.
+//!
+//! [`DBus`]: crate::DBus
+use crate::{
+    runtime::{JoinHandle, RuntimeThread},
+    DBus as AsyncDBus, DBusBuilder, DBusError, DBusNameFlag, DBusResult,
+    DEFAULT_HANDLER_CHANNEL_SIZE,
+};
+use dbus_message_parser::{
+    match_rule::MatchRule,
+    message::Message,
+    value::{Bus, ObjectPath},
+};
+use futures::{
+    channel::mpsc::{channel, Receiver},
+    executor::block_on,
+    StreamExt,
+};
+use std::future::Future;
+
+/// This struct represents a blocking object to communicate with the DBus daemon.
+///
+/// If the object is dropped or [`close`] is called then the connection is closed. All messages,
+/// which were sent before, are written to the socket before the runtime thread stops.
+///
+/// [`close`]: #method.close
+pub struct DBus {
+    dbus: AsyncDBus,
+    connection_handle: Option<JoinHandle<()>>,
+    runtime: RuntimeThread,
+}
+
+impl DBus {
+    /// Connect to the session DBus (see [`DBus::session`]).
+    ///
+    /// [`DBus::session`]: crate::DBus::session
+    pub fn session(introspectable: bool, peer: bool) -> DBusResult<DBus> {
+        DBusBuilder::new()
+            .session()
+            .introspectable(introspectable)
+            .peer(peer)
+            .build_blocking()
+    }
+
+    /// Connect to the system DBus (see [`DBus::system`]).
+    ///
+    /// [`DBus::system`]: crate::DBus::system
+    pub fn system(introspectable: bool, peer: bool) -> DBusResult<DBus> {
+        DBusBuilder::new()
+            .system()
+            .introspectable(introspectable)
+            .peer(peer)
+            .build_blocking()
+    }
+
+    /// Connect to the DBus daemon with the options of the builder.
+    pub(crate) fn from_builder(builder: DBusBuilder) -> DBusResult<DBus> {
+        let runtime = RuntimeThread::new().map_err(DBusError::RuntimeThread)?;
+        let (dbus, connection_handle) = runtime.block_on(builder.build())??;
+        Ok(DBus {
+            dbus,
+            connection_handle: Some(connection_handle),
+            runtime,
+        })
+    }
+
+    /// Run the future in the runtime thread and wait for the result.
+    fn block_on<F, T>(&self, future: F) -> DBusResult<T>
+    where
+        F: Future<Output = DBusResult<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.runtime.block_on(future)?
+    }
+
+    /// Send a [`Message`] (see [`DBus::send`]).
+    ///
+    /// [`Message`]: dbus_message_parser::message::Message
+    /// [`DBus::send`]: crate::DBus::send
+    pub fn send(&self, msg: Message) -> DBusResult<()> {
+        let dbus = self.dbus.clone();
+        self.block_on(async move { dbus.send(msg).await })
+    }
+
+    /// Send a [`Message`] and wait for the response (see [`DBus::call`]).
+    ///
+    /// [`Message`]: dbus_message_parser::message::Message
+    /// [`DBus::call`]: crate::DBus::call
+    pub fn call(&self, msg: Message) -> DBusResult<Message> {
+        let dbus = self.dbus.clone();
+        self.block_on(async move { dbus.call(msg).await })
+    }
+
+    /// Register a name for the peer (see [`DBus::request_name`]).
+    ///
+    /// [`DBus::request_name`]: crate::DBus::request_name
+    pub fn request_name(&self, name: Bus, flags: &DBusNameFlag) -> DBusResult<Message> {
+        let dbus = self.dbus.clone();
+        let flags = *flags;
+        self.block_on(async move { dbus.request_name(name, &flags).await })
+    }
+
+    /// Receive the [`Signal`] messages of the [`ObjectPath`] (see [`DBus::add_signal`]).
+    ///
+    /// [`Signal`]: dbus_message_parser::message::MessageType::Signal
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    /// [`DBus::add_signal`]: crate::DBus::add_signal
+    pub fn add_signal(
+        &self,
+        object_path: ObjectPath,
+        filter: Option<fn(&Message) -> bool>,
+    ) -> DBusResult<Signals> {
+        let (sender, receiver) = channel(DEFAULT_HANDLER_CHANNEL_SIZE);
+        self.dbus.add_signal(object_path, filter, sender)?;
+        Ok(Signals(receiver))
+    }
+
+    /// Receive the messages, which match the [`MatchRule`]s (see [`DBus::add_match_rules`]).
+    ///
+    /// [`MatchRule`]: dbus_message_parser::match_rule::MatchRule
+    /// [`DBus::add_match_rules`]: crate::DBus::add_match_rules
+    pub fn add_match_rules(&self, match_rules: Vec<MatchRule>) -> DBusResult<Signals> {
+        let (sender, receiver) = channel(DEFAULT_HANDLER_CHANNEL_SIZE);
+        self.dbus.add_match_rules(match_rules, sender)?;
+        Ok(Signals(receiver))
+    }
+
+    /// Get the async [`DBus`] object, which is used by this object.
+    ///
+    /// [`DBus`]: crate::DBus
+    pub fn get_dbus(&self) -> &AsyncDBus {
+        &self.dbus
+    }
+
+    /// Close the connection and wait until all messages, which were sent before, are written.
+    pub fn close(mut self) -> DBusResult<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> DBusResult<()> {
+        if let Some(connection_handle) = self.connection_handle.take() {
+            self.dbus.close()?;
+            self.runtime.block_on(connection_handle)??;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DBus {
+    fn drop(&mut self) {
+        if let Err(e) = self.shutdown() {
+            error!("DBus: could not close the connection: {}", e);
+        }
+    }
+}
+
+/// An iterator over the received messages (see [`DBus::add_signal`] and
+/// [`DBus::add_match_rules`]).
+///
+/// The iterator blocks until the next message is received. The iterator ends, if the connection
+/// is closed. If the iterator is dropped then the messages are not received anymore.
+///
+/// [`DBus::add_signal`]: crate::blocking::DBus::add_signal
+/// [`DBus::add_match_rules`]: crate::blocking::DBus::add_match_rules
+pub struct Signals(Receiver<Message>);
+
+impl Iterator for Signals {
+    type Item = Message;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        block_on(self.0.next())
+    }
+}
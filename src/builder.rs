@@ -0,0 +1,352 @@
+use crate::{
+    blocking, stream::Stream, DBus, DBusError, DBusListener, DBusResult, JoinHandle, Transport,
+    MAXIMUM_MESSAGE_SIZE,
+};
+use std::{
+    env::var,
+    os::unix::net::{UnixListener, UnixStream},
+    time::Duration,
+};
+
+/// The default maximum number of method calls, which wait for a reply at the same time.
+pub const DEFAULT_PENDING_CALL_LIMIT: usize = 1024;
+
+/// The default size of the channels of the built-in interfaces and the bindings.
+pub const DEFAULT_HANDLER_CHANNEL_SIZE: usize = 1024;
+
+/// The default size of the queue of the messages, which are sent (see
+/// [`DBusBuilder::send_queue_size`]).
+pub const DEFAULT_SEND_QUEUE_SIZE: usize = 1024;
+
+/// The default size of the queue of the messages, which are received (see
+/// [`DBusBuilder::receive_queue_size`]).
+pub const DEFAULT_RECEIVE_QUEUE_SIZE: usize = 1024;
+
+/// The address of the system bus, if the `DBUS_SYSTEM_BUS_ADDRESS` environment variable is not
+/// defined.
+const DEFAULT_SYSTEM_BUS_ADDRESS: &str = "unix:path=/var/run/dbus/system_bus_socket";
+
+/// The policy of the connection, if a method call is sent and the maximum number of pending calls
+/// is reached (see [`DBusBuilder::pending_call_limit`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PendingCallPolicy {
+    /// Stop waiting for the reply of the oldest pending call. The caller of the oldest call gets a
+    /// [`DBusError::PendingCallEvicted`] error. If the call was sent with
+    /// [`DBus::call_reply_serial`] then an `org.freedesktop.DBus.Error.NoReply` error reply is
+    /// sent to the channel of the caller instead.
+    ///
+    /// [`DBusError::PendingCallEvicted`]: crate::DBusError::PendingCallEvicted
+    /// [`DBus::call_reply_serial`]: crate::DBus::call_reply_serial
+    #[default]
+    EvictOldest,
+    /// Do not send the new method call. The caller gets a [`DBusError::PendingCallLimit`] error.
+    ///
+    /// [`DBusError::PendingCallLimit`]: crate::DBusError::PendingCallLimit
+    RejectNew,
+    /// Send the new method call, after a reply of a pending call is received.
+    Wait,
+}
+
+/// The policy of the connection, if the queue of the received messages is full (see
+/// [`DBusBuilder::receive_queue_size`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReceivePolicy {
+    /// Stop reading from the socket until there is space in the queue.
+    #[default]
+    PauseReading,
+    /// Drop the received signals and count them (see [`DBus::get_dropped_signals`]). All other
+    /// messages pause the reading like [`ReceivePolicy::PauseReading`].
+    DropSignals,
+}
+
+/// The [authentication mechanisms], which are supported.
+///
+/// [authentication mechanisms]: https://dbus.freedesktop.org/doc/dbus-specification.html#auth-mechanisms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMechanism {
+    /// Authenticate with the UID of the process.
+    External,
+    /// Authenticate anonymously.
+    Anonymous,
+}
+
+impl AuthMechanism {
+    /// Get the name of the mechanism, which is used in the authentication protocol.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthMechanism::External => "EXTERNAL",
+            AuthMechanism::Anonymous => "ANONYMOUS",
+        }
+    }
+}
+
+/// The address of the DBus daemon to connect to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BusAddress {
+    Session,
+    System,
+    Address(String),
+}
+
+/// A builder to configure and create a [`DBus`] connection.
+///
+/// ```no_run
+/// use dbus_async::{AuthMechanism, DBusBuilder};
+/// use std::time::Duration;
+///
+/// # async fn connect() -> dbus_async::DBusResult<()> {
+/// let (dbus, _connection_handle) = DBusBuilder::new()
+///     .system()
+///     .auth_mechanisms(vec![AuthMechanism::External])
+///     .call_timeout(Some(Duration::from_secs(25)))
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DBusBuilder {
+    address: BusAddress,
+    pub(crate) auth_mechanisms: Vec<AuthMechanism>,
+    pub(crate) negotiate_unix_fd: bool,
+    pub(crate) introspectable: bool,
+    pub(crate) peer: bool,
+    pub(crate) handler_channel_size: usize,
+    pub(crate) hello: bool,
+    pub(crate) pending_call_limit: usize,
+    pub(crate) pending_call_policy: PendingCallPolicy,
+    pub(crate) call_timeout: Option<Duration>,
+    pub(crate) send_queue_size: usize,
+    pub(crate) receive_queue_size: usize,
+    pub(crate) receive_policy: ReceivePolicy,
+    pub(crate) maximum_message_size: usize,
+}
+
+impl Default for DBusBuilder {
+    fn default() -> Self {
+        DBusBuilder {
+            address: BusAddress::Session,
+            auth_mechanisms: vec![AuthMechanism::External, AuthMechanism::Anonymous],
+            negotiate_unix_fd: false,
+            introspectable: true,
+            peer: true,
+            handler_channel_size: DEFAULT_HANDLER_CHANNEL_SIZE,
+            hello: true,
+            pending_call_limit: DEFAULT_PENDING_CALL_LIMIT,
+            pending_call_policy: PendingCallPolicy::default(),
+            call_timeout: None,
+            send_queue_size: DEFAULT_SEND_QUEUE_SIZE,
+            receive_queue_size: DEFAULT_RECEIVE_QUEUE_SIZE,
+            receive_policy: ReceivePolicy::default(),
+            maximum_message_size: MAXIMUM_MESSAGE_SIZE,
+        }
+    }
+}
+
+impl DBusBuilder {
+    /// Create a builder with the default options. The default is to connect to the session bus.
+    pub fn new() -> DBusBuilder {
+        DBusBuilder::default()
+    }
+
+    /// Connect to the session bus.
+    ///
+    /// The `DBUS_SESSION_BUS_ADDRESS` environment variable **have to** be defined.
+    pub fn session(mut self) -> DBusBuilder {
+        self.address = BusAddress::Session;
+        self
+    }
+
+    /// Connect to the system bus.
+    ///
+    /// If there `DBUS_SYSTEM_BUS_ADDRESS` environment variable is defined then this path will be
+    /// used, else `unix:path=/var/run/dbus/system_bus_socket`.
+    pub fn system(mut self) -> DBusBuilder {
+        self.address = BusAddress::System;
+        self
+    }
+
+    /// Connect to the specific (`addressses`) DBus daemon. The addresses are tried in order.
+    pub fn address(mut self, addressses: &str) -> DBusBuilder {
+        self.address = BusAddress::Address(addressses.to_string());
+        self
+    }
+
+    /// Set the authentication mechanisms in the order, in which they are tried. Only the
+    /// mechanisms, which are offered by the DBus daemon, are tried. The default is `EXTERNAL`
+    /// and then `ANONYMOUS`.
+    pub fn auth_mechanisms(mut self, auth_mechanisms: Vec<AuthMechanism>) -> DBusBuilder {
+        self.auth_mechanisms = auth_mechanisms;
+        self
+    }
+
+    /// Set, if the passing of Unix file descriptors is negotiated on Unix domain sockets. The
+    /// default is `false`, because the file descriptors of the received messages are not read
+    /// from the socket.
+    pub fn negotiate_unix_fd(mut self, negotiate_unix_fd: bool) -> DBusBuilder {
+        self.negotiate_unix_fd = negotiate_unix_fd;
+        self
+    }
+
+    /// Set, if the peer is [introspectable]. The default is `true`.
+    ///
+    /// [introspectable]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-introspectable
+    pub fn introspectable(mut self, introspectable: bool) -> DBusBuilder {
+        self.introspectable = introspectable;
+        self
+    }
+
+    /// Set, if the peer has the [`org.freedesktop.DBus.Peer`] interface. The default is `true`.
+    ///
+    /// [`org.freedesktop.DBus.Peer`]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-peer
+    pub fn peer(mut self, peer: bool) -> DBusBuilder {
+        self.peer = peer;
+        self
+    }
+
+    /// Set the size of the channels of the built-in interfaces and of the objects, which are bound
+    /// by the [`Binder`] and the [`ConcurrentBinder`] traits. A size of `0` is treated as `1`.
+    /// The default is [`DEFAULT_HANDLER_CHANNEL_SIZE`].
+    ///
+    /// [`Binder`]: crate::Binder
+    /// [`ConcurrentBinder`]: crate::ConcurrentBinder
+    pub fn handler_channel_size(mut self, handler_channel_size: usize) -> DBusBuilder {
+        self.handler_channel_size = handler_channel_size.max(1);
+        self
+    }
+
+    /// Set, if the [`Hello()`] method is called after the connection is established. This is
+    /// required by a DBus daemon, but not by a peer-to-peer connection. The default is `true`.
+    ///
+    /// [`Hello()`]: https://dbus.freedesktop.org/doc/dbus-specification.html#bus-messages-hello
+    pub fn hello(mut self, hello: bool) -> DBusBuilder {
+        self.hello = hello;
+        self
+    }
+
+    /// Set the maximum number of method calls, which wait for a reply at the same time. A limit of
+    /// `0` is treated as `1`. The default is [`DEFAULT_PENDING_CALL_LIMIT`].
+    pub fn pending_call_limit(mut self, pending_call_limit: usize) -> DBusBuilder {
+        self.pending_call_limit = pending_call_limit.max(1);
+        self
+    }
+
+    /// Set the [`PendingCallPolicy`], if the maximum number of pending calls is reached.
+    pub fn pending_call_policy(mut self, pending_call_policy: PendingCallPolicy) -> DBusBuilder {
+        self.pending_call_policy = pending_call_policy;
+        self
+    }
+
+    /// Set the default time to wait for the reply of a method call (see [`DBus::call`]). If the
+    /// time elapsed then [`DBusError::CallTimeout`] is returned. The default is `None`, which
+    /// means to wait forever.
+    ///
+    /// [`DBusError::CallTimeout`]: crate::DBusError::CallTimeout
+    pub fn call_timeout(mut self, call_timeout: Option<Duration>) -> DBusBuilder {
+        self.call_timeout = call_timeout;
+        self
+    }
+
+    /// Set the maximum number of messages, which wait to be sent. If the queue is full then
+    /// [`DBus::send`] and [`DBus::call`] wait until there is space in the queue. A size of `0` is
+    /// treated as `1`. The default is [`DEFAULT_SEND_QUEUE_SIZE`].
+    pub fn send_queue_size(mut self, send_queue_size: usize) -> DBusBuilder {
+        self.send_queue_size = send_queue_size.max(1);
+        self
+    }
+
+    /// Set the maximum number of received messages, which wait to be dispatched. A size of `0` is
+    /// treated as `1`. The default is [`DEFAULT_RECEIVE_QUEUE_SIZE`].
+    pub fn receive_queue_size(mut self, receive_queue_size: usize) -> DBusBuilder {
+        self.receive_queue_size = receive_queue_size.max(1);
+        self
+    }
+
+    /// Set the [`ReceivePolicy`], if the queue of the received messages is full.
+    pub fn receive_policy(mut self, receive_policy: ReceivePolicy) -> DBusBuilder {
+        self.receive_policy = receive_policy;
+        self
+    }
+
+    /// Set the maximum size of a received message in bytes. If a larger message is received then
+    /// the connection is closed with [`ProtocolError::TooLarge`] (see
+    /// [`DBus::get_protocol_error`]). A size greater than [`MAXIMUM_MESSAGE_SIZE`] is treated as
+    /// [`MAXIMUM_MESSAGE_SIZE`]. The default is [`MAXIMUM_MESSAGE_SIZE`].
+    ///
+    /// [`ProtocolError::TooLarge`]: crate::ProtocolError::TooLarge
+    /// [`DBus::get_protocol_error`]: crate::DBus::get_protocol_error
+    pub fn maximum_message_size(mut self, maximum_message_size: usize) -> DBusBuilder {
+        self.maximum_message_size = maximum_message_size.min(MAXIMUM_MESSAGE_SIZE);
+        self
+    }
+
+    /// Connect to the DBus daemon with the options.
+    pub async fn build(self) -> DBusResult<(DBus, JoinHandle<()>)> {
+        let addressses = match &self.address {
+            BusAddress::Session => match var("DBUS_SESSION_BUS_ADDRESS") {
+                Ok(addressses) => addressses,
+                // It could not connect to any socket
+                Err(_) => return Err(DBusError::DBusSessionBusAddress),
+            },
+            BusAddress::System => var("DBUS_SYSTEM_BUS_ADDRESS")
+                .unwrap_or_else(|_| DEFAULT_SYSTEM_BUS_ADDRESS.to_string()),
+            BusAddress::Address(addressses) => addressses.clone(),
+        };
+        DBus::from_builder(&addressses, self).await
+    }
+
+    /// Connect to the DBus daemon through the given duplex stream with the options, for example
+    /// through a SSH channel or an in-memory stream. The address of the builder is ignored.
+    ///
+    /// The stream has to implement the `futures` IO traits. A `tokio` IO object can be wrapped
+    /// with [`TokioCompat`].
+    ///
+    /// [`TokioCompat`]: crate::TokioCompat
+    pub async fn build_with_stream<T>(self, transport: T) -> DBusResult<(DBus, JoinHandle<()>)>
+    where
+        T: Transport + 'static,
+    {
+        let stream = Stream::from_transport(transport, &self).await?;
+        DBus::from_stream(None, stream, self).await
+    }
+
+    /// Connect to the DBus daemon through the given connected Unix Domain Stream socket with the
+    /// options, for example a socket, which is inherited through socket activation (see
+    /// [`activation::listen_fds`]). The address of the builder is ignored.
+    ///
+    /// This function must be called inside the runtime.
+    ///
+    /// [`activation::listen_fds`]: crate::activation::listen_fds
+    pub async fn build_with_unix_stream(
+        self,
+        unix_stream: UnixStream,
+    ) -> DBusResult<(DBus, JoinHandle<()>)> {
+        let stream = Stream::from_unix_stream(unix_stream, &self).await?;
+        DBus::from_stream(None, stream, self).await
+    }
+
+    /// Accept peer-to-peer connections on the given listening Unix Domain Stream socket with the
+    /// options, for example a socket, which is inherited through socket activation (see
+    /// [`activation::listen_fds`]). The address of the builder is ignored and no `Hello` message
+    /// is sent, because there is no message bus.
+    ///
+    /// This function must be called inside the runtime.
+    ///
+    /// [`activation::listen_fds`]: crate::activation::listen_fds
+    pub fn listen_with_unix_listener(
+        self,
+        unix_listener: UnixListener,
+    ) -> DBusResult<DBusListener> {
+        DBusListener::new(unix_listener, self)
+    }
+
+    /// Connect to the DBus daemon with the options and return a [`blocking::DBus`], which runs
+    /// the connection in its own runtime thread.
+    ///
+    /// This function must not be called inside an async context.
+    ///
+    /// [`blocking::DBus`]: crate::blocking::DBus
+    pub fn build_blocking(self) -> DBusResult<blocking::DBus> {
+        blocking::DBus::from_builder(self)
+    }
+}
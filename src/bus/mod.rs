@@ -0,0 +1,208 @@
+//! A minimal message bus, which routes the messages between the connected peers.
+//!
+//! Every connection gets a unique name and the bus implements the methods of the
+//! `org.freedesktop.DBus` interface, which are needed to own names and to receive signals.
+mod names;
+mod router;
+
+use crate::{
+    runtime::{spawn, JoinHandle},
+    stream::{create_guid, message_sink, read_message, Handshake, Transport, MAXIMUM_MESSAGE_SIZE},
+    AuthMechanism, Uuid,
+};
+use dbus_message_parser::{
+    message::{Message, MessageFlags, MessageHeader, MessageHeaderFields},
+    value::Bus as BusName,
+};
+use futures::io::AsyncReadExt;
+use hex::encode;
+use router::Router;
+use std::{
+    convert::TryInto,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use tokio::sync::mpsc::{channel, Sender};
+
+/// The name of the bus itself.
+const BUS_NAME: &str = "org.freedesktop.DBus";
+
+/// The size of the queue of the messages, which are sent to a connection. If the queue of a
+/// connection is full then the messages to this connection are dropped and the senders of the
+/// method calls, which expect a reply, get a `org.freedesktop.DBus.Error.LimitsExceeded` error.
+const CONNECTION_QUEUE_SIZE: usize = 1024;
+
+/// The size of the queue of the events, which are sent to the bus task. If the queue is full then
+/// the connections stop reading messages until the bus task catches up.
+const EVENT_QUEUE_SIZE: usize = 1024;
+
+/// The protocol version of the messages, which are created by the bus.
+const PROTOCOL_VERSION: u8 = 1;
+
+/// The events of the connections, which are handled by the bus task.
+#[derive(Debug)]
+enum Event {
+    Connect {
+        unique_name: String,
+        sender: Sender<(Message, Option<MessageFlags>)>,
+        uid: Option<u32>,
+    },
+    Message(String, Box<Message>, MessageFlags),
+    Disconnect(String),
+}
+
+/// Replace the sender of the message.
+///
+/// The message does not provide a setter for the sender, so the header is created again with the
+/// given flags. If this fails then `None` is returned.
+fn set_sender(msg: Message, sender: &str, flags: MessageFlags) -> Option<Message> {
+    let sender: BusName = sender.try_into().ok()?;
+    let (header, body) = msg.split().ok()?;
+    let fields = MessageHeaderFields {
+        path: header.get_path().cloned(),
+        interface: header.get_interface().cloned(),
+        member: header.get_member().cloned(),
+        error_name: header.get_error_name().cloned(),
+        reply_serial: header.get_reply_serial(),
+        destination: header.get_destination().cloned(),
+        sender: Some(sender),
+        ..Default::default()
+    };
+    let header = MessageHeader::new(
+        true,
+        header.get_type(),
+        flags,
+        PROTOCOL_VERSION,
+        header.get_serial(),
+        fields,
+    )
+    .ok()?;
+    Some(Message::new(header, body))
+}
+
+/// How the clients of a connection are authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Authentication {
+    /// The UID, which is sent with the `EXTERNAL` mechanism, is trusted, for example for
+    /// in-memory streams.
+    Trusted,
+    /// The `EXTERNAL` mechanism only accepts the UID of the peer credentials of the socket.
+    #[cfg(feature = "daemon")]
+    PeerUid(u32),
+    /// Only the `ANONYMOUS` mechanism is accepted, for example for TCP sockets.
+    #[cfg(feature = "daemon")]
+    Anonymous,
+}
+
+/// The message bus. The bus task runs until the object is dropped.
+pub(crate) struct Bus {
+    events: Sender<Event>,
+    guid: Uuid,
+    next_id: AtomicU64,
+    task: JoinHandle<()>,
+}
+
+impl Bus {
+    /// Spawn the bus task. This has to be called inside the runtime.
+    pub(crate) fn new() -> Bus {
+        let (events, event_receiver) = channel(EVENT_QUEUE_SIZE);
+        let guid = create_guid();
+        let router = Router::new(encode(guid));
+        let task = spawn(router.run(event_receiver));
+        Bus {
+            events,
+            guid,
+            next_id: AtomicU64::new(1),
+            task,
+        }
+    }
+
+    /// Accept a new connection to the bus. The authentication and the connection are handled in
+    /// a separate task.
+    pub(crate) fn accept<T>(&self, stream: T, authentication: Authentication)
+    where
+        T: Transport + 'static,
+    {
+        let unique_name = format!(":1.{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let events = self.events.clone();
+        let guid = encode(self.guid);
+        spawn(serve(stream, authentication, unique_name, events, guid));
+    }
+
+    /// Get the GUID of the bus, which is sent during the authentication.
+    #[cfg(feature = "daemon")]
+    pub(crate) fn get_guid(&self) -> &Uuid {
+        &self.guid
+    }
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Authenticate the client and forward the messages of the client to the bus task until the
+/// connection is closed.
+async fn serve<T>(
+    stream: T,
+    authentication: Authentication,
+    unique_name: String,
+    events: Sender<Event>,
+    guid: String,
+) where
+    T: Transport + 'static,
+{
+    let (mechanisms, peer_uid): (&[AuthMechanism], _) = match authentication {
+        Authentication::Trusted => (&[AuthMechanism::External, AuthMechanism::Anonymous], None),
+        #[cfg(feature = "daemon")]
+        Authentication::PeerUid(uid) => (
+            &[AuthMechanism::External, AuthMechanism::Anonymous],
+            Some(uid),
+        ),
+        #[cfg(feature = "daemon")]
+        Authentication::Anonymous => (&[AuthMechanism::Anonymous], None),
+    };
+    let result = Handshake::accept(stream, &guid, mechanisms, false, peer_uid).await;
+    let (stream, uid) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            debug!("{}: could not authenticate: {}", unique_name, e);
+            return;
+        }
+    };
+    let (mut stream, sink) = stream.split();
+
+    let (sender, receiver) = channel(CONNECTION_QUEUE_SIZE);
+    let sink_task = spawn(message_sink(receiver, sink));
+    let event = Event::Connect {
+        unique_name: unique_name.clone(),
+        sender,
+        uid,
+    };
+    if events.send(event).await.is_err() {
+        return;
+    }
+
+    loop {
+        match read_message(&mut stream, MAXIMUM_MESSAGE_SIZE).await {
+            Ok((msg, flags)) => {
+                if events
+                    .send(Event::Message(unique_name.clone(), Box::new(msg), flags))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            Err(e) => {
+                debug!("{}: connection is closed: {}", unique_name, e);
+                let _ = events.send(Event::Disconnect(unique_name)).await;
+                break;
+            }
+        }
+    }
+    // The sink task stops, if the bus task removed the connection.
+    if let Err(e) = sink_task.await {
+        error!("Sink task: {}", e);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::DBusNameFlag;
+use std::collections::{HashMap, VecDeque};
+
+/// The [reply] of the `RequestName` method.
+///
+/// [reply]: https://dbus.freedesktop.org/doc/dbus-specification.html#bus-messages-request-name
+pub(super) const REQUEST_NAME_REPLY_PRIMARY_OWNER: u32 = 1;
+pub(super) const REQUEST_NAME_REPLY_IN_QUEUE: u32 = 2;
+pub(super) const REQUEST_NAME_REPLY_EXISTS: u32 = 3;
+pub(super) const REQUEST_NAME_REPLY_ALREADY_OWNER: u32 = 4;
+
+/// The [reply] of the `ReleaseName` method.
+///
+/// [reply]: https://dbus.freedesktop.org/doc/dbus-specification.html#bus-messages-release-name
+pub(super) const RELEASE_NAME_REPLY_RELEASED: u32 = 1;
+pub(super) const RELEASE_NAME_REPLY_NON_EXISTENT: u32 = 2;
+pub(super) const RELEASE_NAME_REPLY_NOT_OWNER: u32 = 3;
+
+/// A connection, which owns or waits for a well-known name.
+#[derive(Debug)]
+struct Owner {
+    unique_name: String,
+    flags: DBusNameFlag,
+}
+
+/// The primary owner of a well-known name has changed.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct OwnerChanged {
+    pub(super) name: String,
+    pub(super) old_owner: Option<String>,
+    pub(super) new_owner: Option<String>,
+}
+
+/// The well-known names and the queues of their owners. The first connection of a queue is the
+/// primary owner of the name.
+#[derive(Debug, Default)]
+pub(super) struct Names(HashMap<String, VecDeque<Owner>>);
+
+impl Names {
+    /// Get the primary owner of the well-known name.
+    pub(super) fn get_owner(&self, name: &str) -> Option<&str> {
+        let queue = self.0.get(name)?;
+        let owner = queue.front()?;
+        Some(&owner.unique_name)
+    }
+
+    /// Get all well-known names, which have an owner.
+    pub(super) fn list_names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(|name| name.as_str())
+    }
+
+    /// Get the primary owner and all connections, which wait for the well-known name.
+    pub(super) fn list_queued_owners(&self, name: &str) -> Option<Vec<String>> {
+        let queue = self.0.get(name)?;
+        Some(
+            queue
+                .iter()
+                .map(|owner| owner.unique_name.clone())
+                .collect(),
+        )
+    }
+
+    /// Request the well-known name for the connection like the `RequestName` method.
+    pub(super) fn request(
+        &mut self,
+        name: &str,
+        unique_name: &str,
+        flags: DBusNameFlag,
+    ) -> (u32, Option<OwnerChanged>) {
+        let queue = self.0.entry(name.to_string()).or_default();
+        let owner = Owner {
+            unique_name: unique_name.to_string(),
+            flags,
+        };
+        let primary_owner = match queue.front_mut() {
+            Some(primary_owner) => primary_owner,
+            None => {
+                queue.push_back(owner);
+                let owner_changed = OwnerChanged {
+                    name: name.to_string(),
+                    old_owner: None,
+                    new_owner: Some(unique_name.to_string()),
+                };
+                return (REQUEST_NAME_REPLY_PRIMARY_OWNER, Some(owner_changed));
+            }
+        };
+
+        if primary_owner.unique_name == unique_name {
+            primary_owner.flags = flags;
+            return (REQUEST_NAME_REPLY_ALREADY_OWNER, None);
+        }
+
+        // Remove the connection from the queue, it is added again, if necessary.
+        queue.retain(|owner| owner.unique_name != unique_name);
+        // The unwrap function call will never panic because the primary owner is not removed.
+        let primary_owner = queue.front().unwrap();
+        if primary_owner
+            .flags
+            .contains(DBusNameFlag::ALLOW_REPLACEMENT)
+            && flags.contains(DBusNameFlag::REPLACE_EXISTING)
+        {
+            // The unwrap function call will never panic because the queue is not empty.
+            let old_owner = queue.pop_front().unwrap();
+            let owner_changed = OwnerChanged {
+                name: name.to_string(),
+                old_owner: Some(old_owner.unique_name.clone()),
+                new_owner: Some(unique_name.to_string()),
+            };
+            if !old_owner.flags.contains(DBusNameFlag::DO_NOT_QUEUE) {
+                queue.push_front(old_owner);
+            }
+            queue.push_front(owner);
+            (REQUEST_NAME_REPLY_PRIMARY_OWNER, Some(owner_changed))
+        } else if flags.contains(DBusNameFlag::DO_NOT_QUEUE) {
+            (REQUEST_NAME_REPLY_EXISTS, None)
+        } else {
+            queue.push_back(owner);
+            (REQUEST_NAME_REPLY_IN_QUEUE, None)
+        }
+    }
+
+    /// Release the well-known name of the connection like the `ReleaseName` method.
+    pub(super) fn release(&mut self, name: &str, unique_name: &str) -> (u32, Option<OwnerChanged>) {
+        let queue = match self.0.get_mut(name) {
+            Some(queue) => queue,
+            None => return (RELEASE_NAME_REPLY_NON_EXISTENT, None),
+        };
+        let position = match queue
+            .iter()
+            .position(|owner| owner.unique_name == unique_name)
+        {
+            Some(position) => position,
+            None => return (RELEASE_NAME_REPLY_NOT_OWNER, None),
+        };
+        queue.remove(position);
+        if position != 0 {
+            return (RELEASE_NAME_REPLY_RELEASED, None);
+        }
+
+        let new_owner = queue.front().map(|owner| owner.unique_name.clone());
+        if new_owner.is_none() {
+            self.0.remove(name);
+        }
+        let owner_changed = OwnerChanged {
+            name: name.to_string(),
+            old_owner: Some(unique_name.to_string()),
+            new_owner,
+        };
+        (RELEASE_NAME_REPLY_RELEASED, Some(owner_changed))
+    }
+
+    /// Release all well-known names of a connection, which is disconnected.
+    pub(super) fn release_all(&mut self, unique_name: &str) -> Vec<OwnerChanged> {
+        let names: Vec<String> = self
+            .0
+            .iter()
+            .filter(|(_, queue)| queue.iter().any(|owner| owner.unique_name == unique_name))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names
+            .iter()
+            .filter_map(|name| self.release(name, unique_name).1)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAME: &str = "org.example.Name";
+
+    #[test]
+    fn request_and_release() {
+        let mut names = Names::default();
+        let (reply, owner_changed) = names.request(NAME, ":1.1", DBusNameFlag::empty());
+        assert_eq!(reply, REQUEST_NAME_REPLY_PRIMARY_OWNER);
+        assert_eq!(owner_changed.unwrap().new_owner.as_deref(), Some(":1.1"));
+        let (reply, _) = names.request(NAME, ":1.1", DBusNameFlag::empty());
+        assert_eq!(reply, REQUEST_NAME_REPLY_ALREADY_OWNER);
+        let (reply, _) = names.request(NAME, ":1.2", DBusNameFlag::DO_NOT_QUEUE);
+        assert_eq!(reply, REQUEST_NAME_REPLY_EXISTS);
+        let (reply, _) = names.request(NAME, ":1.2", DBusNameFlag::empty());
+        assert_eq!(reply, REQUEST_NAME_REPLY_IN_QUEUE);
+        assert_eq!(
+            names.list_queued_owners(NAME).unwrap(),
+            vec![":1.1".to_string(), ":1.2".to_string()]
+        );
+
+        let (reply, _) = names.release(NAME, ":1.3");
+        assert_eq!(reply, RELEASE_NAME_REPLY_NOT_OWNER);
+        let (reply, owner_changed) = names.release(NAME, ":1.1");
+        assert_eq!(reply, RELEASE_NAME_REPLY_RELEASED);
+        assert_eq!(
+            owner_changed.unwrap(),
+            OwnerChanged {
+                name: NAME.to_string(),
+                old_owner: Some(":1.1".to_string()),
+                new_owner: Some(":1.2".to_string()),
+            }
+        );
+        assert_eq!(names.release_all(":1.2").len(), 1);
+        assert_eq!(names.get_owner(NAME), None);
+        let (reply, _) = names.release(NAME, ":1.2");
+        assert_eq!(reply, RELEASE_NAME_REPLY_NON_EXISTENT);
+    }
+
+    #[test]
+    fn replace_existing() {
+        let mut names = Names::default();
+        names.request(NAME, ":1.1", DBusNameFlag::empty());
+        let (reply, _) = names.request(NAME, ":1.2", DBusNameFlag::REPLACE_EXISTING);
+        assert_eq!(reply, REQUEST_NAME_REPLY_IN_QUEUE);
+
+        names.request(NAME, ":1.1", DBusNameFlag::ALLOW_REPLACEMENT);
+        let (reply, owner_changed) = names.request(NAME, ":1.2", DBusNameFlag::REPLACE_EXISTING);
+        assert_eq!(reply, REQUEST_NAME_REPLY_PRIMARY_OWNER);
+        assert_eq!(owner_changed.unwrap().old_owner.as_deref(), Some(":1.1"));
+        assert_eq!(
+            names.list_queued_owners(NAME).unwrap(),
+            vec![":1.2".to_string(), ":1.1".to_string()]
+        );
+    }
+}
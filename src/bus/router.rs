@@ -0,0 +1,570 @@
+use super::{
+    names::{Names, OwnerChanged},
+    set_sender, Event, BUS_NAME, PROTOCOL_VERSION,
+};
+use crate::{
+    marshal::{method_return, FromArgs, ToArgs},
+    DBusNameFlag, ErrorMapping, StandardError,
+};
+use dbus_message_parser::{
+    match_rule::MatchRule,
+    message::{Message, MessageFlags, MessageHeader, MessageHeaderFields, MessageType},
+    value::{Bus as BusName, Value},
+};
+use std::{collections::HashMap, convert::TryInto};
+use tokio::sync::mpsc::{error::TrySendError, Receiver, Sender};
+
+/// The object path of the bus.
+const BUS_PATH: &str = "/org/freedesktop/DBus";
+
+/// A connection of the bus.
+#[derive(Debug)]
+struct Peer {
+    sender: Sender<(Message, Option<MessageFlags>)>,
+    uid: Option<u32>,
+    hello: bool,
+    match_rules: Vec<Vec<MatchRule>>,
+}
+
+/// Parse the arguments of a method call to the bus.
+fn parse_args<A: FromArgs>(body: Vec<Value>) -> Result<A, StandardError> {
+    A::from_args(body).map_err(|e| StandardError::InvalidArgs(e.to_string()))
+}
+
+/// Check if the message is a call of the `Hello` method of the bus.
+fn is_hello(msg: &Message) -> bool {
+    msg.get_type() == MessageType::MethodCall
+        && msg.get_destination().is_some_and(|d| d == BUS_NAME)
+        && msg.get_interface().is_some_and(|i| i == BUS_NAME)
+        && msg.get_member().is_some_and(|m| m == "Hello")
+}
+
+/// Check if the sender of the message expects a reply.
+fn expects_reply(msg: &Message, flags: MessageFlags) -> bool {
+    msg.get_type() == MessageType::MethodCall && !flags.contains(MessageFlags::NO_REPLY_EXPECTED)
+}
+
+/// The state of the bus task, which routes all messages.
+pub(super) struct Router {
+    guid: String,
+    serial: u32,
+    peers: HashMap<String, Peer>,
+    names: Names,
+}
+
+impl Router {
+    pub(super) fn new(guid: String) -> Router {
+        Router {
+            guid,
+            serial: 0,
+            peers: HashMap::new(),
+            names: Names::default(),
+        }
+    }
+
+    /// Run the bus task until all connections are closed and the bus is dropped.
+    pub(super) async fn run(mut self, mut events: Receiver<Event>) {
+        while let Some(event) = events.recv().await {
+            match event {
+                Event::Connect {
+                    unique_name,
+                    sender,
+                    uid,
+                } => {
+                    let peer = Peer {
+                        sender,
+                        uid,
+                        hello: false,
+                        match_rules: Vec::new(),
+                    };
+                    self.peers.insert(unique_name, peer);
+                }
+                Event::Message(unique_name, msg, flags) => {
+                    self.receive_message(&unique_name, *msg, flags)
+                }
+                Event::Disconnect(unique_name) => self.disconnect(&unique_name),
+            }
+        }
+    }
+
+    fn next_serial(&mut self) -> u32 {
+        self.serial = self.serial.wrapping_add(1).max(1);
+        self.serial
+    }
+
+    /// Remove the connection and release all names of it.
+    fn disconnect(&mut self, unique_name: &str) {
+        let peer = match self.peers.remove(unique_name) {
+            Some(peer) => peer,
+            None => return,
+        };
+        for owner_changed in self.names.release_all(unique_name) {
+            self.emit_owner_changed(owner_changed);
+        }
+        if peer.hello {
+            self.emit_owner_changed(OwnerChanged {
+                name: unique_name.to_string(),
+                old_owner: Some(unique_name.to_string()),
+                new_owner: None,
+            });
+        }
+    }
+
+    fn receive_message(&mut self, unique_name: &str, msg: Message, flags: MessageFlags) {
+        let hello = match self.peers.get(unique_name) {
+            Some(peer) => peer.hello,
+            None => return,
+        };
+        if !hello && !is_hello(&msg) {
+            debug!("{}: first message is not Hello", unique_name);
+            self.disconnect(unique_name);
+            return;
+        }
+        let msg = match set_sender(msg, unique_name, flags) {
+            Some(msg) => msg,
+            None => {
+                error!("{}: could not set the sender of the message", unique_name);
+                return;
+            }
+        };
+        match msg.get_destination() {
+            Some(destination) if destination == BUS_NAME => self.call_bus(unique_name, msg, flags),
+            Some(_) => self.unicast(msg, flags),
+            None => self.broadcast(msg),
+        }
+    }
+
+    /// Get the unique name of the connection, which owns the name. The name of the bus is owned
+    /// by the bus itself.
+    fn resolve(&self, name: &str) -> Option<&str> {
+        if name == BUS_NAME {
+            Some(BUS_NAME)
+        } else if name.starts_with(':') {
+            self.peers
+                .get_key_value(name)
+                .filter(|(_, peer)| peer.hello)
+                .map(|(unique_name, _)| unique_name.as_str())
+        } else {
+            self.names.get_owner(name)
+        }
+    }
+
+    /// Send the message to the connection. If the queue of the connection is full then the
+    /// message is returned.
+    fn deliver(peer: &Peer, msg: Message) -> Option<Message> {
+        // The flags of the message are already set, when the sender was set.
+        match peer.sender.try_send((msg, None)) {
+            Ok(()) => None,
+            Err(TrySendError::Full((msg, _))) => {
+                error!("queue of the connection is full: {:?}", msg);
+                Some(msg)
+            }
+            Err(TrySendError::Closed(_)) => {
+                debug!("connection is closed");
+                None
+            }
+        }
+    }
+
+    /// Send the message to the connection, which owns the destination of the message.
+    fn unicast(&mut self, msg: Message, flags: MessageFlags) {
+        // The unwrap function call will never panic because only messages with a destination are
+        // sent to a single connection.
+        let destination = msg.get_destination().unwrap().to_string();
+        let peer = self
+            .resolve(&destination)
+            .and_then(|unique_name| self.peers.get(unique_name));
+        if let Some(peer) = peer {
+            // The message is dropped, if the queue is full. If the sender expects a reply then the
+            // sender gets an error instead of waiting for the reply.
+            match Router::deliver(peer, msg) {
+                Some(msg) if expects_reply(&msg, flags) => {
+                    let error = StandardError::LimitsExceeded(format!(
+                        "The queue of the connection, which owns the name {}, is full",
+                        destination
+                    ));
+                    let (name, message) = error.to_error();
+                    self.send_from_bus(msg.error(name, message), MessageFlags::NO_REPLY_EXPECTED);
+                }
+                _ => {}
+            }
+        } else if expects_reply(&msg, flags) {
+            let error = StandardError::ServiceUnknown(format!(
+                "The name {} was not provided by any .service files",
+                destination
+            ));
+            let (name, message) = error.to_error();
+            self.send_from_bus(msg.error(name, message), MessageFlags::NO_REPLY_EXPECTED);
+        }
+    }
+
+    /// Check if the message matches all rules. The rules with a well-known name as the sender
+    /// match the messages of the primary owner of the name.
+    fn is_matching(&self, match_rules: &[MatchRule], msg: &Message) -> bool {
+        match_rules.iter().all(|match_rule| match match_rule {
+            MatchRule::Sender(sender @ BusName::WellKnownBusName(_)) => {
+                match (self.names.get_owner(sender.as_ref()), msg.get_sender()) {
+                    (Some(owner), Some(msg_sender)) => msg_sender == owner,
+                    _ => match_rule.matching_rule(msg),
+                }
+            }
+            match_rule => match_rule.matching_rule(msg),
+        })
+    }
+
+    /// Send the message to all connections, which added a matching rule.
+    fn broadcast(&mut self, msg: Message) {
+        for peer in self.peers.values().filter(|peer| peer.hello) {
+            let is_matching = peer
+                .match_rules
+                .iter()
+                .any(|match_rules| self.is_matching(match_rules, &msg));
+            if is_matching {
+                // The signals do not expect a reply, so they are just dropped, if the queue is
+                // full.
+                Router::deliver(peer, msg.clone());
+            }
+        }
+    }
+
+    /// Send a message, which is created by the bus with the given flags.
+    fn send_from_bus(&mut self, mut msg: Message, flags: MessageFlags) {
+        msg.set_serial(self.next_serial());
+        let msg = match set_sender(msg, BUS_NAME, flags) {
+            Some(msg) => msg,
+            None => {
+                error!("could not set the sender of the message");
+                return;
+            }
+        };
+        if msg.get_destination().is_some() {
+            self.unicast(msg, flags);
+        } else {
+            self.broadcast(msg);
+        }
+    }
+
+    /// Create a signal of the bus.
+    fn signal<A: ToArgs>(&mut self, member: &str, destination: Option<&str>, args: A) -> Message {
+        // The unwrap function calls will never panic because all values are valid.
+        let fields = MessageHeaderFields {
+            path: Some(BUS_PATH.try_into().unwrap()),
+            interface: Some(BUS_NAME.try_into().unwrap()),
+            member: Some(member.try_into().unwrap()),
+            destination: destination.map(|destination| destination.try_into().unwrap()),
+            ..Default::default()
+        };
+        let header = MessageHeader::new(
+            true,
+            MessageType::Signal,
+            MessageFlags::empty(),
+            PROTOCOL_VERSION,
+            self.next_serial(),
+            fields,
+        )
+        .unwrap();
+        Message::new(header, args.to_args().unwrap())
+    }
+
+    /// Send the `NameOwnerChanged` signal to all connections and the `NameLost` and the
+    /// `NameAcquired` signals to the old and the new owner.
+    fn emit_owner_changed(&mut self, owner_changed: OwnerChanged) {
+        let OwnerChanged {
+            name,
+            old_owner,
+            new_owner,
+        } = owner_changed;
+        if let Some(old_owner) = old_owner.as_deref() {
+            let signal = self.signal("NameLost", Some(old_owner), (name.clone(),));
+            self.send_from_bus(signal, MessageFlags::empty());
+        }
+        let args = (
+            name.clone(),
+            old_owner.unwrap_or_default(),
+            new_owner.clone().unwrap_or_default(),
+        );
+        let signal = self.signal("NameOwnerChanged", None, args);
+        self.send_from_bus(signal, MessageFlags::empty());
+        if let Some(new_owner) = new_owner.as_deref() {
+            let signal = self.signal("NameAcquired", Some(new_owner), (name,));
+            self.send_from_bus(signal, MessageFlags::empty());
+        }
+    }
+
+    /// Handle a method call to the bus.
+    fn call_bus(&mut self, unique_name: &str, msg: Message, flags: MessageFlags) {
+        let expects_reply = expects_reply(&msg, flags);
+        let (header, body) = match msg.split() {
+            Ok(result) => result,
+            Err(e) => {
+                error!("{}: could not split message: {}", unique_name, e);
+                return;
+            }
+        };
+        if header.get_type() != MessageType::MethodCall {
+            return;
+        }
+        let (reply, owner_changed) = match self.call_method(unique_name, &header, body) {
+            Ok(result) => result,
+            Err(error) => {
+                let (name, message) = error.to_error();
+                (header.error(name, message), None)
+            }
+        };
+        if expects_reply {
+            // The replies and the errors do not expect a reply.
+            self.send_from_bus(reply, MessageFlags::NO_REPLY_EXPECTED);
+        }
+        if let Some(owner_changed) = owner_changed {
+            self.emit_owner_changed(owner_changed);
+        }
+    }
+
+    fn call_method(
+        &mut self,
+        unique_name: &str,
+        header: &MessageHeader,
+        body: Vec<Value>,
+    ) -> Result<(Message, Option<OwnerChanged>), StandardError> {
+        let interface = header.get_interface().map(|interface| interface.as_ref());
+        let member = header.get_member().map(|member| member.as_ref());
+        let member = match (interface, member) {
+            (Some("org.freedesktop.DBus.Peer"), Some("Ping")) => {
+                return Ok((method_return(header, ()), None));
+            }
+            (Some(BUS_NAME), Some(member)) | (None, Some(member)) => member,
+            _ => {
+                return Err(StandardError::UnknownMethod(format!(
+                    "{}.{} does not exist",
+                    interface.unwrap_or(BUS_NAME),
+                    member.unwrap_or_default()
+                )))
+            }
+        };
+        let reply = match member {
+            "Hello" => {
+                return self.hello(unique_name, header);
+            }
+            "RequestName" => {
+                let (name, flags): (String, u32) = parse_args(body)?;
+                return self.request_name(unique_name, header, name, flags);
+            }
+            "ReleaseName" => {
+                let (name,): (String,) = parse_args(body)?;
+                let (reply, owner_changed) = self.names.release(&name, unique_name);
+                return Ok((method_return(header, (reply,)), owner_changed));
+            }
+            "GetNameOwner" => {
+                let (name,): (String,) = parse_args(body)?;
+                match self.resolve(&name) {
+                    Some(owner) => method_return(header, (owner.to_string(),)),
+                    None => {
+                        return Err(StandardError::NameHasNoOwner(format!(
+                            "Could not get owner of name '{}': no such name",
+                            name
+                        )))
+                    }
+                }
+            }
+            "NameHasOwner" => {
+                let (name,): (String,) = parse_args(body)?;
+                method_return(header, (self.resolve(&name).is_some(),))
+            }
+            "ListNames" => {
+                let mut names = vec![BUS_NAME.to_string()];
+                names.extend(
+                    self.peers
+                        .iter()
+                        .filter(|(_, peer)| peer.hello)
+                        .map(|(unique_name, _)| unique_name.clone()),
+                );
+                names.extend(self.names.list_names().map(|name| name.to_string()));
+                method_return(header, (names,))
+            }
+            "ListQueuedOwners" => {
+                let (name,): (String,) = parse_args(body)?;
+                match self.names.list_queued_owners(&name) {
+                    Some(owners) => method_return(header, (owners,)),
+                    None => {
+                        return Err(StandardError::NameHasNoOwner(format!(
+                            "Could not get owners of name '{}': no such name",
+                            name
+                        )))
+                    }
+                }
+            }
+            "AddMatch" => {
+                let (match_rule,): (String,) = parse_args(body)?;
+                let match_rules = MatchRule::decode(&match_rule)
+                    .map_err(|e| StandardError::MatchRuleInvalid(e.to_string()))?;
+                self.get_peer(unique_name)?.match_rules.push(match_rules);
+                method_return(header, ())
+            }
+            "RemoveMatch" => {
+                let (match_rule,): (String,) = parse_args(body)?;
+                let match_rules = MatchRule::decode(&match_rule)
+                    .map_err(|e| StandardError::MatchRuleInvalid(e.to_string()))?;
+                let peer = self.get_peer(unique_name)?;
+                match peer.match_rules.iter().position(|m| m == &match_rules) {
+                    Some(position) => {
+                        peer.match_rules.remove(position);
+                        method_return(header, ())
+                    }
+                    None => {
+                        return Err(StandardError::MatchRuleNotFound(
+                            "The given match rule wasn't found and can't be removed".to_string(),
+                        ))
+                    }
+                }
+            }
+            "GetConnectionUnixUser" => {
+                let (name,): (String,) = parse_args(body)?;
+                // The bus itself has no connection, so its UID is unknown.
+                let peer = self
+                    .resolve(&name)
+                    .map(|unique_name| self.peers.get(unique_name));
+                match peer {
+                    Some(Some(Peer { uid: Some(uid), .. })) => method_return(header, (*uid,)),
+                    Some(_) => {
+                        return Err(StandardError::Failed(format!(
+                            "Could not determine UID for '{}'",
+                            name
+                        )))
+                    }
+                    None => {
+                        return Err(StandardError::NameHasNoOwner(format!(
+                            "Could not get UID of name '{}': no such name",
+                            name
+                        )))
+                    }
+                }
+            }
+            "GetId" => method_return(header, (self.guid.clone(),)),
+            member => {
+                return Err(StandardError::UnknownMethod(format!(
+                    "{}.{} does not exist",
+                    BUS_NAME, member
+                )))
+            }
+        };
+        Ok((reply, None))
+    }
+
+    fn get_peer(&mut self, unique_name: &str) -> Result<&mut Peer, StandardError> {
+        self.peers
+            .get_mut(unique_name)
+            .ok_or_else(|| StandardError::Disconnected("Connection is closed".to_string()))
+    }
+
+    fn hello(
+        &mut self,
+        unique_name: &str,
+        header: &MessageHeader,
+    ) -> Result<(Message, Option<OwnerChanged>), StandardError> {
+        let peer = self.get_peer(unique_name)?;
+        if peer.hello {
+            return Err(StandardError::Failed(
+                "Already handled an Hello message".to_string(),
+            ));
+        }
+        peer.hello = true;
+        let owner_changed = OwnerChanged {
+            name: unique_name.to_string(),
+            old_owner: None,
+            new_owner: Some(unique_name.to_string()),
+        };
+        let reply = method_return(header, (unique_name.to_string(),));
+        Ok((reply, Some(owner_changed)))
+    }
+
+    fn request_name(
+        &mut self,
+        unique_name: &str,
+        header: &MessageHeader,
+        name: String,
+        flags: u32,
+    ) -> Result<(Message, Option<OwnerChanged>), StandardError> {
+        let name: BusName = name
+            .as_str()
+            .try_into()
+            .map_err(|e| StandardError::InvalidArgs(format!("Invalid name '{}': {}", name, e)))?;
+        let name = match name {
+            BusName::WellKnownBusName(name) if name != *BUS_NAME => name,
+            name => {
+                return Err(StandardError::InvalidArgs(format!(
+                    "Cannot acquire the name '{}'",
+                    name
+                )))
+            }
+        };
+        let flags = DBusNameFlag::from_bits_truncate(flags);
+        let (reply, owner_changed) = self.names.request(name.as_ref(), unique_name, flags);
+        Ok((method_return(header, (reply,)), owner_changed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::{channel, Receiver};
+
+    fn connect(
+        router: &mut Router,
+        unique_name: &str,
+    ) -> Receiver<(Message, Option<MessageFlags>)> {
+        let (sender, receiver) = channel(1);
+        let peer = Peer {
+            sender,
+            uid: None,
+            hello: true,
+            match_rules: Vec::new(),
+        };
+        router.peers.insert(unique_name.to_string(), peer);
+        receiver
+    }
+
+    fn method_call(sender: &str, destination: &str, flags: MessageFlags) -> Message {
+        let msg = Message::method_call(
+            destination.try_into().unwrap(),
+            "/org/example".try_into().unwrap(),
+            "org.example.Interface".try_into().unwrap(),
+            "Method".try_into().unwrap(),
+        );
+        set_sender(msg, sender, flags).unwrap()
+    }
+
+    #[test]
+    fn queue_full() {
+        let mut router = Router::new(String::new());
+        let mut caller = connect(&mut router, ":1.1");
+        let mut callee = connect(&mut router, ":1.2");
+
+        // Fill the queue of the callee.
+        router.unicast(
+            method_call(":1.1", ":1.2", MessageFlags::empty()),
+            MessageFlags::empty(),
+        );
+        assert!(caller.try_recv().is_err());
+
+        // The method call without a reply is dropped silently.
+        let flags = MessageFlags::NO_REPLY_EXPECTED;
+        router.unicast(method_call(":1.1", ":1.2", flags), flags);
+        assert!(caller.try_recv().is_err());
+
+        // The caller, which expects a reply, gets an error.
+        router.unicast(
+            method_call(":1.1", ":1.2", MessageFlags::empty()),
+            MessageFlags::empty(),
+        );
+        let (error, _) = caller.try_recv().unwrap();
+        assert_eq!(error.get_type(), MessageType::Error);
+        assert_eq!(
+            error.get_error_name().unwrap().as_ref(),
+            "org.freedesktop.DBus.Error.LimitsExceeded"
+        );
+        assert_eq!(error.get_destination().unwrap(), ":1.1");
+
+        assert!(callee.try_recv().is_ok());
+        assert!(callee.try_recv().is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use dbus_message_parser::message::MessageFlags;
+
+/// The options of an outgoing message, which set the [message flags].
+///
+/// The flags are written into the header, when the message is encoded.
+///
+/// [message flags]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-protocol-messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallOptions {
+    flags: MessageFlags,
+}
+
+impl Default for CallOptions {
+    fn default() -> Self {
+        CallOptions {
+            flags: MessageFlags::empty(),
+        }
+    }
+}
+
+impl CallOptions {
+    /// Create the options without any flags.
+    pub fn new() -> CallOptions {
+        CallOptions::default()
+    }
+
+    /// Set the `NO_REPLY_EXPECTED` flag. The connection does not wait for a reply and the callee
+    /// does not send a reply.
+    pub fn no_reply_expected(mut self, value: bool) -> CallOptions {
+        self.flags.set(MessageFlags::NO_REPLY_EXPECTED, value);
+        self
+    }
+
+    /// Set the `NO_AUTO_START` flag. The bus does not start the owner of the destination name, if
+    /// there is none.
+    pub fn no_auto_start(mut self, value: bool) -> CallOptions {
+        self.flags.set(MessageFlags::NO_AUTO_START, value);
+        self
+    }
+
+    /// Set the `ALLOW_INTERACTIVE_AUTHORIZATION` flag. The callee may prompt the user for an
+    /// authorization, which can take a long time.
+    pub fn allow_interactive_authorization(mut self, value: bool) -> CallOptions {
+        self.flags
+            .set(MessageFlags::ALLOW_INTERACTIVE_AUTHORIZATION, value);
+        self
+    }
+
+    /// Get the [`MessageFlags`] of the options.
+    ///
+    /// [`MessageFlags`]: dbus_message_parser::message::MessageFlags
+    pub fn get_flags(&self) -> MessageFlags {
+        self.flags
+    }
+}
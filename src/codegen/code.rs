@@ -0,0 +1,40 @@
+/// A buffer for the generated code, which keeps track of the indentation.
+pub(super) struct Code {
+    code: String,
+    depth: usize,
+}
+
+impl Code {
+    pub(super) fn new() -> Code {
+        Code {
+            code: String::new(),
+            depth: 0,
+        }
+    }
+
+    /// Add a line. A line, which starts with a closing bracket, decreases the indentation and a
+    /// line, which ends with an opening bracket, increases the indentation.
+    pub(super) fn line(&mut self, line: &str) {
+        if line.starts_with(['}', ')', ']']) {
+            self.depth = self.depth.saturating_sub(1);
+        }
+        if !line.is_empty() {
+            for _ in 0..self.depth {
+                self.code.push_str("    ");
+            }
+            self.code.push_str(line);
+        }
+        self.code.push('\n');
+        if line.ends_with(['{', '(', '[']) {
+            self.depth += 1;
+        }
+    }
+
+    pub(super) fn doc(&mut self, doc: &str) {
+        self.line(&format!("/// {}", doc));
+    }
+
+    pub(super) fn into_string(self) -> String {
+        self.code
+    }
+}
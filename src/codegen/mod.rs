@@ -0,0 +1,254 @@
+//! Generate typed async proxies and server traits from [introspection data].
+//!
+//! The generator can be used in a `build.rs`:
+//! ```no_run
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! let output = std::path::Path::new(&out_dir).join("interface.rs");
+//! dbus_async::codegen::generate_file("interface.xml", output).unwrap();
+//! ```
+//! and the generated code can be included with
+//! `include!(concat!(env!("OUT_DIR"), "/interface.rs"));`.
+//!
+//! Alternatively, the `dbus-async-codegen` binary writes the generated code to the standard
+//! output.
+//!
+//! For every interface `org.example.Name` the following items are generated:
+//! * `NameProxy`: a client, which calls the methods and gets and sets the properties through
+//!   [`DBus::call`].
+//! * `Name`: a trait, which has to be implemented by the server object.
+//! * `handle_name`: a function, which dispatches a method call to an object, which implements the
+//!   trait (like [`handle_peer`]). There is no reply to the methods with the
+//!   `org.freedesktop.DBus.Method.NoReply` annotation and to the method calls with the
+//!   `NO_REPLY_EXPECTED` flag.
+//! * `NameServer`: a wrapper, which implements the [`Handler`] trait, so it can be bound to an
+//!   object path with the [`Binder`] trait.
+//! * `emit_name_signal`: a function for every signal to emit it.
+//!
+//! The generated code uses the crates, which are re-exported by the [`export`] module, so the
+//! crate, which includes the generated code, only has to depend on `dbus-async`.
+//!
+//! Only basic types are mapped to Rust types, all other types are represented as [`Value`].
+//!
+//! The methods are named in snake case (`GetDevices` becomes `get_devices`) and the properties
+//! have the accessors `get_` and `set_`. If a name collides with another method, an accessor or
+//! the constructor `new` then a method gets the prefix `call_` (`call_new`) and a property gets
+//! the prefix `property_` (`get_property_devices`).
+//!
+//! [introspection data]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
+//! [`DBus::call`]: crate::DBus::call
+//! [`handle_peer`]: crate::handle_peer
+//! [`Handler`]: crate::Handler
+//! [`Binder`]: crate::Binder
+//! [`Value`]: dbus_message_parser::value::Value
+//! [`export`]: crate::export
+mod code;
+mod name;
+mod proxy;
+mod server;
+mod r#type;
+
+use crate::introspect::{Access, Annotation, Arg, Direction, Interface, IntrospectError, Node};
+use code::Code;
+use name::snake_case;
+use r#type::RustType;
+use std::{
+    fs::{read_to_string, write},
+    io::Error as IoError,
+    path::Path,
+};
+use thiserror::Error;
+
+const DEPRECATED: &str = "org.freedesktop.DBus.Deprecated";
+const NO_REPLY: &str = "org.freedesktop.DBus.Method.NoReply";
+const EMITS_CHANGED_SIGNAL: &str = "org.freedesktop.DBus.Property.EmitsChangedSignal";
+
+/// The identifiers, which are used by the generated code next to the arguments.
+const RESERVED: &[&str] = &[
+    "body",
+    "dbus",
+    "header",
+    "interface",
+    "member",
+    "msg",
+    "object",
+    "object_path",
+    "reply",
+    "response",
+    "signal",
+    "unknown_interface",
+    "value",
+];
+
+/// The interfaces, which are already implemented by this crate.
+const STANDARD_INTERFACES: &[&str] = &[
+    "org.freedesktop.DBus.Introspectable",
+    "org.freedesktop.DBus.Peer",
+    "org.freedesktop.DBus.Properties",
+];
+
+#[derive(Debug, Error)]
+pub enum CodegenError {
+    #[error("IO Error: {0}")]
+    IoError(#[from] IoError),
+    #[error("Could not decode introspection data: {0}")]
+    IntrospectError(#[from] IntrospectError),
+}
+
+fn annotation<'a>(annotations: &'a [Annotation], name: &str) -> Option<&'a str> {
+    annotations
+        .iter()
+        .find(|annotation| annotation.name == name)
+        .map(|annotation| annotation.value.as_str())
+}
+
+fn is_deprecated(annotations: &[Annotation]) -> bool {
+    annotation(annotations, DEPRECATED) == Some("true")
+}
+
+fn deprecated(code: &mut Code, annotations: &[Annotation]) {
+    if is_deprecated(annotations) {
+        code.line("#[deprecated]");
+    }
+}
+
+/// Get the Rust names and the types of the arguments with the given direction.
+///
+/// The arguments of a method have the direction `in` by default and the arguments of a signal
+/// have the direction `out` by default.
+fn args(args: &[Arg], direction: Direction, default: Direction) -> Vec<(String, RustType)> {
+    let mut result: Vec<(String, RustType)> = Vec::new();
+    for (i, arg) in args.iter().enumerate() {
+        if arg.direction.unwrap_or(default) != direction {
+            continue;
+        }
+        let mut name = match &arg.name {
+            Some(name) => snake_case(name),
+            None => format!("arg{}", i),
+        };
+        // The name has to be unique and must not be the same as an identifier of the generated
+        // code.
+        if RESERVED.contains(&name.as_str()) || result.iter().any(|(other, _)| other == &name) {
+            name = format!("{}_{}", name.trim_start_matches("r#"), i);
+        }
+        result.push((name, RustType::new(&arg.r#type)));
+    }
+    result
+}
+
+/// The Rust names of the methods and the properties of an interface.
+///
+/// The methods and the accessors of the properties (`get_` and `set_`) are generated in the same
+/// `impl` block and trait, so their names have to be unique and must not collide with the
+/// constructor of the proxy (`new`). A colliding method gets the prefix `call_` and a colliding
+/// property gets the prefix `property_`. If the name is still not unique then `_` is appended.
+/// The methods are named first, so a method keeps its name, if it collides with a property.
+struct Names {
+    methods: Vec<String>,
+    properties: Vec<String>,
+}
+
+impl Names {
+    fn new(interface: &Interface) -> Names {
+        let mut taken = vec!["new".to_string()];
+
+        let mut methods = Vec::with_capacity(interface.methods.len());
+        for method in &interface.methods {
+            let mut name = snake_case(&method.name);
+            if taken.contains(&name) {
+                name = format!("call_{}", name.trim_start_matches("r#"));
+            }
+            while taken.contains(&name) {
+                name.push('_');
+            }
+            taken.push(name.clone());
+            methods.push(name);
+        }
+
+        let mut properties = Vec::with_capacity(interface.properties.len());
+        for property in &interface.properties {
+            let accessors = |name: &str| {
+                let mut accessors = Vec::with_capacity(2);
+                if property.access != Access::Write {
+                    accessors.push(format!("get_{}", name));
+                }
+                if property.access != Access::Read {
+                    accessors.push(format!("set_{}", name));
+                }
+                accessors
+            };
+            let is_taken = |name: &str| accessors(name).iter().any(|name| taken.contains(name));
+            let mut name = snake_case(&property.name)
+                .trim_start_matches("r#")
+                .to_string();
+            if is_taken(&name) {
+                name = format!("property_{}", name);
+            }
+            while is_taken(&name) {
+                name.push('_');
+            }
+            taken.extend(accessors(&name));
+            properties.push(name);
+        }
+
+        Names {
+            methods,
+            properties,
+        }
+    }
+}
+
+fn collect_interfaces<'a>(node: &'a Node, interfaces: &mut Vec<&'a Interface>) {
+    for interface in &node.interfaces {
+        if STANDARD_INTERFACES.contains(&interface.name.as_str()) {
+            continue;
+        }
+        if interfaces.iter().all(|other| other.name != interface.name) {
+            interfaces.push(interface);
+        }
+    }
+    for node in &node.nodes {
+        collect_interfaces(node, interfaces);
+    }
+}
+
+/// Generate the code for all interfaces of the given [`Node`] and all child nodes.
+///
+/// The standard interfaces (`Introspectable`, `Peer` and `Properties`) are skipped.
+///
+/// [`Node`]: crate::introspect::Node
+pub fn generate_node(node: &Node) -> String {
+    let mut interfaces = Vec::new();
+    collect_interfaces(node, &mut interfaces);
+
+    let mut code = Code::new();
+    code.line("// This file was generated by dbus-async-codegen. Do not edit.");
+    code.line("#[allow(unused_imports)]");
+    code.line("use std::convert::TryInto;");
+    for interface in interfaces {
+        let names = Names::new(interface);
+        code.line("");
+        proxy::proxy(&mut code, interface, &names);
+        code.line("");
+        server::server(&mut code, interface, &names);
+    }
+    code.into_string()
+}
+
+/// Generate the code for the given introspection data.
+pub fn generate(xml: &str) -> Result<String, CodegenError> {
+    let node = Node::decode(xml)?;
+    Ok(generate_node(&node))
+}
+
+/// Read the introspection data from the `input` file and write the generated code to the
+/// `output` file.
+pub fn generate_file<P, Q>(input: P, output: Q) -> Result<(), CodegenError>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let xml = read_to_string(input)?;
+    let code = generate(&xml)?;
+    write(output, code)?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
+    "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Convert a D-Bus name (for example `GetManagedObjects`) to a snake case Rust identifier
+/// (`get_managed_objects`).
+pub(super) fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::with_capacity(name.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i != 0 {
+                let prev = chars[i - 1];
+                let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+                if prev.is_ascii_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_ascii_uppercase() && next_is_lower)
+                {
+                    result.push('_');
+                }
+            }
+            result.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            result.push(*c);
+        } else {
+            result.push('_');
+        }
+    }
+    identifier(result)
+}
+
+/// Convert a D-Bus name (for example `network_manager`) to an upper camel case Rust identifier
+/// (`NetworkManager`).
+pub(super) fn camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper = true;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if upper {
+                result.push(c.to_ascii_uppercase());
+                upper = false;
+            } else {
+                result.push(c);
+            }
+        } else {
+            upper = true;
+        }
+    }
+    identifier(result)
+}
+
+/// Get the Rust type name of an interface. This is the last element of the interface name.
+pub(super) fn interface_name(interface: &str) -> String {
+    let last = interface.rsplit('.').next().unwrap_or(interface);
+    camel_case(last)
+}
+
+/// The keywords, which cannot be used as raw identifiers.
+const NOT_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+fn identifier(mut name: String) -> String {
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if NOT_RAW_KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    } else if KEYWORDS.contains(&name.as_str()) {
+        name.insert_str(0, "r#");
+    }
+    name
+}
@@ -0,0 +1,175 @@
+use super::{
+    annotation, args, deprecated,
+    name::interface_name,
+    r#type::{tuple_type, RustType},
+    Code, Names, NO_REPLY,
+};
+use crate::introspect::{Access, Direction, Interface, Method, Property};
+
+fn method(code: &mut Code, method: &Method, name: &str) {
+    let inputs = args(&method.args, Direction::In, Direction::In);
+    let outputs = args(&method.args, Direction::Out, Direction::In);
+    let no_reply = annotation(&method.annotations, NO_REPLY) == Some("true");
+
+    let mut parameters = vec!["&self".to_string()];
+    for (name, type_) in inputs.iter() {
+        parameters.push(format!("{}: {}", name, type_.name));
+    }
+    let output_types: Vec<RustType> = outputs.into_iter().map(|(_, type_)| type_).collect();
+    let output = if no_reply {
+        "()".to_string()
+    } else {
+        tuple_type(&output_types)
+    };
+
+    code.doc(&format!("Call the `{}` method.", method.name));
+    code.line("#[allow(clippy::too_many_arguments)]");
+    deprecated(code, &method.annotations);
+    code.line(&format!(
+        "pub async fn {}({}) -> dbus_async::DBusResult<{}> {{",
+        name,
+        parameters.join(", "),
+        output
+    ));
+    code.line("#[allow(unused_mut)]");
+    code.line(
+        "let mut msg = dbus_async::export::dbus_message_parser::message::Message::method_call(",
+    );
+    code.line("self.destination.clone(),");
+    code.line("self.object_path.clone(),");
+    code.line("Self::INTERFACE.try_into().unwrap(),");
+    code.line(&format!("\"{}\".try_into().unwrap(),", method.name));
+    code.line(");");
+    for (name, type_) in inputs.iter() {
+        code.line(&format!("msg.add_value({});", type_.to_value(name)));
+    }
+    if no_reply {
+        code.line("let options = dbus_async::CallOptions::new().no_reply_expected(true);");
+        code.line("self.dbus.send_with_options(msg, &options).await");
+        code.line("}");
+        return;
+    }
+    code.line("let reply = self.dbus.call_checked(msg).await?;");
+    code.line("match reply.get_body() {");
+    let bindings: Vec<String> = (0..output_types.len())
+        .map(|i| format!("value_{}", i))
+        .collect();
+    let patterns: Vec<String> = output_types
+        .iter()
+        .zip(bindings.iter())
+        .map(|(type_, binding)| type_.pattern(binding))
+        .collect();
+    let values: Vec<String> = output_types
+        .iter()
+        .zip(bindings.iter())
+        .map(|(type_, binding)| type_.take(binding))
+        .collect();
+    let value = match values.as_slice() {
+        [value] => value.clone(),
+        values => format!("({})", values.join(", ")),
+    };
+    code.line(&format!("[{}] => Ok({}),", patterns.join(", "), value));
+    code.line("_ => Err(dbus_async::DBusError::InvalidReply(Box::new(reply))),");
+    code.line("}");
+    code.line("}");
+}
+
+fn property(code: &mut Code, property: &Property, name: &str) {
+    let type_ = RustType::new(&property.r#type);
+
+    if property.access != Access::Write {
+        code.doc(&format!("Get the `{}` property.", property.name));
+        deprecated(code, &property.annotations);
+        code.line(&format!(
+            "pub async fn get_{}(&self) -> dbus_async::DBusResult<{}> {{",
+            name, type_.name
+        ));
+        code.line(
+            "let msg = dbus_async::export::dbus_message_parser::message::Message::property_get(",
+        );
+        code.line("self.destination.clone(),");
+        code.line("self.object_path.clone(),");
+        code.line("Self::INTERFACE.try_into().unwrap(),");
+        code.line(&format!("\"{}\",", property.name));
+        code.line(");");
+        code.line("let reply = self.dbus.call_checked(msg).await?;");
+        code.line(
+            "if let [dbus_async::export::dbus_message_parser::value::Value::Variant(value)] = reply.get_body() {",
+        );
+        if type_.is_value() {
+            code.line("return Ok(value.as_ref().clone());");
+        } else {
+            code.line(&format!(
+                "if let {} = value.as_ref() {{",
+                type_.pattern("value")
+            ));
+            code.line(&format!("return Ok({});", type_.take("value")));
+            code.line("}");
+        }
+        code.line("}");
+        code.line("Err(dbus_async::DBusError::InvalidReply(Box::new(reply)))");
+        code.line("}");
+    }
+
+    if property.access != Access::Read {
+        code.doc(&format!("Set the `{}` property.", property.name));
+        deprecated(code, &property.annotations);
+        code.line(&format!(
+            "pub async fn set_{}(&self, value: {}) -> dbus_async::DBusResult<()> {{",
+            name, type_.name
+        ));
+        code.line(
+            "let msg = dbus_async::export::dbus_message_parser::message::Message::property_set(",
+        );
+        code.line("self.destination.clone(),");
+        code.line("self.object_path.clone(),");
+        code.line("Self::INTERFACE.try_into().unwrap(),");
+        code.line(&format!("\"{}\",", property.name));
+        code.line(&format!("{},", type_.to_value("value")));
+        code.line(");");
+        code.line("self.dbus.call_checked(msg).await?;");
+        code.line("Ok(())");
+        code.line("}");
+    }
+}
+
+pub(super) fn proxy(code: &mut Code, interface: &Interface, names: &Names) {
+    let name = format!("{}Proxy", interface_name(&interface.name));
+    code.doc(&format!("A proxy for the `{}` interface.", interface.name));
+    deprecated(code, &interface.annotations);
+    code.line("#[derive(Clone)]");
+    code.line(&format!("pub struct {} {{", name));
+    code.line("dbus: dbus_async::DBus,");
+    code.line("destination: dbus_async::export::dbus_message_parser::value::Bus,");
+    code.line("object_path: dbus_async::export::dbus_message_parser::value::ObjectPath,");
+    code.line("}");
+    code.line("");
+    code.line("#[allow(deprecated)]");
+    code.line(&format!("impl {} {{", name));
+    code.line(&format!(
+        "pub const INTERFACE: &'static str = \"{}\";",
+        interface.name
+    ));
+    code.line("");
+    code.doc("Create a proxy for the object on the given destination.");
+    code.line("pub fn new(");
+    code.line("dbus: dbus_async::DBus,");
+    code.line("destination: dbus_async::export::dbus_message_parser::value::Bus,");
+    code.line("object_path: dbus_async::export::dbus_message_parser::value::ObjectPath,");
+    code.line(&format!(") -> {} {{", name));
+    code.line(&format!("{} {{", name));
+    code.line("dbus,");
+    code.line("destination,");
+    code.line("object_path,");
+    code.line("}");
+    code.line("}");
+    for (m, name) in interface.methods.iter().zip(&names.methods) {
+        code.line("");
+        method(code, m, name);
+    }
+    for (p, name) in interface.properties.iter().zip(&names.properties) {
+        code.line("");
+        property(code, p, name);
+    }
+    code.line("}");
+}
@@ -0,0 +1,458 @@
+use super::{
+    annotation, args, deprecated,
+    name::{interface_name, snake_case},
+    r#type::{tuple_type, RustType},
+    Code, Names, EMITS_CHANGED_SIGNAL, NO_REPLY,
+};
+use crate::introspect::{Access, Direction, Interface, Method, Property, Signal};
+
+const ERROR: &str = "(dbus_async::export::dbus_message_parser::value::Error, String)";
+const DICT_TYPE: &str = "dbus_async::export::dbus_message_parser::value::Type::DictEntry(Box::new((\
+    dbus_async::export::dbus_message_parser::value::Type::String, dbus_async::export::dbus_message_parser::value::Type::Variant)))";
+
+/// Get the value of the `EmitsChangedSignal` annotation. The annotation of the property overrides
+/// the annotation of the interface.
+fn emits_changed_signal<'a>(interface: &'a Interface, property: &'a Property) -> &'a str {
+    annotation(&property.annotations, EMITS_CHANGED_SIGNAL)
+        .or_else(|| annotation(&interface.annotations, EMITS_CHANGED_SIGNAL))
+        .unwrap_or("true")
+}
+
+fn trait_method(code: &mut Code, method: &Method, name: &str) {
+    let inputs = args(&method.args, Direction::In, Direction::In);
+    let outputs = args(&method.args, Direction::Out, Direction::In);
+    let mut parameters = vec![
+        "&mut self".to_string(),
+        "dbus: &dbus_async::DBus".to_string(),
+    ];
+    for (name, type_) in inputs.iter() {
+        parameters.push(format!("{}: {}", name, type_.name));
+    }
+    let output_types: Vec<RustType> = outputs.into_iter().map(|(_, type_)| type_).collect();
+    code.doc(&format!("Handle the `{}` method.", method.name));
+    code.line("#[allow(clippy::too_many_arguments)]");
+    deprecated(code, &method.annotations);
+    code.line(&format!(
+        "async fn {}({}) -> Result<{}, {}>;",
+        name,
+        parameters.join(", "),
+        tuple_type(&output_types),
+        ERROR
+    ));
+}
+
+fn trait_property(code: &mut Code, property: &Property, name: &str) {
+    let type_ = RustType::new(&property.r#type);
+    if property.access != Access::Write {
+        code.doc(&format!("Get the `{}` property.", property.name));
+        deprecated(code, &property.annotations);
+        code.line(&format!(
+            "async fn get_{}(&mut self, dbus: &dbus_async::DBus) -> Result<{}, {}>;",
+            name, type_.name, ERROR
+        ));
+    }
+    if property.access != Access::Read {
+        code.doc(&format!("Set the `{}` property.", property.name));
+        deprecated(code, &property.annotations);
+        code.line(&format!(
+            "async fn set_{}(&mut self, dbus: &dbus_async::DBus, value: {}) -> Result<(), {}>;",
+            name, type_.name, ERROR
+        ));
+    }
+}
+
+/// Generate the match arm for a method call.
+fn dispatch_method(code: &mut Code, interface: &Interface, method: &Method, name: &str) {
+    let inputs = args(&method.args, Direction::In, Direction::In);
+    let outputs = args(&method.args, Direction::Out, Direction::In);
+    code.line(&format!(
+        "(Some(\"{}\") | None, \"{}\") => match body.as_slice() {{",
+        interface.name, method.name
+    ));
+    let patterns: Vec<String> = inputs
+        .iter()
+        .map(|(name, type_)| type_.pattern(name))
+        .collect();
+    let mut arguments = vec!["dbus".to_string()];
+    for (name, type_) in inputs.iter() {
+        arguments.push(type_.take(name));
+    }
+    code.line(&format!("[{}] => {{", patterns.join(", ")));
+    code.line(&format!(
+        "match object.{}({}).await {{",
+        name,
+        arguments.join(", ")
+    ));
+    let names: Vec<&str> = outputs.iter().map(|(name, _)| name.as_str()).collect();
+    let pattern = match names.as_slice() {
+        [name] => name.to_string(),
+        names => format!("({})", names.join(", ")),
+    };
+    code.line(&format!("Ok({}) => {{", pattern));
+    if outputs.is_empty() {
+        code.line("header.method_return().unwrap()");
+    } else {
+        code.line("let mut response = header.method_return().unwrap();");
+        for (name, type_) in outputs.iter() {
+            code.line(&format!("response.add_value({});", type_.to_value(name)));
+        }
+        code.line("response");
+    }
+    code.line("}");
+    code.line("Err((name, message)) => header.error(name, message),");
+    code.line("}");
+    code.line("}");
+    code.line(&format!(
+        "_ => header.invalid_args(\"Expected arguments: {}\".to_string()),",
+        method
+            .args
+            .iter()
+            .filter(|arg| arg.direction.unwrap_or(Direction::In) == Direction::In)
+            .map(|arg| arg.r#type.as_str())
+            .collect::<String>()
+    ));
+    code.line("},");
+}
+
+fn dispatch_get(code: &mut Code, interface: &Interface, names: &Names) {
+    code.line("(Some(\"org.freedesktop.DBus.Properties\"), \"Get\") => match body.as_slice() {");
+    code.line("[dbus_async::export::dbus_message_parser::value::Value::String(interface), dbus_async::export::dbus_message_parser::value::Value::String(property)]");
+    code.line(&format!("if interface == \"{}\" =>", interface.name));
+    code.line("{");
+    code.line("match property.as_str() {");
+    for (property, name) in interface.properties.iter().zip(&names.properties) {
+        if property.access == Access::Write {
+            continue;
+        }
+        let type_ = RustType::new(&property.r#type);
+        code.line(&format!(
+            "\"{}\" => match object.get_{}(dbus).await {{",
+            property.name, name
+        ));
+        code.line("Ok(value) => {");
+        code.line("let mut response = header.method_return().unwrap();");
+        code.line(&format!(
+            "response.add_value(dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new({})));",
+            type_.to_value("value")
+        ));
+        code.line("response");
+        code.line("}");
+        code.line("Err((name, message)) => header.error(name, message),");
+        code.line("},");
+    }
+    code.line("_ => header.unknown_property(property),");
+    code.line("}");
+    code.line("}");
+    code.line("_ => unknown_interface(&header),");
+    code.line("},");
+}
+
+fn dispatch_get_all(code: &mut Code, interface: &Interface, get_all: &str) {
+    code.line("(Some(\"org.freedesktop.DBus.Properties\"), \"GetAll\") => match body.as_slice() {");
+    code.line(&format!(
+        "[dbus_async::export::dbus_message_parser::value::Value::String(interface)] if interface == \"{}\" => {{",
+        interface.name
+    ));
+    code.line(&format!("match {}(object, dbus).await {{", get_all));
+    code.line("Ok(properties) => {");
+    code.line("let mut response = header.method_return().unwrap();");
+    code.line("response.add_value(properties);");
+    code.line("response");
+    code.line("}");
+    code.line("Err((name, message)) => header.error(name, message),");
+    code.line("}");
+    code.line("}");
+    code.line("_ => unknown_interface(&header),");
+    code.line("},");
+}
+
+/// Generate the code to emit the `PropertiesChanged` signal after a property was set.
+fn emit_properties_changed(code: &mut Code, interface: &Interface, property: &Property) {
+    let type_ = RustType::new(&property.r#type);
+    let (changed, invalidated) = match emits_changed_signal(interface, property) {
+        "true" => (
+            format!(
+                "vec![dbus_async::export::dbus_message_parser::value::Value::DictEntry(Box::new((\
+                dbus_async::export::dbus_message_parser::value::Value::String(\"{}\".to_string()), \
+                dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new({}))\
+                )))]",
+                property.name,
+                type_.to_value(&type_.take("value"))
+            ),
+            "Vec::new()".to_string(),
+        ),
+        "invalidates" => (
+            "Vec::new()".to_string(),
+            format!(
+                "vec![dbus_async::export::dbus_message_parser::value::Value::String(\"{}\".to_string())]",
+                property.name
+            ),
+        ),
+        _ => return,
+    };
+    code.line(
+        "let mut signal = dbus_async::export::dbus_message_parser::message::Message::signal(",
+    );
+    code.line("header.get_path().unwrap().clone(),");
+    code.line("\"org.freedesktop.DBus.Properties\".try_into().unwrap(),");
+    code.line("\"PropertiesChanged\".try_into().unwrap(),");
+    code.line(");");
+    code.line(&format!(
+        "signal.add_value(dbus_async::export::dbus_message_parser::value::Value::String(\"{}\".to_string()));",
+        interface.name
+    ));
+    code.line(&format!(
+        "let changed = dbus_async::export::dbus_message_parser::value::Array::new({}, {}).unwrap();",
+        changed, DICT_TYPE
+    ));
+    code.line(
+        "signal.add_value(dbus_async::export::dbus_message_parser::value::Value::Array(changed));",
+    );
+    code.line(&format!(
+        "let invalidated = dbus_async::export::dbus_message_parser::value::Array::new({}, dbus_async::export::dbus_message_parser::value::Type::String).unwrap();",
+        invalidated
+    ));
+    code.line("signal.add_value(dbus_async::export::dbus_message_parser::value::Value::Array(invalidated));");
+    code.line("dbus.send(signal).await?;");
+}
+
+fn dispatch_set(code: &mut Code, interface: &Interface, names: &Names) {
+    code.line("(Some(\"org.freedesktop.DBus.Properties\"), \"Set\") => match body.as_slice() {");
+    code.line("[dbus_async::export::dbus_message_parser::value::Value::String(interface), dbus_async::export::dbus_message_parser::value::Value::String(property), dbus_async::export::dbus_message_parser::value::Value::Variant(value)]");
+    code.line(&format!("if interface == \"{}\" =>", interface.name));
+    code.line("{");
+    code.line("match (property.as_str(), value.as_ref()) {");
+    for (property, name) in interface.properties.iter().zip(&names.properties) {
+        let type_ = RustType::new(&property.r#type);
+        if property.access == Access::Read {
+            code.line(&format!(
+                "(\"{}\", _) => header.error(\"org.freedesktop.DBus.Error.PropertyReadOnly\".try_into().unwrap(), \"Property is read-only\".to_string()),",
+                property.name
+            ));
+            continue;
+        }
+        code.line(&format!(
+            "(\"{}\", {}) => match object.set_{}(dbus, {}).await {{",
+            property.name,
+            type_.pattern("value"),
+            name,
+            type_.take("value")
+        ));
+        code.line("Ok(()) => {");
+        emit_properties_changed(code, interface, property);
+        code.line("header.method_return().unwrap()");
+        code.line("}");
+        code.line("Err((name, message)) => header.error(name, message),");
+        code.line("},");
+        if !type_.is_value() {
+            code.line(&format!(
+                "(\"{}\", _) => header.invalid_args(\"Expected type: {}\".to_string()),",
+                property.name, property.r#type
+            ));
+        }
+    }
+    code.line("_ => header.unknown_property(property),");
+    code.line("}");
+    code.line("}");
+    code.line("_ => unknown_interface(&header),");
+    code.line("},");
+}
+
+fn get_all(code: &mut Code, interface: &Interface, names: &Names, trait_name: &str, get_all: &str) {
+    code.line("#[allow(deprecated)]");
+    code.line(&format!(
+        "async fn {}<T: {}>(object: &mut T, dbus: &dbus_async::DBus) -> Result<dbus_async::export::dbus_message_parser::value::Value, {}> {{",
+        get_all, trait_name, ERROR
+    ));
+    code.line("#[allow(unused_mut)]");
+    code.line("let mut properties = Vec::new();");
+    for (property, name) in interface.properties.iter().zip(&names.properties) {
+        if property.access == Access::Write {
+            continue;
+        }
+        let type_ = RustType::new(&property.r#type);
+        code.line(&format!("let value = object.get_{}(dbus).await?;", name));
+        code.line("properties.push(dbus_async::export::dbus_message_parser::value::Value::DictEntry(Box::new((");
+        code.line(&format!(
+            "dbus_async::export::dbus_message_parser::value::Value::String(\"{}\".to_string()),",
+            property.name
+        ));
+        code.line(&format!(
+            "dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new({})),",
+            type_.to_value("value")
+        ));
+        code.line("))));");
+    }
+    code.line(&format!(
+        "let properties = dbus_async::export::dbus_message_parser::value::Array::new(properties, {}).unwrap();",
+        DICT_TYPE
+    ));
+    code.line("Ok(dbus_async::export::dbus_message_parser::value::Value::Array(properties))");
+    code.line("}");
+}
+
+fn emit_signal(code: &mut Code, interface: &Interface, signal: &Signal) {
+    let outputs = args(&signal.args, Direction::Out, Direction::Out);
+    let mut parameters = vec![
+        "dbus: &dbus_async::DBus".to_string(),
+        "object_path: dbus_async::export::dbus_message_parser::value::ObjectPath".to_string(),
+    ];
+    for (name, type_) in outputs.iter() {
+        parameters.push(format!("{}: {}", name, type_.name));
+    }
+    code.doc(&format!("Emit the `{}` signal.", signal.name));
+    code.line("#[allow(clippy::too_many_arguments)]");
+    deprecated(code, &signal.annotations);
+    code.line(&format!(
+        "pub async fn emit_{}_{}({}) -> dbus_async::DBusResult<()> {{",
+        snake_case(&interface_name(&interface.name)).trim_start_matches("r#"),
+        snake_case(&signal.name).trim_start_matches("r#"),
+        parameters.join(", ")
+    ));
+    code.line("#[allow(unused_mut)]");
+    code.line(
+        "let mut signal = dbus_async::export::dbus_message_parser::message::Message::signal(",
+    );
+    code.line("object_path,");
+    code.line(&format!("\"{}\".try_into().unwrap(),", interface.name));
+    code.line(&format!("\"{}\".try_into().unwrap(),", signal.name));
+    code.line(");");
+    for (name, type_) in outputs.iter() {
+        code.line(&format!("signal.add_value({});", type_.to_value(name)));
+    }
+    code.line("dbus.send(signal).await");
+    code.line("}");
+}
+
+pub(super) fn server(code: &mut Code, interface: &Interface, names: &Names) {
+    let trait_name = interface_name(&interface.name);
+    let snake_name = snake_case(&trait_name).trim_start_matches("r#").to_string();
+    let server_name = format!("{}Server", trait_name);
+    let handle = format!("handle_{}", snake_name);
+    let get_all_name = format!("{}_get_all", snake_name);
+    let has_properties = !interface.properties.is_empty();
+
+    // The trait.
+    code.doc(&format!(
+        "The server side of the `{}` interface.",
+        interface.name
+    ));
+    deprecated(code, &interface.annotations);
+    code.line("#[dbus_async::export::async_trait::async_trait]");
+    code.line(&format!("pub trait {}: Send {{", trait_name));
+    for (method, name) in interface.methods.iter().zip(&names.methods) {
+        trait_method(code, method, name);
+    }
+    for (property, name) in interface.properties.iter().zip(&names.properties) {
+        trait_property(code, property, name);
+    }
+    code.line("}");
+
+    // The handle function.
+    code.line("");
+    code.doc(&format!(
+        "Handle a method call of the `{}` interface.",
+        interface.name
+    ));
+    code.line("#[allow(deprecated, unused_variables)]");
+    code.line(&format!(
+        "pub async fn {}<T: {}>(object: &mut T, dbus: &dbus_async::DBus, header: dbus_async::export::dbus_message_parser::message::MessageHeader, body: Vec<dbus_async::export::dbus_message_parser::value::Value>, flags: dbus_async::export::dbus_message_parser::message::MessageFlags) -> dbus_async::DBusResult<()> {{",
+        handle, trait_name
+    ));
+    code.line("fn unknown_interface(header: &dbus_async::export::dbus_message_parser::message::MessageHeader) -> dbus_async::export::dbus_message_parser::message::Message {");
+    code.line("header.error(");
+    code.line("\"org.freedesktop.DBus.Error.UnknownInterface\".try_into().unwrap(),");
+    code.line("\"Unknown interface\".to_string(),");
+    code.line(")");
+    code.line("}");
+    code.line("");
+    code.line("let member = if let Some(member) = header.get_member() {");
+    code.line("member.clone()");
+    code.line("} else {");
+    code.line("return Ok(());");
+    code.line("};");
+    code.line("let interface = header.get_interface().map(|interface| interface.as_ref());");
+    // The methods with the `NoReply` annotation never reply, like the method calls, which do not
+    // expect a reply.
+    let no_reply_methods: Vec<String> = interface
+        .methods
+        .iter()
+        .filter(|method| annotation(&method.annotations, NO_REPLY) == Some("true"))
+        .map(|method| format!("\"{}\"", method.name))
+        .collect();
+    let mut no_reply =
+        "flags.contains(dbus_async::export::dbus_message_parser::message::MessageFlags::NO_REPLY_EXPECTED)".to_string();
+    if !no_reply_methods.is_empty() {
+        no_reply.push_str(&format!(
+            " || matches!((interface, member.as_ref()), (Some(\"{}\") | None, {}))",
+            interface.name,
+            no_reply_methods.join(" | ")
+        ));
+    }
+    code.line(&format!("let no_reply = {};", no_reply));
+    code.line("let response = match (interface, member.as_ref()) {");
+    if has_properties {
+        dispatch_get(code, interface, names);
+        dispatch_get_all(code, interface, &get_all_name);
+        dispatch_set(code, interface, names);
+    }
+    for (method, name) in interface.methods.iter().zip(&names.methods) {
+        dispatch_method(code, interface, method, name);
+    }
+    code.line(&format!(
+        "(Some(\"{}\") | None, _) => match header.unknown_member() {{",
+        interface.name
+    ));
+    code.line("Some(msg) => msg,");
+    code.line("None => return Ok(()),");
+    code.line("},");
+    code.line("_ => unknown_interface(&header),");
+    code.line("};");
+    code.line("if no_reply {");
+    code.line("return Ok(());");
+    code.line("}");
+    code.line("dbus.send(response).await");
+    code.line("}");
+
+    if has_properties {
+        code.line("");
+        get_all(code, interface, names, &trait_name, &get_all_name);
+    }
+
+    // The handler.
+    code.line("");
+    code.doc(&format!(
+        "A [`Handler`](dbus_async::Handler) for an object, which implements the `{}` interface.",
+        interface.name
+    ));
+    code.line(&format!("pub struct {}<T>(pub T);", server_name));
+    code.line("");
+    code.line("#[allow(deprecated)]");
+    code.line("#[dbus_async::export::async_trait::async_trait]");
+    code.line(&format!(
+        "impl<T: {}> dbus_async::Handler for {}<T> {{",
+        trait_name, server_name
+    ));
+    code.line("async fn handle(&mut self, dbus: &dbus_async::DBus, msg: dbus_async::export::dbus_message_parser::message::Message) -> dbus_async::DBusResult<()> {");
+    code.line("self.handle_with_flags(dbus, msg, dbus_async::export::dbus_message_parser::message::MessageFlags::empty()).await");
+    code.line("}");
+    code.line("");
+    code.line("async fn handle_with_flags(&mut self, dbus: &dbus_async::DBus, msg: dbus_async::export::dbus_message_parser::message::Message, flags: dbus_async::export::dbus_message_parser::message::MessageFlags) -> dbus_async::DBusResult<()> {");
+    code.line("if dbus_async::export::dbus_message_parser::message::MessageType::MethodCall != msg.get_type() {");
+    code.line("return Ok(());");
+    code.line("}");
+    code.line("match msg.split() {");
+    code.line(&format!(
+        "Ok((header, body)) => {}(&mut self.0, dbus, header, body, flags).await,",
+        handle
+    ));
+    code.line("Err(_) => Ok(()),");
+    code.line("}");
+    code.line("}");
+    code.line("}");
+
+    for signal in &interface.signals {
+        code.line("");
+        emit_signal(code, interface, signal);
+    }
+}
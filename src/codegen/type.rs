@@ -0,0 +1,95 @@
+/// The Rust type of a D-Bus signature.
+///
+/// Only the basic types are mapped to Rust types. All other types are represented as
+/// [`Value`](dbus_message_parser::value::Value).
+pub(super) struct RustType {
+    pub(super) name: &'static str,
+    variant: Option<&'static str>,
+    is_copy: bool,
+}
+
+impl RustType {
+    pub(super) fn new(signature: &str) -> RustType {
+        let (name, variant, is_copy) = match signature {
+            "y" => ("u8", Some("Byte"), true),
+            "b" => ("bool", Some("Boolean"), true),
+            "n" => ("i16", Some("Int16"), true),
+            "q" => ("u16", Some("Uint16"), true),
+            "i" => ("i32", Some("Int32"), true),
+            "u" => ("u32", Some("Uint32"), true),
+            "x" => ("i64", Some("Int64"), true),
+            "t" => ("u64", Some("Uint64"), true),
+            "d" => ("f64", Some("Double"), true),
+            "s" => ("String", Some("String"), false),
+            "o" => (
+                "dbus_async::export::dbus_message_parser::value::ObjectPath",
+                Some("ObjectPath"),
+                false,
+            ),
+            "g" => (
+                "Vec<dbus_async::export::dbus_message_parser::value::Type>",
+                Some("Signature"),
+                false,
+            ),
+            _ => (
+                "dbus_async::export::dbus_message_parser::value::Value",
+                None,
+                false,
+            ),
+        };
+        RustType {
+            name,
+            variant,
+            is_copy,
+        }
+    }
+
+    /// It is true if the type is represented as `Value`.
+    pub(super) fn is_value(&self) -> bool {
+        self.variant.is_none()
+    }
+
+    /// The expression to convert the Rust value (`expr`) to a `Value`.
+    pub(super) fn to_value(&self, expr: &str) -> String {
+        match self.variant {
+            Some(variant) => format!(
+                "dbus_async::export::dbus_message_parser::value::Value::{}({})",
+                variant, expr
+            ),
+            None => expr.to_string(),
+        }
+    }
+
+    /// The pattern to match a `Value` by reference and bind the inner value to `binding`.
+    pub(super) fn pattern(&self, binding: &str) -> String {
+        match self.variant {
+            Some(variant) => format!(
+                "dbus_async::export::dbus_message_parser::value::Value::{}({})",
+                variant, binding
+            ),
+            None => binding.to_string(),
+        }
+    }
+
+    /// The expression to get an owned value from the `binding` of the [`pattern`].
+    ///
+    /// [`pattern`]: #method.pattern
+    pub(super) fn take(&self, binding: &str) -> String {
+        if self.is_copy {
+            format!("*{}", binding)
+        } else {
+            format!("{}.clone()", binding)
+        }
+    }
+}
+
+/// The Rust type of multiple values. A single value is not wrapped in a tuple.
+pub(super) fn tuple_type(types: &[RustType]) -> String {
+    match types {
+        [type_] => type_.name.to_string(),
+        types => {
+            let names: Vec<&str> = types.iter().map(|type_| type_.name).collect();
+            format!("({})", names.join(", "))
+        }
+    }
+}
@@ -1,6 +1,7 @@
+use crate::DBusResult;
 use dbus_message_parser::{
     match_rule::MatchRule,
-    message::Message,
+    message::{Message, MessageFlags},
     value::{Interface, ObjectPath},
 };
 use futures::channel::{
@@ -9,20 +10,96 @@ use futures::channel::{
 };
 use std::collections::HashSet;
 
+/// A channel, which receives the `MethodCall` messages with or without their [`MessageFlags`].
+pub enum MethodCallSender {
+    Message(MpscSender<Message>),
+    WithFlags(MpscSender<(Message, MessageFlags)>),
+}
+
+impl MethodCallSender {
+    /// Try to send the message. If this fails then the message is returned together with a flag,
+    /// if the channel is disconnected.
+    pub fn try_send(
+        &mut self,
+        msg: Message,
+        flags: MessageFlags,
+    ) -> Result<(), (Box<Message>, bool)> {
+        match self {
+            MethodCallSender::Message(sender) => sender.try_send(msg).map_err(|e| {
+                let is_disconnected = e.is_disconnected();
+                (Box::new(e.into_inner()), is_disconnected)
+            }),
+            MethodCallSender::WithFlags(sender) => sender.try_send((msg, flags)).map_err(|e| {
+                let is_disconnected = e.is_disconnected();
+                (Box::new(e.into_inner().0), is_disconnected)
+            }),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        match self {
+            MethodCallSender::Message(sender) => sender.is_closed(),
+            MethodCallSender::WithFlags(sender) => sender.is_closed(),
+        }
+    }
+
+    pub fn same_receiver(&self, other: &MethodCallSender) -> bool {
+        match (self, other) {
+            (MethodCallSender::Message(sender), MethodCallSender::Message(other)) => {
+                sender.same_receiver(other)
+            }
+            (MethodCallSender::WithFlags(sender), MethodCallSender::WithFlags(other)) => {
+                sender.same_receiver(other)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_connected_to(&self, receiver: &MethodCallReceiver) -> bool {
+        match (self, receiver) {
+            (MethodCallSender::Message(sender), MethodCallReceiver::Message(receiver)) => {
+                sender.is_connected_to(receiver)
+            }
+            (MethodCallSender::WithFlags(sender), MethodCallReceiver::WithFlags(receiver)) => {
+                sender.is_connected_to(receiver)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The receiving side of a [`MethodCallSender`].
+pub enum MethodCallReceiver {
+    Message(MpscReceiver<Message>),
+    WithFlags(MpscReceiver<(Message, MessageFlags)>),
+}
+
 /// An enum representing all command the server task understands.
+///
+/// The [`MessageFlags`] of the send commands replace the flags of the message, if they are given.
+/// If they are not given then the flags of the message are kept and a method call expects a reply.
 pub enum Command {
-    SendMessage(Message),
-    SendMessageOneshot(Message, OneshotSender<Message>),
-    SendMessageMpcs(Message, OneshotSender<u32>, MpscSender<Message>),
-    AddMethodCall(ObjectPath, MpscSender<Message>),
+    SendMessage(Message, Option<MessageFlags>),
+    SendMessageOneshot(
+        Message,
+        Option<MessageFlags>,
+        OneshotSender<DBusResult<Message>>,
+    ),
+    SendMessageMpcs(
+        Message,
+        Option<MessageFlags>,
+        OneshotSender<DBusResult<u32>>,
+        MpscSender<Message>,
+    ),
+    AddMethodCall(ObjectPath, MethodCallSender),
     DeleteMethodCall(ObjectPath),
-    DeleteMethodCallSender(MpscSender<Message>),
-    DeleteMethodCallReceiver(MpscReceiver<Message>),
+    DeleteMethodCallSender(MethodCallSender),
+    DeleteMethodCallReceiver(MethodCallReceiver),
     ListMethodCall(ObjectPath, OneshotSender<HashSet<String>>),
-    AddMethodCallInterface(Interface, MpscSender<Message>),
+    AddMethodCallInterface(Interface, MethodCallSender),
     DeleteMethodCallInterface(Interface),
-    DeleteMethodCallInterfaceSender(MpscSender<Message>),
-    DeleteMethodCallInterfaceReceiver(MpscReceiver<Message>),
+    DeleteMethodCallInterfaceSender(MethodCallSender),
+    DeleteMethodCallInterfaceReceiver(MethodCallReceiver),
     AddSignal(
         ObjectPath,
         Option<fn(&Message) -> bool>,
@@ -5,6 +5,8 @@ use std::collections::HashSet;
 
 impl Connection {
     pub(super) fn list_path(&mut self, object_path: &ObjectPath, sender: Sender<HashSet<String>>) {
+        // Remove the handlers, which are not bound anymore.
+        self.method_calls.retain(|_, sender| !sender.is_closed());
         // List the handler.
         let mut result = HashSet::new();
 
@@ -4,10 +4,14 @@ use crate::command::Command;
 impl Connection {
     pub(in super::super) fn receive_command(&mut self, cmd: Command) {
         match cmd {
-            Command::SendMessage(msg) => self.send_message(msg),
-            Command::SendMessageOneshot(msg, response) => self.send_message_oneshot(msg, response),
-            Command::SendMessageMpcs(msg, response_reply_serial, response) => {
-                self.send_message_mpsc(msg, response_reply_serial, response)
+            Command::SendMessage(msg, flags) => {
+                self.send(msg, flags);
+            }
+            Command::SendMessageOneshot(msg, flags, response) => {
+                self.send_message_oneshot(msg, flags, response)
+            }
+            Command::SendMessageMpcs(msg, flags, response_reply_serial, response) => {
+                self.send_message_mpsc(msg, flags, response_reply_serial, response)
             }
             Command::AddMethodCall(object_path, object) => {
                 // Add the handler.
@@ -77,10 +81,11 @@ impl Connection {
             Command::Close => {
                 // Stop the server.
                 self.command_receiver.close();
+                self.send_command_receiver.close();
                 self.message_stream.close();
-                self.message_sink.close_channel();
                 self.method_calls.clear();
                 self.method_calls_interface.clear();
+                self.waiting_calls.clear();
                 self.signals.clear();
             }
         }
@@ -1,58 +1,95 @@
 use super::super::{Connection, MessageSender};
-use dbus_message_parser::message::Message;
-use futures::channel::{
-    mpsc::{Sender as MpscSender, TrySendError},
-    oneshot::Sender as OneshotSender,
-};
+use crate::{command::Command, DBusError, DBusResult, PendingCallPolicy};
+use dbus_message_parser::message::{Message, MessageFlags, MessageType};
+use futures::channel::{mpsc::Sender as MpscSender, oneshot::Sender as OneshotSender};
 
 impl Connection {
-    fn send(&mut self, mut msg: Message) -> Result<u32, TrySendError<Message>> {
-        // Increment the serial number.
-        self.serial += 1;
-        msg.set_serial(self.serial);
+    /// Set the serial of the message and add it to the outbox. The flags replace the flags of the
+    /// message, when it is written. Returns the serial.
+    pub(in super::super) fn send(&mut self, mut msg: Message, flags: Option<MessageFlags>) -> u32 {
+        let serial = self.next_serial();
+        msg.set_serial(serial);
 
         // Send the message.
-        self.message_sink.unbounded_send(msg)?;
-        Ok(self.serial)
+        self.outbox.push_back((msg, flags));
+        serial
     }
 
-    pub(super) fn send_message(&mut self, msg: Message) {
-        if let Err(e) = self.send(msg) {
-            error!("could not send msg: {:?}", e);
-        }
+    /// Check if a reply is expected for the message. If no flags are given then a reply is
+    /// expected for every method call.
+    fn is_reply_expected(msg: &Message, flags: Option<MessageFlags>) -> bool {
+        MessageType::MethodCall == msg.get_type()
+            && !flags.is_some_and(|flags| flags.contains(MessageFlags::NO_REPLY_EXPECTED))
     }
 
-    pub(super) fn send_message_oneshot(&mut self, msg: Message, response: OneshotSender<Message>) {
-        match self.send(msg) {
-            Ok(reply_serial) => {
-                // Add the response sender to the Map.
-                let response = MessageSender::Oneshot(response);
-                self.replies.put(reply_serial, response);
-            }
-            Err(e) => {
-                error!("could not send msg: {:?}", e);
+    pub(super) fn send_message_oneshot(
+        &mut self,
+        msg: Message,
+        flags: Option<MessageFlags>,
+        response: OneshotSender<DBusResult<Message>>,
+    ) {
+        if !Connection::is_reply_expected(&msg, flags) {
+            // Drop the response sender, so the caller does not wait for a reply.
+            self.send(msg, flags);
+            return;
+        }
+        if self.is_pending_call_limit() {
+            match self.pending_call_policy {
+                PendingCallPolicy::EvictOldest => self.evict_oldest_reply(),
+                PendingCallPolicy::RejectNew => {
+                    let error = DBusError::PendingCallLimit(self.pending_call_limit);
+                    if let Err(e) = response.send(Err(error)) {
+                        error!("could not send error: {:?}", e);
+                    }
+                    return;
+                }
+                PendingCallPolicy::Wait => {
+                    let cmd = Command::SendMessageOneshot(msg, flags, response);
+                    self.waiting_calls.push_back(cmd);
+                    return;
+                }
             }
         }
+        let reply_serial = self.send(msg, flags);
+        // Add the response sender to the Map.
+        let response = MessageSender::Oneshot(response);
+        self.add_reply(reply_serial, response);
     }
 
     pub(super) fn send_message_mpsc(
         &mut self,
         msg: Message,
-        response_reply_serial: OneshotSender<u32>,
+        flags: Option<MessageFlags>,
+        response_reply_serial: OneshotSender<DBusResult<u32>>,
         response: MpscSender<Message>,
     ) {
-        match self.send(msg) {
-            Ok(reply_serial) => {
-                if let Err(e) = response_reply_serial.send(reply_serial) {
-                    error!("could not send reply serial: {:?}", e);
+        let is_reply_expected = Connection::is_reply_expected(&msg, flags);
+        if is_reply_expected && self.is_pending_call_limit() {
+            match self.pending_call_policy {
+                PendingCallPolicy::EvictOldest => self.evict_oldest_reply(),
+                PendingCallPolicy::RejectNew => {
+                    let error = DBusError::PendingCallLimit(self.pending_call_limit);
+                    if let Err(e) = response_reply_serial.send(Err(error)) {
+                        error!("could not send error: {:?}", e);
+                    }
+                    return;
+                }
+                PendingCallPolicy::Wait => {
+                    let cmd = Command::SendMessageMpcs(msg, flags, response_reply_serial, response);
+                    self.waiting_calls.push_back(cmd);
+                    return;
                 }
-                // Add the response sender to the Map.
-                let response = MessageSender::Mpcs(response);
-                self.replies.put(reply_serial, response);
-            }
-            Err(e) => {
-                error!("could not send msg: {:?}", e);
             }
         }
+        let reply_serial = self.send(msg, flags);
+        if let Err(e) = response_reply_serial.send(Ok(reply_serial)) {
+            error!("could not send reply serial: {:?}", e);
+        }
+        if !is_reply_expected {
+            return;
+        }
+        // Add the response sender to the Map.
+        let response = MessageSender::Mpcs(response);
+        self.add_reply(reply_serial, response);
     }
 }
@@ -6,11 +6,11 @@ impl Connection {
         // It is an Error so we have to get the reply serial
         let serial = msg.get_reply_serial().unwrap();
         // Try to get the response handler.
-        if let Some(sender) = self.replies.pop(&serial) {
+        if let Some(sender) = self.take_reply(serial) {
             // Try to send it.
             match sender {
                 MessageSender::Oneshot(sender) => {
-                    if let Err(e) = sender.send(msg) {
+                    if let Err(e) = sender.send(Ok(msg)) {
                         error!("oneshot.send: {:?}", e);
                     }
                 }
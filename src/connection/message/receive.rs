@@ -1,11 +1,11 @@
 use super::super::Connection;
 use dbus_message_parser::{
     match_rule::MatchRule,
-    message::{Message, MessageType},
+    message::{Message, MessageFlags, MessageType},
 };
 
 impl Connection {
-    pub(in super::super) fn receive_message(&mut self, msg: Message) {
+    pub(in super::super) fn receive_message(&mut self, msg: Message, flags: MessageFlags) {
         for (match_rules, sender) in self.match_rules.iter_mut() {
             if MatchRule::matching_rules(match_rules, &msg) {
                 if let Err(e) = sender.try_send(msg.clone()) {
@@ -15,7 +15,7 @@ impl Connection {
         }
 
         match msg.get_type() {
-            MessageType::MethodCall => self.method_call(msg),
+            MessageType::MethodCall => self.method_call(msg, flags),
             MessageType::MethodReturn => self.method_return(msg),
             MessageType::Error => self.error(msg),
             MessageType::Signal => self.signal(msg),
@@ -1,6 +1,5 @@
 use super::super::Connection;
 use dbus_message_parser::message::Message;
-use retain_mut::RetainMut;
 
 impl Connection {
     pub(super) fn signal(&mut self, msg: Message) {
@@ -1,16 +1,15 @@
 use super::super::Connection;
-use dbus_message_parser::message::Message;
+use dbus_message_parser::message::{Message, MessageFlags};
 
 impl Connection {
-    pub(super) fn unhandled(&mut self, msg: Message) {
+    pub(super) fn unhandled(&mut self, msg: Message, flags: MessageFlags) {
         error!("MethodCall: UNHANDLED: {:?}", msg);
-        if let Some(mut msg) = msg.unknown_path() {
-            self.serial += 1;
-            msg.set_serial(self.serial);
-
-            if let Err(e) = self.message_sink.unbounded_send(msg) {
-                error!("MethodCall: message_sender.unbounded_send: {:?}", e);
-            }
+        if flags.contains(MessageFlags::NO_REPLY_EXPECTED) {
+            // The caller does not expect any reply.
+            return;
+        }
+        if let Some(msg) = msg.unknown_path() {
+            self.send(msg, None);
         }
     }
 }
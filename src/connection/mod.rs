@@ -1,6 +1,8 @@
 mod command;
 mod message;
+mod pending_call;
 mod run;
+mod serial;
 mod r#struct;
 
 pub(crate) use r#struct::{Connection, MessageSender};
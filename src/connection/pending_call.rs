@@ -0,0 +1,128 @@
+use super::{Connection, MessageSender};
+use crate::{command::Command, DBusError, ErrorMapping, ProtocolError, StandardError};
+use dbus_message_parser::{
+    message::{Message, MessageFlags, MessageHeader, MessageHeaderFields, MessageType},
+    value::Value,
+};
+use std::sync::atomic::Ordering;
+
+/// Create the error reply, which is sent to the channel of an evicted pending call, because the
+/// channel can only receive messages.
+fn create_evicted_reply(serial: u32) -> Message {
+    let error = DBusError::PendingCallEvicted(serial);
+    let (error_name, message) = StandardError::NoReply(error.to_string()).to_error();
+    let fields = MessageHeaderFields {
+        error_name: Some(error_name),
+        reply_serial: Some(serial),
+        ..Default::default()
+    };
+    // The unwrap function call will never panic because all required fields are set.
+    let header = MessageHeader::new(
+        true,
+        MessageType::Error,
+        MessageFlags::NO_REPLY_EXPECTED,
+        1,
+        0,
+        fields,
+    )
+    .unwrap();
+    Message::new(header, vec![Value::String(message)])
+}
+
+impl Connection {
+    /// Update the number of pending calls for the monitoring.
+    fn update_pending_calls(&self) {
+        self.pending_calls
+            .store(self.replies.len(), Ordering::Relaxed);
+    }
+
+    /// Add the sender for the reply of a pending call.
+    pub(super) fn add_reply(&mut self, serial: u32, sender: MessageSender) {
+        self.replies.put(serial, sender);
+        self.update_pending_calls();
+    }
+
+    /// Take the sender for the reply of a pending call and send the method calls, which wait
+    /// until the number of pending calls is below the limit.
+    pub(super) fn take_reply(&mut self, serial: u32) -> Option<MessageSender> {
+        let sender = self.replies.pop(&serial);
+        self.update_pending_calls();
+        while self.replies.len() < self.pending_call_limit {
+            if let Some(cmd) = self.waiting_calls.pop_front() {
+                self.receive_command(cmd);
+            } else {
+                break;
+            }
+        }
+        sender
+    }
+
+    /// Remove the pending calls, whose callers stopped waiting for the reply (for example, because
+    /// the call timed out).
+    fn purge_canceled_replies(&mut self) {
+        let canceled: Vec<u32> = self
+            .replies
+            .iter()
+            .filter_map(|(serial, sender)| match sender {
+                MessageSender::Oneshot(sender) if sender.is_canceled() => Some(*serial),
+                _ => None,
+            })
+            .collect();
+        for serial in canceled {
+            self.replies.pop(&serial);
+        }
+        self.update_pending_calls();
+    }
+
+    /// Check if the maximum number of pending calls is reached.
+    pub(super) fn is_pending_call_limit(&mut self) -> bool {
+        if self.pending_call_limit <= self.replies.len() {
+            self.purge_canceled_replies();
+        }
+        self.pending_call_limit <= self.replies.len()
+    }
+
+    /// Send the error to the callers of all pending calls and of the calls, which wait until they
+    /// can be sent, because the connection is closed. The error is stored, so it is returned by
+    /// the next calls.
+    pub(super) fn fail_pending_calls(&mut self, error: ProtocolError) {
+        let _ = self.protocol_error.set(error.clone());
+        while let Some((_, sender)) = self.replies.pop_lru() {
+            if let MessageSender::Oneshot(sender) = sender {
+                let _ = sender.send(Err(DBusError::ProtocolError(error.clone())));
+            }
+        }
+        for cmd in self.waiting_calls.drain(..) {
+            match cmd {
+                Command::SendMessageOneshot(_, _, sender) => {
+                    let _ = sender.send(Err(DBusError::ProtocolError(error.clone())));
+                }
+                Command::SendMessageMpcs(_, _, sender, _) => {
+                    let _ = sender.send(Err(DBusError::ProtocolError(error.clone())));
+                }
+                _ => {}
+            }
+        }
+        self.update_pending_calls();
+    }
+
+    /// Stop waiting for the reply of the oldest pending call.
+    pub(super) fn evict_oldest_reply(&mut self) {
+        if let Some((serial, sender)) = self.replies.pop_lru() {
+            error!("Evict the pending call with the serial {}", serial);
+            match sender {
+                MessageSender::Oneshot(sender) => {
+                    if let Err(e) = sender.send(Err(DBusError::PendingCallEvicted(serial))) {
+                        error!("oneshot.send: {:?}", e);
+                    }
+                }
+                MessageSender::Mpcs(mut sender) => {
+                    if let Err(e) = sender.try_send(create_evicted_reply(serial)) {
+                        error!("mpsc.try_send: {:?}", e);
+                    }
+                }
+            }
+            self.update_pending_calls();
+        }
+    }
+}
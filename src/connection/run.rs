@@ -1,5 +1,34 @@
 use super::Connection;
-use futures::StreamExt;
+use crate::{command::Command, ProtocolError};
+use dbus_message_parser::message::{Message, MessageFlags};
+use futures::{future::pending, pin_mut, select_biased, FutureExt, StreamExt};
+use std::collections::VecDeque;
+use tokio::sync::mpsc::Sender;
+
+/// The events of the connection task.
+enum Event {
+    Command(Option<Command>),
+    Sent(bool),
+    Message(Option<Result<(Message, MessageFlags), ProtocolError>>),
+    SendCommand(Option<Command>),
+}
+
+/// Send the next message of the outbox, if there is space in the channel of the sink task.
+/// Returns `false`, if the sink task is closed.
+async fn send_next(
+    message_sink: &Sender<(Message, Option<MessageFlags>)>,
+    outbox: &mut VecDeque<(Message, Option<MessageFlags>)>,
+) -> bool {
+    match message_sink.reserve().await {
+        Ok(permit) => {
+            if let Some(msg) = outbox.pop_front() {
+                permit.send(msg);
+            }
+            true
+        }
+        Err(_) => false,
+    }
+}
 
 impl Connection {
     fn has_channels(&self) -> bool {
@@ -8,10 +37,33 @@ impl Connection {
             || !self.method_calls_interface.is_empty()
     }
 
+    /// Send all messages, which wait in the outbox, to the sink task.
+    async fn flush(&mut self) {
+        while let Some(msg) = self.outbox.pop_front() {
+            if let Err(e) = self.message_sink.send(msg).await {
+                error!("could not send msg: {:?}", e);
+                self.outbox.clear();
+            }
+        }
+    }
+
     async fn receive_only_message(&mut self) {
+        // Send the messages, which were sent before the command stream was closed.
+        while let Ok(cmd) = self.send_command_receiver.try_recv() {
+            self.receive_command(cmd);
+        }
+        self.flush().await;
         if self.has_channels() {
-            while let Some(msg) = self.message_stream.next().await {
-                self.receive_message(msg);
+            while let Some(msg) = self.message_stream.recv().await {
+                let (msg, flags) = match msg {
+                    Ok(result) => result,
+                    Err(e) => {
+                        self.fail_pending_calls(e);
+                        break;
+                    }
+                };
+                self.receive_message(msg, flags);
+                self.flush().await;
                 if !self.has_channels() {
                     debug!("Has not channels");
                     break;
@@ -21,31 +73,90 @@ impl Connection {
         debug!("Message stream is closed");
     }
 
+    /// Wait for the next event of the connection task.
+    async fn next_event(&mut self) -> Event {
+        let has_outbox = !self.outbox.is_empty();
+        let has_space = self.outbox.len() < self.send_queue_size;
+        let message_sink = &self.message_sink;
+        let outbox = &mut self.outbox;
+        let send_command_receiver = &mut self.send_command_receiver;
+        // Send the next message of the outbox.
+        let send = async move {
+            if has_outbox {
+                send_next(message_sink, outbox).await
+            } else {
+                pending().await
+            }
+        }
+        .fuse();
+        let receive = self.message_stream.recv().fuse();
+        // Get the next message to send, if the outbox is not full.
+        let send_command = async move {
+            if has_space {
+                send_command_receiver.recv().await
+            } else {
+                pending().await
+            }
+        }
+        .fuse();
+        pin_mut!(send, receive, send_command);
+        select_biased! {
+            // The commands, which add or delete channels, are preferred, so a channel is added
+            // before a message is sent, whose response is received by this channel.
+            next = self.command_receiver.next() => Event::Command(next),
+            is_open = send => Event::Sent(is_open),
+            next = receive => Event::Message(next),
+            next = send_command => Event::SendCommand(next),
+        }
+    }
+
     /// Run the connection task.
     pub(crate) async fn run(mut self) {
         loop {
-            tokio::select! {
-                next = self.message_stream.next() => match next {
-                    Some(msg) => self.receive_message(msg),
-                    None => {
-                        debug!("Message stream is closed");
-                        break;
-                    }
-                },
-                // Get the next command.
-                next = self.command_receiver.next() => match next {
-                    Some(cmd) => self.receive_command(cmd),
-                    None => {
-                        debug!("Command stream is closed");
-                        self.receive_only_message().await;
+            match self.next_event().await {
+                Event::Command(Some(cmd)) => self.receive_command(cmd),
+                Event::Command(None) => {
+                    debug!("Command stream is closed");
+                    self.receive_only_message().await;
+                    break;
+                }
+                Event::Sent(is_open) => {
+                    if !is_open {
+                        debug!("Message sink is closed");
                         break;
                     }
-                },
-                else => {
-                    debug!("Both stream are closed");
+                }
+                Event::Message(Some(Ok((msg, flags)))) => self.receive_message(msg, flags),
+                Event::Message(Some(Err(e))) => {
+                    error!("Close connection: {}", e);
+                    self.fail_pending_calls(e);
+                    break;
+                }
+                Event::Message(None) => {
+                    debug!("Message stream is closed");
+                    break;
+                }
+                Event::SendCommand(Some(cmd)) => self.receive_command(cmd),
+                Event::SendCommand(None) => {
+                    debug!("Send command stream is closed");
+                    self.receive_only_message().await;
                     break;
                 }
             }
         }
+        self.flush().await;
+        // Close the channel of the sink task and wait until all messages are written.
+        let Connection {
+            message_sink,
+            sink_task,
+            stream_task,
+            ..
+        } = self;
+        drop(message_sink);
+        if let Err(e) = sink_task.await {
+            error!("Sink task: {}", e);
+        }
+        // Stop the stream task, so the socket is closed.
+        stream_task.abort();
     }
 }
@@ -0,0 +1,52 @@
+use super::Connection;
+
+/// Get the serial after the given serial.
+///
+/// The serial wraps around, skips `0`, which is invalid, and skips the serials, which are still
+/// pending.
+fn next_serial<F>(mut serial: u32, is_pending: F) -> u32
+where
+    F: Fn(u32) -> bool,
+{
+    loop {
+        serial = serial.wrapping_add(1);
+        if serial != 0 && !is_pending(serial) {
+            return serial;
+        }
+    }
+}
+
+impl Connection {
+    /// Allocate the serial for the next message.
+    pub(super) fn next_serial(&mut self) -> u32 {
+        let replies = &self.replies;
+        self.serial = next_serial(self.serial, |serial| replies.contains(&serial));
+        self.serial
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_serial;
+
+    #[test]
+    fn next_serial_increment() {
+        assert_eq!(next_serial(0, |_| false), 1);
+        assert_eq!(next_serial(41, |_| false), 42);
+    }
+
+    #[test]
+    fn next_serial_wrap() {
+        assert_eq!(next_serial(u32::MAX - 1, |_| false), u32::MAX);
+        assert_eq!(next_serial(u32::MAX, |_| false), 1);
+    }
+
+    #[test]
+    fn next_serial_skip_pending() {
+        let pending = [u32::MAX, 1, 2];
+        assert_eq!(
+            next_serial(u32::MAX - 1, |serial| pending.contains(&serial)),
+            3
+        );
+    }
+}
@@ -1,50 +1,90 @@
-use crate::command::Command;
+use crate::{
+    command::{Command, MethodCallSender},
+    runtime::JoinHandle,
+    stream::StreamHandle,
+    DBusBuilder, DBusResult, PendingCallPolicy, ProtocolError,
+};
 use dbus_message_parser::{
     match_rule::MatchRule,
-    message::Message,
+    message::{Message, MessageFlags},
     value::{Interface, ObjectPath},
 };
 use futures::channel::{
-    mpsc::{Sender as MpscSender, UnboundedReceiver, UnboundedSender},
+    mpsc::{Sender as MpscSender, UnboundedReceiver},
     oneshot::Sender as OneshotSender,
 };
 use lru::LruCache;
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{atomic::AtomicUsize, Arc, OnceLock},
+};
+use tokio::sync::mpsc::{Receiver, Sender};
 
 pub(crate) enum MessageSender {
-    Oneshot(OneshotSender<Message>),
+    Oneshot(OneshotSender<DBusResult<Message>>),
     Mpcs(MpscSender<Message>),
 }
 
 pub(crate) struct Connection {
     pub(super) serial: u32,
     pub(super) replies: LruCache<u32, MessageSender>,
+    pub(super) pending_call_limit: usize,
+    pub(super) pending_call_policy: PendingCallPolicy,
+    // The method calls, which wait until the number of pending calls is below the limit.
+    pub(super) waiting_calls: VecDeque<Command>,
+    pub(super) pending_calls: Arc<AtomicUsize>,
+    // The violation of the protocol, which closed the connection.
+    pub(super) protocol_error: Arc<OnceLock<ProtocolError>>,
     pub(super) signals:
         HashMap<ObjectPath, Vec<(Option<fn(&Message) -> bool>, MpscSender<Message>)>>,
-    pub(super) method_calls: HashMap<ObjectPath, MpscSender<Message>>,
-    pub(super) method_calls_interface: HashMap<Interface, MpscSender<Message>>,
+    pub(super) method_calls: HashMap<ObjectPath, MethodCallSender>,
+    pub(super) method_calls_interface: HashMap<Interface, MethodCallSender>,
     pub(super) match_rules: Vec<(Vec<MatchRule>, MpscSender<Message>)>,
     pub(super) command_receiver: UnboundedReceiver<Command>,
-    pub(super) message_sink: UnboundedSender<Message>,
-    pub(super) message_stream: UnboundedReceiver<Message>,
+    // The commands, which send messages. The channel is bounded, so the senders wait if the
+    // messages cannot be sent fast enough.
+    pub(super) send_command_receiver: Receiver<Command>,
+    pub(super) message_sink: Sender<(Message, Option<MessageFlags>)>,
+    // The sink task, which writes the messages to the socket.
+    pub(super) sink_task: JoinHandle<()>,
+    // The stream task, which reads the messages from the socket.
+    pub(super) stream_task: JoinHandle<()>,
+    pub(super) message_stream: Receiver<Result<(Message, MessageFlags), ProtocolError>>,
+    // The messages, which wait until there is space in the channel of the sink task, and the
+    // flags, which replace the flags of the messages.
+    pub(super) outbox: VecDeque<(Message, Option<MessageFlags>)>,
+    pub(super) send_queue_size: usize,
 }
 
 impl Connection {
     pub(crate) fn from(
         command_receiver: UnboundedReceiver<Command>,
-        message_sink: UnboundedSender<Message>,
-        message_stream: UnboundedReceiver<Message>,
+        send_command_receiver: Receiver<Command>,
+        stream_handle: StreamHandle,
+        builder: &DBusBuilder,
+        pending_calls: Arc<AtomicUsize>,
+        protocol_error: Arc<OnceLock<ProtocolError>>,
     ) -> Connection {
         Connection {
             serial: 0,
-            replies: LruCache::new(1024),
+            replies: LruCache::unbounded(),
+            pending_call_limit: builder.pending_call_limit,
+            pending_call_policy: builder.pending_call_policy,
+            waiting_calls: VecDeque::new(),
+            pending_calls,
+            protocol_error,
             signals: HashMap::new(),
             method_calls: HashMap::new(),
             method_calls_interface: HashMap::new(),
             match_rules: Vec::new(),
             command_receiver,
-            message_sink,
-            message_stream,
+            send_command_receiver,
+            message_sink: stream_handle.message_sink,
+            sink_task: stream_handle.sink_task,
+            stream_task: stream_handle.stream_task,
+            message_stream: stream_handle.message_stream,
+            outbox: VecDeque::new(),
+            send_queue_size: builder.send_queue_size,
         }
     }
 }
@@ -0,0 +1,41 @@
+use crate::{
+    bus::{Authentication, Bus},
+    runtime::{sleep, TcpListener, UnixListener},
+    stream::get_peer_uid,
+};
+use std::{sync::Arc, time::Duration};
+
+/// The time to wait after a connection could not be accepted, for example because the process
+/// has no free file descriptors.
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(100);
+
+/// Accept the connections of the Unix Domain Stream socket. The `EXTERNAL` mechanism is checked
+/// against the peer credentials of the connection.
+pub(super) async fn accept_unix(listener: UnixListener, bus: Arc<Bus>) {
+    loop {
+        match listener.accept().await {
+            Ok(stream) => match get_peer_uid(&stream) {
+                Ok(uid) => bus.accept(stream, Authentication::PeerUid(uid)),
+                Err(e) => error!("Could not get the peer credentials: {}", e),
+            },
+            Err(e) => {
+                error!("Could not accept a connection: {}", e);
+                sleep(ACCEPT_ERROR_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Accept the connections of the TCP socket. The UID of a client cannot be checked, so only the
+/// `ANONYMOUS` mechanism is accepted.
+pub(super) async fn accept_tcp(listener: TcpListener, bus: Arc<Bus>) {
+    loop {
+        match listener.accept().await {
+            Ok(stream) => bus.accept(stream, Authentication::Anonymous),
+            Err(e) => {
+                error!("Could not accept a connection: {}", e);
+                sleep(ACCEPT_ERROR_DELAY).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! A minimal message bus daemon, which can be embedded or run as the `dbus-async-daemon` binary.
+//!
+//! This module is only available with the `daemon` feature.
+//!
+//! ```no_run
+//! use dbus_async::{daemon::BusDaemon, DBus};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut daemon = BusDaemon::new();
+//! let address = daemon.listen("unix:path=/tmp/dbus-async-daemon").await?;
+//! let (dbus, _connection_handle) = DBus::new(&address, true, true).await?;
+//! # Ok(())
+//! # }
+//! ```
+mod listener;
+
+use crate::{
+    bus::{Authentication, Bus},
+    runtime::{bind_tcp, bind_unix, lookup_host, spawn, unix_listener_from_std, JoinHandle},
+    Transport,
+};
+use dbus_server_address_parser::{Address, DecodeError, Family, Tcp, Unix, UnixType};
+use listener::{accept_tcp, accept_unix};
+use std::{
+    fs::remove_file,
+    io::Error as IoError,
+    net::{IpAddr, SocketAddr},
+    os::unix::net::UnixListener,
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// The host of a TCP address, if no host is given.
+const DEFAULT_TCP_HOST: &str = "localhost";
+
+/// An enum representing all errors, which can occur during the listening of the [`BusDaemon`].
+#[derive(Debug, Error)]
+pub enum DaemonError {
+    #[error("Could not parse address: {0}")]
+    DecodeError(#[from] DecodeError),
+    #[error("Address is not supported: {0}")]
+    AddressNotSupported(Address),
+    #[error("Could not resolve IP addresses, which match the given IP family")]
+    TcpResolveIpAddress,
+    #[error("IO Error: {0}")]
+    IoError(#[from] IoError),
+}
+
+/// A minimal message bus daemon.
+///
+/// The daemon implements the methods of the `org.freedesktop.DBus` interface, which are needed to
+/// own names and to receive signals: `Hello`, `RequestName`, `ReleaseName`, `GetNameOwner`,
+/// `NameHasOwner`, `ListNames`, `ListQueuedOwners`, `AddMatch`, `RemoveMatch`,
+/// `GetConnectionUnixUser` and `GetId`. There is no security policy, every connection can own
+/// every name and can send messages to every connection.
+///
+/// The daemon runs until the object is dropped, so the object has to be created inside the
+/// runtime. The Unix Domain Stream sockets, which were created by the daemon, are removed, if the
+/// object is dropped.
+pub struct BusDaemon {
+    bus: Arc<Bus>,
+    listeners: Vec<JoinHandle<()>>,
+    paths: Vec<String>,
+}
+
+impl BusDaemon {
+    /// Start a new daemon without any listening sockets.
+    pub fn new() -> BusDaemon {
+        BusDaemon {
+            bus: Arc::new(Bus::new()),
+            listeners: Vec::new(),
+            paths: Vec::new(),
+        }
+    }
+
+    /// Listen on all [server addresses], which are separated by `;`. The supported addresses are
+    /// `unix:path=...` and `tcp:host=...,port=...`. If the port is `0` then a free port is chosen.
+    ///
+    /// Returns the addresses, which can be used by the clients to connect to the daemon.
+    ///
+    /// [server addresses]: https://dbus.freedesktop.org/doc/dbus-specification.html#addresses
+    pub async fn listen(&mut self, addresses: &str) -> Result<String, DaemonError> {
+        let mut result = Vec::new();
+        for address in Address::decode(addresses)? {
+            let address = match address {
+                Address::Unix(unix) => self.listen_unix(unix).await?,
+                Address::Tcp(tcp) => self.listen_tcp(tcp).await?,
+                address => return Err(DaemonError::AddressNotSupported(address)),
+            };
+            result.push(address.to_string());
+        }
+        Ok(result.join(";"))
+    }
+
+    async fn listen_unix(&mut self, unix: Unix) -> Result<Address, DaemonError> {
+        let path = match unix.r#type {
+            UnixType::Path(path) => path,
+            r#type => {
+                let unix = Unix { r#type, guid: None };
+                return Err(DaemonError::AddressNotSupported(Address::Unix(unix)));
+            }
+        };
+        let listener = bind_unix(&path).await?;
+        debug!("Listen on {}", path);
+        self.listeners
+            .push(spawn(accept_unix(listener, self.bus.clone())));
+        self.paths.push(path.clone());
+        let unix = Unix {
+            r#type: UnixType::Path(path),
+            guid: Some(*self.bus.get_guid()),
+        };
+        Ok(Address::Unix(unix))
+    }
+
+    fn tcp_family_match(socket_addr: &SocketAddr, family: &Option<Family>) -> bool {
+        match family {
+            Some(Family::Ipv4) => socket_addr.is_ipv4(),
+            Some(Family::Ipv6) => socket_addr.is_ipv6(),
+            None => true,
+        }
+    }
+
+    async fn listen_tcp(&mut self, tcp: Tcp) -> Result<Address, DaemonError> {
+        let Tcp {
+            host,
+            bind,
+            port,
+            family,
+            ..
+        } = tcp;
+        let host = bind
+            .or(host)
+            .unwrap_or_else(|| DEFAULT_TCP_HOST.to_string());
+        let port = port.unwrap_or(0);
+        let socket_addrs = match host.parse::<IpAddr>() {
+            Ok(ip_addr) => vec![SocketAddr::new(ip_addr, port)],
+            Err(_) => lookup_host(&host, port).await?,
+        };
+        let socket_addr = socket_addrs
+            .into_iter()
+            .find(|socket_addr| BusDaemon::tcp_family_match(socket_addr, &family))
+            .ok_or(DaemonError::TcpResolveIpAddress)?;
+
+        let listener = bind_tcp(&socket_addr).await?;
+        let local_addr = listener.local_addr()?;
+        debug!("Listen on {}", local_addr);
+        self.listeners
+            .push(spawn(accept_tcp(listener, self.bus.clone())));
+        let family = if local_addr.is_ipv4() {
+            Family::Ipv4
+        } else {
+            Family::Ipv6
+        };
+        let tcp = Tcp {
+            host: Some(local_addr.ip().to_string()),
+            bind: None,
+            port: Some(local_addr.port()),
+            family: Some(family),
+            guid: Some(*self.bus.get_guid()),
+        };
+        Ok(Address::Tcp(tcp))
+    }
+
+    /// Listen on the given listening Unix Domain Stream socket, for example a socket, which is
+    /// inherited through socket activation (see [`activation::listen_fds`]). The socket file is not
+    /// removed, if the object is dropped.
+    ///
+    /// [`activation::listen_fds`]: crate::activation::listen_fds
+    pub fn listen_with_unix_listener(
+        &mut self,
+        unix_listener: UnixListener,
+    ) -> Result<(), DaemonError> {
+        let listener = unix_listener_from_std(unix_listener)?;
+        self.listeners
+            .push(spawn(accept_unix(listener, self.bus.clone())));
+        Ok(())
+    }
+
+    /// Accept a connection through the given duplex stream, for example an in-memory stream.
+    ///
+    /// The UID, which the client sends during the authentication, is trusted.
+    pub fn accept<T>(&self, transport: T)
+    where
+        T: Transport + 'static,
+    {
+        self.bus.accept(transport, Authentication::Trusted);
+    }
+}
+
+impl Default for BusDaemon {
+    fn default() -> Self {
+        BusDaemon::new()
+    }
+}
+
+impl Drop for BusDaemon {
+    fn drop(&mut self) {
+        for listener in self.listeners.iter() {
+            listener.abort();
+        }
+        for path in self.paths.iter() {
+            if let Err(e) = remove_file(path) {
+                error!("Could not remove {}: {}", path, e);
+            }
+        }
+    }
+}
@@ -1,30 +1,50 @@
 use crate::{
-    command::Command,
+    command::{Command, MethodCallReceiver, MethodCallSender},
     connection::Connection,
     error::DBusResult,
-    introspect::add_introspect,
+    introspect::{add_introspect, IntrospectError, Node},
+    marshal::{FromArgs, ToArgs},
     peer::add_peer,
-    stream::Stream,
-    {DBusError, DBusNameFlag},
+    runtime::{spawn, timeout, JoinHandle},
+    stream::{ProtocolError, Stream},
+    {CallOptions, DBusBuilder, DBusError, DBusNameFlag},
 };
+use async_recursion::async_recursion;
 use dbus_message_parser::{
     match_rule::MatchRule,
-    message::{Message, MessageType},
-    value::{Bus, Interface, ObjectPath, Value},
+    message::{Message, MessageFlags, MessageHeader, MessageType},
+    value::{Bus, Interface, Member, ObjectPath, Value},
 };
 use dbus_server_address_parser::Address;
 use futures::channel::{
     mpsc::{unbounded, Receiver as MpscReceiver, Sender as MpscSender, UnboundedSender},
     oneshot::channel,
 };
-use std::{collections::HashSet, convert::TryInto, env::var, sync::Arc};
-use tokio::{spawn, task::JoinHandle};
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
+use tokio::sync::mpsc::{channel as bounded_channel, Sender as BoundedSender};
 
 /// This struct represents an object to communicate with the DBus daemon.
 #[derive(Clone)]
 pub struct DBus {
     command_sender: UnboundedSender<Command>,
-    address: Arc<Address>,
+    send_command_sender: BoundedSender<Command>,
+    address: Option<Arc<Address>>,
+    pending_calls: Arc<AtomicUsize>,
+    dropped_signals: Arc<AtomicUsize>,
+    protocol_error: Arc<OnceLock<ProtocolError>>,
+    call_timeout: Option<Duration>,
+    handler_channel_size: usize,
+    /// The serial and the sender of a method call and if a reply to it was sent through this
+    /// object (see [`DBus::track_reply`]).
+    reply_tracker: Option<(u32, Option<Bus>, Arc<AtomicBool>)>,
 }
 
 impl DBus {
@@ -34,17 +54,19 @@ impl DBus {
     /// If the second argument (`peer`) is `true` then the Peer has the
     /// [`org.freedesktop.DBus.Peer`].
     ///
-    /// The `DBUS_SESSION_BUS_ADDRESS` environment variable **have to** be defined.
+    /// The `DBUS_SESSION_BUS_ADDRESS` environment variable **have to** be defined. Use the
+    /// [`DBusBuilder`] for more options.
     ///
     /// [introspectable]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-introspectable
     /// [`org.freedesktop.DBus.Peer`]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-peer
+    /// [`DBusBuilder`]: crate::DBusBuilder
     pub async fn session(introspectable: bool, peer: bool) -> DBusResult<(DBus, JoinHandle<()>)> {
-        if let Ok(path) = var("DBUS_SESSION_BUS_ADDRESS") {
-            DBus::new(&path, introspectable, peer).await
-        } else {
-            // It could not connect to any socket
-            Err(DBusError::DBusSessionBusAddress)
-        }
+        DBusBuilder::new()
+            .session()
+            .introspectable(introspectable)
+            .peer(peer)
+            .build()
+            .await
     }
 
     /// Connect to the system DBus.
@@ -54,54 +76,102 @@ impl DBus {
     /// [`org.freedesktop.DBus.Peer`].
     ///
     /// If there `DBUS_SYSTEM_BUS_ADDRESS` environment variable is defined then this path will be
-    /// used, else `unix:path=/var/run/dbus/system_bus_socket`.
+    /// used, else `unix:path=/var/run/dbus/system_bus_socket`. Use the [`DBusBuilder`] for more
+    /// options.
     ///
     /// [introspectable]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-introspectable
     /// [`org.freedesktop.DBus.Peer`]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-peer
+    /// [`DBusBuilder`]: crate::DBusBuilder
     pub async fn system(introspectable: bool, peer: bool) -> DBusResult<(DBus, JoinHandle<()>)> {
-        let path = if let Ok(path) = var("DBUS_SYSTEM_BUS_ADDRESS") {
-            path
-        } else {
-            "unix:path=/var/run/dbus/system_bus_socket".to_string()
-        };
-        DBus::new(&path, introspectable, peer).await
+        DBusBuilder::new()
+            .system()
+            .introspectable(introspectable)
+            .peer(peer)
+            .build()
+            .await
     }
 
     /// Connect to the specific (`addressses`) DBus daemon.
     ///
     /// If the second argument (`introspectable`) is `true` then the Peer is [introspectable].
     /// If the third argument (`peer`) is `true` then the Peer has the
-    /// [`org.freedesktop.DBus.Peer`].
+    /// [`org.freedesktop.DBus.Peer`]. Use the [`DBusBuilder`] for more options.
     ///
     /// [introspectable]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-introspectable
     /// [`org.freedesktop.DBus.Peer`]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-peer
+    /// [`DBusBuilder`]: crate::DBusBuilder
     pub async fn new(
         addressses: &str,
         introspectable: bool,
         peer: bool,
+    ) -> DBusResult<(DBus, JoinHandle<()>)> {
+        DBusBuilder::new()
+            .address(addressses)
+            .introspectable(introspectable)
+            .peer(peer)
+            .build()
+            .await
+    }
+
+    /// Connect to the specific (`addressses`) DBus daemon with the options of the builder.
+    pub(crate) async fn from_builder(
+        addressses: &str,
+        builder: DBusBuilder,
+    ) -> DBusResult<(DBus, JoinHandle<()>)> {
+        let (address, stream) = Stream::new(addressses, &builder).await?;
+        DBus::from_stream(Some(address), stream, builder).await
+    }
+
+    /// Create the DBus object for the connected stream with the options of the builder.
+    pub(crate) async fn from_stream(
+        address: Option<Address>,
+        stream: Stream,
+        builder: DBusBuilder,
     ) -> DBusResult<(DBus, JoinHandle<()>)> {
         let (command_sender, command_receiver) = unbounded::<Command>();
+        let (send_command_sender, send_command_receiver) =
+            bounded_channel::<Command>(builder.send_queue_size);
 
         // Create and spawn the stream and sink task.
-        let (address, stream) = Stream::new(addressses).await?;
-        let (message_sink, message_stream) = stream.start();
+        let dropped_signals = Arc::new(AtomicUsize::new(0));
+        let stream_handle = stream.start(&builder, dropped_signals.clone());
 
         // Spawn the connection task.
-        let connection = Connection::from(command_receiver, message_sink, message_stream);
+        let pending_calls = Arc::new(AtomicUsize::new(0));
+        let protocol_error = Arc::new(OnceLock::new());
+        let connection = Connection::from(
+            command_receiver,
+            send_command_receiver,
+            stream_handle,
+            &builder,
+            pending_calls.clone(),
+            protocol_error.clone(),
+        );
         let connection_handle = spawn(connection.run());
 
-        let address = Arc::new(address);
+        let address = address.map(Arc::new);
         let dbus = DBus {
             command_sender,
+            send_command_sender,
             address,
+            pending_calls,
+            dropped_signals,
+            protocol_error,
+            call_timeout: builder.call_timeout,
+            handler_channel_size: builder.handler_channel_size,
+            reply_tracker: None,
         };
 
-        if introspectable {
-            add_introspect(dbus.clone())?;
+        if builder.introspectable {
+            add_introspect(dbus.clone(), builder.handler_channel_size)?;
+        }
+
+        if builder.peer {
+            add_peer(dbus.clone(), builder.handler_channel_size)?;
         }
 
-        if peer {
-            add_peer(dbus.clone())?;
+        if !builder.hello {
+            return Ok((dbus, connection_handle));
         }
 
         // Send the Hello message.
@@ -114,27 +184,209 @@ impl DBus {
         }
     }
 
-    /// Send a [`Message`](dbus_message_parser::message::Message).
-    pub fn send(&self, msg: Message) -> DBusResult<()> {
+    /// Send a [`Message`].
+    ///
+    /// If the send queue is full (see [`DBusBuilder::send_queue_size`]) then this function waits
+    /// until there is space in the queue.
+    ///
+    /// [`Message`]: dbus_message_parser::message::Message
+    /// [`DBusBuilder::send_queue_size`]: crate::DBusBuilder::send_queue_size
+    pub async fn send(&self, msg: Message) -> DBusResult<()> {
+        self.send_message(msg, None).await
+    }
+
+    /// Send a [`Message`]. If flags are given then they replace the flags of the message.
+    async fn send_message(&self, msg: Message, flags: Option<MessageFlags>) -> DBusResult<()> {
+        let is_reply = self.is_tracked_reply(&msg);
         // Try to send the message.
-        let command = Command::SendMessage(msg);
-        self.command_sender.unbounded_send(command)?;
+        let command = Command::SendMessage(msg, flags);
+        self.send_command_sender
+            .send(command)
+            .await
+            .map_err(|e| self.connection_error(e))?;
+        self.set_replied(is_reply);
         Ok(())
     }
 
+    /// Try to send a [`Message`] without waiting.
+    ///
+    /// If the send queue is full (see [`DBusBuilder::send_queue_size`]) then
+    /// [`DBusError::SendMessage`] is returned.
+    ///
+    /// [`Message`]: dbus_message_parser::message::Message
+    /// [`DBusBuilder::send_queue_size`]: crate::DBusBuilder::send_queue_size
+    pub fn try_send(&self, msg: Message) -> DBusResult<()> {
+        let is_reply = self.is_tracked_reply(&msg);
+        let command = Command::SendMessage(msg, None);
+        self.send_command_sender
+            .try_send(command)
+            .map_err(|e| self.connection_error(e))?;
+        self.set_replied(is_reply);
+        Ok(())
+    }
+
+    /// Get the size of the channels of the handlers (see [`DBusBuilder::handler_channel_size`]).
+    ///
+    /// [`DBusBuilder::handler_channel_size`]: crate::DBusBuilder::handler_channel_size
+    pub(crate) fn get_handler_channel_size(&self) -> usize {
+        self.handler_channel_size
+    }
+
+    /// Create a clone, which records if a reply to the method call with the given header is sent
+    /// through it. A message is a reply, if it has the serial of the method call as the reply
+    /// serial and the sender of the method call as the destination. The returned flag is set,
+    /// after the reply was sent.
+    pub(crate) fn track_reply(&self, header: &MessageHeader) -> (DBus, Arc<AtomicBool>) {
+        let replied = Arc::new(AtomicBool::new(false));
+        let mut dbus = self.clone();
+        let sender = header.get_sender().cloned();
+        dbus.reply_tracker = Some((header.get_serial(), sender, replied.clone()));
+        (dbus, replied)
+    }
+
+    /// Replace the error with the violation of the protocol, which closed the connection, if there
+    /// is one. Otherwise, the error is converted.
+    fn connection_error<E: Into<DBusError>>(&self, error: E) -> DBusError {
+        match self.protocol_error.get() {
+            Some(e) => DBusError::ProtocolError(e.clone()),
+            None => error.into(),
+        }
+    }
+
+    fn is_tracked_reply(&self, msg: &Message) -> bool {
+        match &self.reply_tracker {
+            Some((serial, sender, _)) => {
+                msg.get_reply_serial() == Some(*serial) && msg.get_destination() == sender.as_ref()
+            }
+            None => false,
+        }
+    }
+
+    fn set_replied(&self, is_reply: bool) {
+        if let (true, Some((_, _, replied))) = (is_reply, &self.reply_tracker) {
+            replied.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Send a [`Message`] with the flags of the [`CallOptions`].
+    ///
+    /// [`Message`]: dbus_message_parser::message::Message
+    /// [`CallOptions`]: crate::CallOptions
+    pub async fn send_with_options(&self, msg: Message, options: &CallOptions) -> DBusResult<()> {
+        self.send_message(msg, Some(options.get_flags())).await
+    }
+
     /// Send a [`Message`] and wait for a response.
     ///
-    /// The [`Message`] have to be a `MessageCall`.
+    /// The [`Message`] have to be a `MessageCall`. If the send queue is full then this function
+    /// waits until there is space in the queue. If a call timeout is configured (see
+    /// [`DBusBuilder::call_timeout`]) and no response is received in time then
+    /// [`DBusError::CallTimeout`] is returned.
+    ///
+    /// The flags of the [`Message`] are not checked, so a reply is always expected. Use
+    /// [`call_with_options`] to send a method call with the `NO_REPLY_EXPECTED` flag.
     ///
     /// [`Message`]: dbus_message_parser::message::Message
+    /// [`DBusBuilder::call_timeout`]: crate::DBusBuilder::call_timeout
+    /// [`call_with_options`]: #method.call_with_options
     pub async fn call(&self, msg: Message) -> DBusResult<Message> {
+        self.call_message(msg, None).await
+    }
+
+    /// Send a [`Message`] and wait for a response. If flags are given then they replace the flags
+    /// of the message.
+    async fn call_message(&self, msg: Message, flags: Option<MessageFlags>) -> DBusResult<Message> {
         // Create a oneshot channel for the response
-        let (msg_sender, msg_receiver) = channel::<Message>();
+        let (msg_sender, msg_receiver) = channel::<DBusResult<Message>>();
         // Try to send the message.
-        let command = Command::SendMessageOneshot(msg, msg_sender);
-        self.command_sender.unbounded_send(command)?;
-        let msg = msg_receiver.await?;
-        Ok(msg)
+        let command = Command::SendMessageOneshot(msg, flags, msg_sender);
+        self.send_command_sender
+            .send(command)
+            .await
+            .map_err(|e| self.connection_error(e))?;
+        let result = if let Some(call_timeout) = self.call_timeout {
+            match timeout(call_timeout, msg_receiver).await {
+                Ok(result) => result,
+                Err(_) => return Err(DBusError::CallTimeout(call_timeout)),
+            }
+        } else {
+            msg_receiver.await
+        };
+        result.map_err(|e| self.connection_error(e))?
+    }
+
+    /// Send a [`Message`] and wait for a response, which is not an error.
+    ///
+    /// If the response is an `Error` message then [`DBusError::MethodError`] is returned. The
+    /// error can be converted to a typed error with [`DBusError::to_typed`].
+    ///
+    /// [`Message`]: dbus_message_parser::message::Message
+    pub async fn call_checked(&self, msg: Message) -> DBusResult<Message> {
+        let reply = self.call(msg).await?;
+        if let MessageType::Error = reply.get_type() {
+            let name = reply.get_error_name().unwrap().clone();
+            let message = match reply.get_body().first() {
+                Some(Value::String(message)) => Some(message.clone()),
+                _ => None,
+            };
+            Err(DBusError::MethodError { name, message })
+        } else {
+            Ok(reply)
+        }
+    }
+
+    /// Send a [`Message`] with the flags of the [`CallOptions`] and wait for a response.
+    ///
+    /// If the `NO_REPLY_EXPECTED` flag is set then the connection does not wait for a response
+    /// and `None` is returned, as soon as the message is queued. Otherwise the response is
+    /// returned.
+    ///
+    /// [`Message`]: dbus_message_parser::message::Message
+    /// [`CallOptions`]: crate::CallOptions
+    pub async fn call_with_options(
+        &self,
+        msg: Message,
+        options: &CallOptions,
+    ) -> DBusResult<Option<Message>> {
+        let flags = options.get_flags();
+        if flags.contains(MessageFlags::NO_REPLY_EXPECTED) {
+            self.send_message(msg, Some(flags)).await?;
+            Ok(None)
+        } else {
+            self.call_message(msg, Some(flags)).await.map(Some)
+        }
+    }
+
+    /// Call a method with the given arguments and convert the reply to the return type.
+    ///
+    /// The arguments and the return values are tuples of types, which implement the
+    /// [`ToDBus`] and [`FromDBus`] traits. If the reply is an error then
+    /// [`DBusError::MethodError`] is returned. If the reply has different types then
+    /// [`DBusError::MarshalError`] is returned.
+    ///
+    /// [`ToDBus`]: crate::marshal::ToDBus
+    /// [`FromDBus`]: crate::marshal::FromDBus
+    pub async fn call_method<A, R>(
+        &self,
+        destination: Bus,
+        object_path: ObjectPath,
+        interface: Interface,
+        member: Member,
+        args: A,
+    ) -> DBusResult<R>
+    where
+        A: ToArgs,
+        R: FromArgs,
+    {
+        let mut msg = Message::method_call(destination, object_path, interface, member);
+        for value in args.to_args()? {
+            msg.add_value(value);
+        }
+        let reply = self.call_checked(msg).await?;
+        match reply.split() {
+            Ok((_, body)) => Ok(R::from_args(body)?),
+            Err(e) => Err(DBusError::MarshalError(e.into())),
+        }
     }
 
     /// Send a [`Message`] and specify a channel, where the response should be send.
@@ -148,12 +400,16 @@ impl DBus {
         msg: Message,
         msg_sender: MpscSender<Message>,
     ) -> DBusResult<u32> {
-        let (reply_serial_sender, reply_serial_receiver) = channel::<u32>();
+        let (reply_serial_sender, reply_serial_receiver) = channel::<DBusResult<u32>>();
         // Try to send the message.
-        let command = Command::SendMessageMpcs(msg, reply_serial_sender, msg_sender);
-        self.command_sender.unbounded_send(command)?;
-        let reply_serial = reply_serial_receiver.await?;
-        Ok(reply_serial)
+        let command = Command::SendMessageMpcs(msg, None, reply_serial_sender, msg_sender);
+        self.send_command_sender
+            .send(command)
+            .await
+            .map_err(|e| self.connection_error(e))?;
+        reply_serial_receiver
+            .await
+            .map_err(|e| self.connection_error(e))?
     }
 
     /// Call the [`Hello()`] method of the DBus daemon.
@@ -188,18 +444,38 @@ impl DBus {
     /// Add a channel to a specific [`ObjectPath`].
     ///
     /// The channel will receive all [`MethodCall`] messages for the specified [`ObjectPath`].
+    /// Use [`add_method_call_with_flags`] to receive the [`MessageFlags`] of the messages, too.
     ///
     /// If there is already channel added for this [`ObjectPath`] then it will be replace. So the
     /// old channel will not receive any [`MethodCall`] messages for the [`ObjectPath`] anymore.
     ///
     /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
     /// [`MethodCall`]: dbus_message_parser::message::MessageType::MethodCall
+    /// [`add_method_call_with_flags`]: #method.add_method_call_with_flags
+    /// [`MessageFlags`]: dbus_message_parser::message::MessageFlags
     pub fn add_method_call(
         &self,
         object_path: ObjectPath,
         sender: MpscSender<Message>,
     ) -> DBusResult<()> {
-        let command = Command::AddMethodCall(object_path, sender);
+        let command = Command::AddMethodCall(object_path, MethodCallSender::Message(sender));
+        self.command_sender.unbounded_send(command)?;
+        Ok(())
+    }
+
+    /// Add a channel to a specific [`ObjectPath`] like [`add_method_call`], which receives the
+    /// [`MethodCall`] messages together with their [`MessageFlags`].
+    ///
+    /// [`add_method_call`]: #method.add_method_call
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    /// [`MethodCall`]: dbus_message_parser::message::MessageType::MethodCall
+    /// [`MessageFlags`]: dbus_message_parser::message::MessageFlags
+    pub fn add_method_call_with_flags(
+        &self,
+        object_path: ObjectPath,
+        sender: MpscSender<(Message, MessageFlags)>,
+    ) -> DBusResult<()> {
+        let command = Command::AddMethodCall(object_path, MethodCallSender::WithFlags(sender));
         self.command_sender.unbounded_send(command)?;
         Ok(())
     }
@@ -221,7 +497,20 @@ impl DBus {
     ///
     /// [`add_method_call`]: #method.add_method_call
     pub fn delete_method_call_sender(&self, sender: MpscSender<Message>) -> DBusResult<()> {
-        let command = Command::DeleteMethodCallSender(sender);
+        let command = Command::DeleteMethodCallSender(MethodCallSender::Message(sender));
+        self.command_sender.unbounded_send(command)?;
+        Ok(())
+    }
+
+    /// Delete the channel for every [`ObjectPath`], which the given sender is connected to
+    /// (see [`add_method_call_with_flags`]).
+    ///
+    /// [`add_method_call_with_flags`]: #method.add_method_call_with_flags
+    pub fn delete_method_call_sender_with_flags(
+        &self,
+        sender: MpscSender<(Message, MessageFlags)>,
+    ) -> DBusResult<()> {
+        let command = Command::DeleteMethodCallSender(MethodCallSender::WithFlags(sender));
         self.command_sender.unbounded_send(command)?;
         Ok(())
     }
@@ -232,7 +521,21 @@ impl DBus {
     /// [`add_method_call`]: #method.add_method_call
     /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
     pub fn delete_method_call_receiver(&self, receiver: MpscReceiver<Message>) -> DBusResult<()> {
-        let command = Command::DeleteMethodCallReceiver(receiver);
+        let command = Command::DeleteMethodCallReceiver(MethodCallReceiver::Message(receiver));
+        self.command_sender.unbounded_send(command)?;
+        Ok(())
+    }
+
+    /// Delete the channel for every [`ObjectPath`], which the given sender is connected to
+    /// (see [`add_method_call_with_flags`]).
+    ///
+    /// [`add_method_call_with_flags`]: #method.add_method_call_with_flags
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    pub fn delete_method_call_receiver_with_flags(
+        &self,
+        receiver: MpscReceiver<(Message, MessageFlags)>,
+    ) -> DBusResult<()> {
+        let command = Command::DeleteMethodCallReceiver(MethodCallReceiver::WithFlags(receiver));
         self.command_sender.unbounded_send(command)?;
         Ok(())
     }
@@ -252,7 +555,24 @@ impl DBus {
         interface: Interface,
         sender: MpscSender<Message>,
     ) -> DBusResult<()> {
-        let command = Command::AddMethodCallInterface(interface, sender);
+        let command = Command::AddMethodCallInterface(interface, MethodCallSender::Message(sender));
+        self.command_sender.unbounded_send(command)?;
+        Ok(())
+    }
+
+    /// Add a channel to a specific [`Interface`] like [`add_method_call_interface`], which
+    /// receives the `MethodCall` messages together with their [`MessageFlags`].
+    ///
+    /// [`add_method_call_interface`]: #method.add_method_call_interface
+    /// [`Interface`]: dbus_message_parser::value::Interface
+    /// [`MessageFlags`]: dbus_message_parser::message::MessageFlags
+    pub fn add_method_call_interface_with_flags(
+        &self,
+        interface: Interface,
+        sender: MpscSender<(Message, MessageFlags)>,
+    ) -> DBusResult<()> {
+        let command =
+            Command::AddMethodCallInterface(interface, MethodCallSender::WithFlags(sender));
         self.command_sender.unbounded_send(command)?;
         Ok(())
     }
@@ -266,7 +586,21 @@ impl DBus {
         &self,
         sender: MpscSender<Message>,
     ) -> DBusResult<()> {
-        let command = Command::DeleteMethodCallInterfaceSender(sender);
+        let command = Command::DeleteMethodCallInterfaceSender(MethodCallSender::Message(sender));
+        self.command_sender.unbounded_send(command)?;
+        Ok(())
+    }
+
+    /// Delete the channel for every [`Interface`], which the given sender is connected to
+    /// (see [`add_method_call_interface_with_flags`]).
+    ///
+    /// [`add_method_call_interface_with_flags`]: #method.add_method_call_interface_with_flags
+    /// [`Interface`]: dbus_message_parser::value::Interface
+    pub fn delete_method_call_interface_sender_with_flags(
+        &self,
+        sender: MpscSender<(Message, MessageFlags)>,
+    ) -> DBusResult<()> {
+        let command = Command::DeleteMethodCallInterfaceSender(MethodCallSender::WithFlags(sender));
         self.command_sender.unbounded_send(command)?;
         Ok(())
     }
@@ -280,7 +614,23 @@ impl DBus {
         &self,
         receiver: MpscReceiver<Message>,
     ) -> DBusResult<()> {
-        let command = Command::DeleteMethodCallInterfaceReceiver(receiver);
+        let command =
+            Command::DeleteMethodCallInterfaceReceiver(MethodCallReceiver::Message(receiver));
+        self.command_sender.unbounded_send(command)?;
+        Ok(())
+    }
+
+    /// Delete the channel for every [`Interface`], which the given sender is connected to
+    /// (see [`add_method_call_interface_with_flags`]).
+    ///
+    /// [`add_method_call_interface_with_flags`]: #method.add_method_call_interface_with_flags
+    /// [`Interface`]: dbus_message_parser::value::Interface
+    pub fn delete_method_call_interface_receiver_with_flags(
+        &self,
+        receiver: MpscReceiver<(Message, MessageFlags)>,
+    ) -> DBusResult<()> {
+        let command =
+            Command::DeleteMethodCallInterfaceReceiver(MethodCallReceiver::WithFlags(receiver));
         self.command_sender.unbounded_send(command)?;
         Ok(())
     }
@@ -382,14 +732,118 @@ impl DBus {
         Ok(list)
     }
 
+    /// Call the [`Introspect()`] method of the given object and decode the introspection data.
+    ///
+    /// The child nodes only contain the names (see [`introspect_tree`]).
+    ///
+    /// [`Introspect()`]: https://dbus.freedesktop.org/doc/dbus-specification.html#standard-interfaces-introspectable
+    /// [`introspect_tree`]: #method.introspect_tree
+    pub async fn introspect(&self, destination: Bus, object_path: ObjectPath) -> DBusResult<Node> {
+        let msg = Message::method_call(
+            destination,
+            object_path,
+            "org.freedesktop.DBus.Introspectable".try_into().unwrap(),
+            "Introspect".try_into().unwrap(),
+        );
+        let msg = self.call(msg).await?;
+        if let MessageType::Error = msg.get_type() {
+            let error = msg.get_error_name().unwrap();
+            return Err(DBusError::Introspect(error.clone()));
+        }
+        match msg.get_body() {
+            [Value::String(xml)] => Ok(Node::decode(xml)?),
+            _ => Err(DBusError::IntrospectError(IntrospectError::Body)),
+        }
+    }
+
+    /// Introspect the given object and all child objects recursively.
+    ///
+    /// The returned [`Node`] contains the whole object hierarchy under the given [`ObjectPath`].
+    ///
+    /// [`Node`]: crate::introspect::Node
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    #[async_recursion]
+    pub async fn introspect_tree(
+        &self,
+        destination: Bus,
+        object_path: ObjectPath,
+    ) -> DBusResult<Node> {
+        let mut node = self
+            .introspect(destination.clone(), object_path.clone())
+            .await?;
+        for child in node.nodes.iter_mut() {
+            let name = if let Some(name) = child.name.take() {
+                name
+            } else {
+                continue;
+            };
+            let mut child_path = object_path.clone();
+            if !child_path.append(&name) {
+                return Err(DBusError::IntrospectError(IntrospectError::NodeName(name)));
+            }
+            *child = self
+                .introspect_tree(destination.clone(), child_path)
+                .await?;
+            child.name = Some(name);
+        }
+        Ok(node)
+    }
+
     /// Close the DBus connection.
     pub fn close(&self) -> DBusResult<()> {
         self.command_sender.unbounded_send(Command::Close)?;
         Ok(())
     }
 
+    /// Get the number of method calls, which wait for a reply.
+    ///
+    /// The method calls, which wait until they can be sent (see [`PendingCallPolicy::Wait`]), are
+    /// not counted.
+    ///
+    /// [`PendingCallPolicy::Wait`]: crate::PendingCallPolicy::Wait
+    pub fn get_pending_calls(&self) -> usize {
+        self.pending_calls.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of received signals, which were dropped because the receive queue was full
+    /// (see [`ReceivePolicy::DropSignals`]).
+    ///
+    /// [`ReceivePolicy::DropSignals`]: crate::ReceivePolicy::DropSignals
+    pub fn get_dropped_signals(&self) -> usize {
+        self.dropped_signals.load(Ordering::Relaxed)
+    }
+
+    /// Get the violation of the protocol, which closed the connection (see [`ProtocolError`]). If
+    /// the connection is open or was closed for another reason then `None` is returned.
+    ///
+    /// After the connection was closed by a violation, the sending functions return
+    /// [`DBusError::ProtocolError`] with this error.
+    ///
+    /// [`ProtocolError`]: crate::ProtocolError
+    pub fn get_protocol_error(&self) -> Option<&ProtocolError> {
+        self.protocol_error.get()
+    }
+
     /// Get the current path of the DBus daemon.
+    ///
+    /// # Panics
+    /// If the connection was created from a stream (see [`DBusBuilder::build_with_stream`]), for
+    /// example a connection of a `MockBus`, then there is no address and this function panics.
+    /// Use [`try_get_address`] instead.
+    ///
+    /// [`DBusBuilder::build_with_stream`]: crate::DBusBuilder::build_with_stream
+    /// [`try_get_address`]: #method.try_get_address
+    #[deprecated(note = "panics for connections without an address, use `try_get_address`")]
     pub fn get_address(&self) -> &Address {
-        self.address.as_ref()
+        self.try_get_address()
+            .expect("the connection was created from a stream without an address")
+    }
+
+    /// Get the current path of the DBus daemon. If the connection was created from a stream (see
+    /// [`DBusBuilder::build_with_stream`]) then there is no address.
+    ///
+    /// [`DBusBuilder::build_with_stream`]: crate::DBusBuilder::build_with_stream
+    pub fn try_get_address(&self) -> Option<&Address> {
+        self.address.as_deref()
     }
 }
@@ -1,19 +1,31 @@
-use crate::{command::Command, stream::StreamError};
+#[cfg(feature = "serde")]
+use crate::serde::SerdeError;
+use crate::{
+    command::Command,
+    introspect::IntrospectError,
+    marshal::MarshalError,
+    runtime::JoinError,
+    stream::{ProtocolError, StreamError},
+};
 use dbus_message_parser::{
     match_rule::MatchRule,
     message::Message,
-    value::{Error as ErrorName, Interface, ObjectPath},
+    value::{Error as ErrorName, Interface, Member, ObjectPath},
 };
 use futures::channel::{mpsc::TrySendError, oneshot::Canceled};
 use std::{
     fmt::{Display, Formatter, Result as FmtResult},
     io::{Error as IoError, ErrorKind as IoErrorKind},
+    time::Duration,
 };
 use thiserror::Error;
+use tokio::sync::mpsc::error::{
+    SendError as BoundedSendError, TrySendError as BoundedTrySendError,
+};
 
 #[derive(Debug, Error)]
 pub enum DBusError {
-    SendMessage(Message),
+    SendMessage(Box<Message>),
     AddMethodCall(ObjectPath),
     DeleteMethodCall(Option<ObjectPath>),
     ListMethodCall(ObjectPath),
@@ -23,19 +35,44 @@ pub enum DBusError {
     DeleteSignal,
     AddMatchRules(Vec<MatchRule>),
     DeleteMatchRules,
-    ReceiveMessage(Option<Message>),
+    ReceiveMessage(Option<Box<Message>>),
     StreamError(#[from] StreamError),
     DBusSessionBusAddress,
     Hello(ErrorName),
+    MethodError {
+        name: ErrorName,
+        message: Option<String>,
+    },
+    InvalidReply(Box<Message>),
+    MarshalError(#[from] MarshalError),
+    #[cfg(feature = "serde")]
+    SerdeError(#[from] SerdeError),
+    HandlerPanic(String),
+    PendingCallLimit(usize),
+    PendingCallEvicted(u32),
+    CallTimeout(Duration),
+    ProtocolError(#[from] ProtocolError),
+    Introspect(ErrorName),
+    IntrospectError(#[from] IntrospectError),
+    InterfaceNotDeclared(Interface),
+    SignalNotDeclared(Interface, Member),
+    SignalSignature {
+        member: Member,
+        expected: String,
+        got: String,
+    },
+    RuntimeThread(IoError),
+    Task(#[from] JoinError),
     Close,
 }
 
-impl From<TrySendError<Command>> for DBusError {
-    fn from(e: TrySendError<Command>) -> Self {
-        match e.into_inner() {
-            Command::SendMessage(msg) => DBusError::SendMessage(msg),
-            Command::SendMessageOneshot(msg, _) => DBusError::SendMessage(msg),
-            Command::SendMessageMpcs(msg, _, _) => DBusError::SendMessage(msg),
+impl DBusError {
+    /// Create the error for a command, which could not be sent to the connection task.
+    fn from_command(cmd: Command) -> DBusError {
+        match cmd {
+            Command::SendMessage(msg, _) => DBusError::SendMessage(Box::new(msg)),
+            Command::SendMessageOneshot(msg, _, _) => DBusError::SendMessage(Box::new(msg)),
+            Command::SendMessageMpcs(msg, _, _, _) => DBusError::SendMessage(Box::new(msg)),
             Command::AddMethodCall(object_path, _) => DBusError::AddMethodCall(object_path),
             Command::DeleteMethodCall(object_path) => {
                 DBusError::DeleteMethodCall(Some(object_path))
@@ -66,6 +103,27 @@ impl From<TrySendError<Command>> for DBusError {
     }
 }
 
+impl From<TrySendError<Command>> for DBusError {
+    fn from(e: TrySendError<Command>) -> Self {
+        DBusError::from_command(e.into_inner())
+    }
+}
+
+impl From<BoundedSendError<Command>> for DBusError {
+    fn from(e: BoundedSendError<Command>) -> Self {
+        DBusError::from_command(e.0)
+    }
+}
+
+impl From<BoundedTrySendError<Command>> for DBusError {
+    fn from(e: BoundedTrySendError<Command>) -> Self {
+        match e {
+            BoundedTrySendError::Full(cmd) => DBusError::from_command(cmd),
+            BoundedTrySendError::Closed(cmd) => DBusError::from_command(cmd),
+        }
+    }
+}
+
 impl From<Canceled> for DBusError {
     fn from(_: Canceled) -> Self {
         DBusError::ReceiveMessage(None)
@@ -126,6 +184,54 @@ impl Display for DBusError {
                 "DBUS_SESSION_BUS_ADDRESS environment variable is not defined"
             ),
             DBusError::Hello(e) => write!(f, "Hello: {}", e),
+            DBusError::MethodError { name, message } => {
+                write!(f, "Method call failed: {}", name)?;
+                if let Some(message) = message {
+                    write!(f, ": {}", message)
+                } else {
+                    Ok(())
+                }
+            }
+            DBusError::InvalidReply(msg) => write!(f, "Unexpected reply: {:?}", msg),
+            DBusError::MarshalError(e) => write!(f, "Could not convert arguments: {}", e),
+            #[cfg(feature = "serde")]
+            DBusError::SerdeError(e) => write!(f, "Could not serialize arguments: {}", e),
+            DBusError::HandlerPanic(e) => write!(f, "Handler panicked: {}", e),
+            DBusError::PendingCallLimit(limit) => {
+                write!(f, "Too many pending calls: the limit is {}", limit)
+            }
+            DBusError::PendingCallEvicted(serial) => write!(
+                f,
+                "Stopped waiting for the reply of the call with the serial {}",
+                serial
+            ),
+            DBusError::CallTimeout(duration) => {
+                write!(f, "No reply received within {:?}", duration)
+            }
+            DBusError::ProtocolError(e) => write!(f, "Connection closed: {}", e),
+            DBusError::Introspect(e) => write!(f, "Introspect: {}", e),
+            DBusError::IntrospectError(e) => write!(f, "Could not introspect: {}", e),
+            DBusError::InterfaceNotDeclared(interface) => write!(
+                f,
+                "The interface {} is not added to the object server",
+                interface
+            ),
+            DBusError::SignalNotDeclared(interface, member) => write!(
+                f,
+                "The signal {} is not declared in the interface {}",
+                member, interface
+            ),
+            DBusError::SignalSignature {
+                member,
+                expected,
+                got,
+            } => write!(
+                f,
+                "The signal {} is emitted with a different signature: expected '{}' got '{}'",
+                member, expected, got
+            ),
+            DBusError::RuntimeThread(e) => write!(f, "Could not start the runtime thread: {}", e),
+            DBusError::Task(e) => write!(f, "Task did not complete: {}", e),
             DBusError::Close => write!(f, "Could not close DBus"),
         }
     }
@@ -0,0 +1,161 @@
+use crate::{DBusError, DBusErrorReply};
+use dbus_message_parser::value::Error as ErrorName;
+use std::{
+    convert::TryInto,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// A trait to map the name and the message of a DBus error to a typed Rust error and back.
+///
+/// The trait is used on the client side to convert a [`DBusError::MethodError`] (see
+/// [`DBusError::to_typed`]) and on the server side to create the error reply (see
+/// [`DBusErrorReply::from_typed`]).
+///
+/// [`DBusErrorReply::from_typed`]: crate::DBusErrorReply::from_typed
+pub trait ErrorMapping: Sized {
+    /// Convert the name and the message of a DBus error. If the name is unknown then `None` is
+    /// returned.
+    fn from_error(name: &ErrorName, message: Option<&str>) -> Option<Self>;
+
+    /// Convert the error to the name and the message of a DBus error.
+    fn to_error(&self) -> (ErrorName, String);
+}
+
+macro_rules! standard_error {
+    ($($(#[$doc:meta])* $variant:ident => $name:expr),+ $(,)?) => {
+        /// The well-known errors of the DBus specification and the DBus daemon. The value of
+        /// every variant is the message of the error.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum StandardError {
+            $($(#[$doc])* $variant(String)),+
+        }
+
+        impl ErrorMapping for StandardError {
+            fn from_error(name: &ErrorName, message: Option<&str>) -> Option<Self> {
+                let message = message.unwrap_or_default().to_string();
+                match name.as_ref() {
+                    $($name => Some(StandardError::$variant(message)),)+
+                    _ => None,
+                }
+            }
+
+            fn to_error(&self) -> (ErrorName, String) {
+                let (name, message) = match self {
+                    $(StandardError::$variant(message) => ($name, message),)+
+                };
+                // The unwrap function call will never panic because all names are valid.
+                (name.try_into().unwrap(), message.clone())
+            }
+        }
+    };
+}
+
+standard_error! {
+    /// `org.freedesktop.DBus.Error.Failed`
+    Failed => "org.freedesktop.DBus.Error.Failed",
+    /// `org.freedesktop.DBus.Error.NoMemory`
+    NoMemory => "org.freedesktop.DBus.Error.NoMemory",
+    /// `org.freedesktop.DBus.Error.ServiceUnknown`
+    ServiceUnknown => "org.freedesktop.DBus.Error.ServiceUnknown",
+    /// `org.freedesktop.DBus.Error.NameHasNoOwner`
+    NameHasNoOwner => "org.freedesktop.DBus.Error.NameHasNoOwner",
+    /// `org.freedesktop.DBus.Error.NoReply`
+    NoReply => "org.freedesktop.DBus.Error.NoReply",
+    /// `org.freedesktop.DBus.Error.IOError`
+    IoError => "org.freedesktop.DBus.Error.IOError",
+    /// `org.freedesktop.DBus.Error.BadAddress`
+    BadAddress => "org.freedesktop.DBus.Error.BadAddress",
+    /// `org.freedesktop.DBus.Error.NotSupported`
+    NotSupported => "org.freedesktop.DBus.Error.NotSupported",
+    /// `org.freedesktop.DBus.Error.LimitsExceeded`
+    LimitsExceeded => "org.freedesktop.DBus.Error.LimitsExceeded",
+    /// `org.freedesktop.DBus.Error.AccessDenied`
+    AccessDenied => "org.freedesktop.DBus.Error.AccessDenied",
+    /// `org.freedesktop.DBus.Error.AuthFailed`
+    AuthFailed => "org.freedesktop.DBus.Error.AuthFailed",
+    /// `org.freedesktop.DBus.Error.NoServer`
+    NoServer => "org.freedesktop.DBus.Error.NoServer",
+    /// `org.freedesktop.DBus.Error.Timeout`
+    Timeout => "org.freedesktop.DBus.Error.Timeout",
+    /// `org.freedesktop.DBus.Error.NoNetwork`
+    NoNetwork => "org.freedesktop.DBus.Error.NoNetwork",
+    /// `org.freedesktop.DBus.Error.AddressInUse`
+    AddressInUse => "org.freedesktop.DBus.Error.AddressInUse",
+    /// `org.freedesktop.DBus.Error.Disconnected`
+    Disconnected => "org.freedesktop.DBus.Error.Disconnected",
+    /// `org.freedesktop.DBus.Error.InvalidArgs`
+    InvalidArgs => "org.freedesktop.DBus.Error.InvalidArgs",
+    /// `org.freedesktop.DBus.Error.FileNotFound`
+    FileNotFound => "org.freedesktop.DBus.Error.FileNotFound",
+    /// `org.freedesktop.DBus.Error.FileExists`
+    FileExists => "org.freedesktop.DBus.Error.FileExists",
+    /// `org.freedesktop.DBus.Error.UnknownMethod`
+    UnknownMethod => "org.freedesktop.DBus.Error.UnknownMethod",
+    /// `org.freedesktop.DBus.Error.UnknownObject`
+    UnknownObject => "org.freedesktop.DBus.Error.UnknownObject",
+    /// `org.freedesktop.DBus.Error.UnknownInterface`
+    UnknownInterface => "org.freedesktop.DBus.Error.UnknownInterface",
+    /// `org.freedesktop.DBus.Error.UnknownProperty`
+    UnknownProperty => "org.freedesktop.DBus.Error.UnknownProperty",
+    /// `org.freedesktop.DBus.Error.PropertyReadOnly`
+    PropertyReadOnly => "org.freedesktop.DBus.Error.PropertyReadOnly",
+    /// `org.freedesktop.DBus.Error.TimedOut`
+    TimedOut => "org.freedesktop.DBus.Error.TimedOut",
+    /// `org.freedesktop.DBus.Error.MatchRuleNotFound`
+    MatchRuleNotFound => "org.freedesktop.DBus.Error.MatchRuleNotFound",
+    /// `org.freedesktop.DBus.Error.MatchRuleInvalid`
+    MatchRuleInvalid => "org.freedesktop.DBus.Error.MatchRuleInvalid",
+    /// `org.freedesktop.DBus.Error.InteractiveAuthorizationRequired`
+    InteractiveAuthorizationRequired => "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired",
+}
+
+impl Display for StandardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let (name, message) = self.to_error();
+        write!(f, "{}: {}", name, message)
+    }
+}
+
+impl ErrorMapping for DBusErrorReply {
+    fn from_error(name: &ErrorName, message: Option<&str>) -> Option<Self> {
+        let message = message.unwrap_or_default().to_string();
+        Some(DBusErrorReply::new(name.clone(), message))
+    }
+
+    fn to_error(&self) -> (ErrorName, String) {
+        (self.get_name().clone(), self.get_message().to_string())
+    }
+}
+
+impl DBusError {
+    /// Convert a [`DBusError::MethodError`] to a typed error (see [`ErrorMapping`]).
+    ///
+    /// If the error is another variant or the name of the error is unknown then `None` is
+    /// returned.
+    pub fn to_typed<E>(&self) -> Option<E>
+    where
+        E: ErrorMapping,
+    {
+        match self {
+            DBusError::MethodError { name, message } => E::from_error(name, message.as_deref()),
+            _ => None,
+        }
+    }
+}
+
+impl DBusErrorReply {
+    /// Create an error reply from a typed error (see [`ErrorMapping`]).
+    pub fn from_typed<E>(error: &E) -> DBusErrorReply
+    where
+        E: ErrorMapping,
+    {
+        let (name, message) = error.to_error();
+        DBusErrorReply::new(name, message)
+    }
+}
+
+impl From<StandardError> for DBusErrorReply {
+    fn from(error: StandardError) -> Self {
+        DBusErrorReply::from_typed(&error)
+    }
+}
@@ -0,0 +1,4 @@
+//! The crates, which are used in the public API and by the generated code of the `codegen` module,
+//! so the generated code only depends on `dbus-async`.
+pub use async_trait;
+pub use dbus_message_parser;
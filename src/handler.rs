@@ -1,62 +1,649 @@
-use crate::{DBus, DBusResult};
+use crate::{Binding, DBus, DBusError, DBusResult};
 use async_trait::async_trait;
-use dbus_message_parser::{message::Message, value::ObjectPath};
+use dbus_message_parser::{
+    message::{Message, MessageFlags, MessageHeader, MessageType},
+    value::{Error as ErrorName, ObjectPath},
+};
 use futures::{
-    channel::mpsc::{channel, Receiver},
+    channel::{
+        mpsc::{channel, Receiver},
+        oneshot::{
+            channel as oneshot_channel, Receiver as OneshotReceiver, Sender as OneshotSender,
+        },
+    },
+    future::{pending, Either},
     lock::Mutex,
-    StreamExt,
+    select,
+    stream::FuturesUnordered,
+    FutureExt, Stream, StreamExt,
+};
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    convert::TryInto,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{atomic::Ordering, Arc},
 };
-use std::sync::Arc;
 
 /// A trait for the generic `Message` handler.
 #[async_trait]
 pub trait Handler: Send {
     /// Handle the `Message`.
     async fn handle(&mut self, dbus: &DBus, msg: Message) -> DBusResult<()>;
+
+    /// Handle the `Message`, which was received with the given [`MessageFlags`].
+    ///
+    /// The bindings (see [`Binder`]) call this method. The default implementation ignores the
+    /// flags and calls [`handle`].
+    ///
+    /// [`MessageFlags`]: dbus_message_parser::message::MessageFlags
+    /// [`handle`]: #tymethod.handle
+    async fn handle_with_flags(
+        &mut self,
+        dbus: &DBus,
+        msg: Message,
+        _flags: MessageFlags,
+    ) -> DBusResult<()> {
+        self.handle(dbus, msg).await
+    }
+}
+
+/// A trait for the generic `Message` handler, which can handle multiple messages at the same time
+/// (see [`ConcurrentBinder`]).
+#[async_trait]
+pub trait SharedHandler: Send + Sync {
+    /// Handle the `Message`.
+    async fn handle(&self, dbus: &DBus, msg: Message) -> DBusResult<()>;
+
+    /// Handle the `Message`, which was received with the given [`MessageFlags`].
+    ///
+    /// The bindings (see [`ConcurrentBinder`]) call this method. The default implementation
+    /// ignores the flags and calls [`handle`].
+    ///
+    /// [`MessageFlags`]: dbus_message_parser::message::MessageFlags
+    /// [`handle`]: #tymethod.handle
+    async fn handle_with_flags(
+        &self,
+        dbus: &DBus,
+        msg: Message,
+        _flags: MessageFlags,
+    ) -> DBusResult<()> {
+        self.handle(dbus, msg).await
+    }
+}
+
+#[async_trait]
+impl<T> SharedHandler for Arc<T>
+where
+    T: SharedHandler + ?Sized,
+{
+    async fn handle(&self, dbus: &DBus, msg: Message) -> DBusResult<()> {
+        self.as_ref().handle(dbus, msg).await
+    }
+
+    async fn handle_with_flags(
+        &self,
+        dbus: &DBus,
+        msg: Message,
+        flags: MessageFlags,
+    ) -> DBusResult<()> {
+        self.as_ref().handle_with_flags(dbus, msg, flags).await
+    }
+}
+
+/// The policy of a binding, if the [`Handler`] returns an error or panics.
+///
+/// A panic of the [`Handler`] is converted to a [`DBusError::HandlerPanic`]. If the [`Handler`]
+/// already replied to the method call through the given [`DBus`] object then no error is sent.
+///
+/// [`DBusError::HandlerPanic`]: crate::DBusError::HandlerPanic
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ErrorPolicy {
+    /// Stop the binding and return the error.
+    #[default]
+    Stop,
+    /// Reply with an `org.freedesktop.DBus.Error.Failed` error to the caller and keep serving.
+    Reply,
+    /// Reply with the error name and the message returned by the function to the caller and keep
+    /// serving.
+    Map(fn(&DBusError) -> (ErrorName, String)),
+}
+
+/// The order in which a [`ConcurrentBinder`] handles the messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CallOrdering {
+    /// The messages are handled in any order.
+    #[default]
+    Unordered,
+    /// The messages of the same sender are handled one after another in the order of arrival.
+    /// The messages of different senders are handled concurrently.
+    PerSender,
+}
+
+/// A handle to stop a binding (see [`BindOptions::shutdown_handle`]).
+///
+/// Dropping the handle does not stop the binding.
+pub struct ShutdownHandle(OneshotSender<()>);
+
+impl ShutdownHandle {
+    /// Stop the binding. The binding returns `Ok(())` after the current message is handled.
+    pub fn shutdown(self) {
+        // If the binding already ended then there is nothing to stop.
+        let _ = self.0.send(());
+    }
+}
+
+/// The default number of messages, which are handled at the same time by a [`ConcurrentBinder`].
+pub const DEFAULT_CONCURRENCY_LIMIT: usize = 16;
+
+/// The options of a binding (see [`Binder::bind_with_options`] and
+/// [`ConcurrentBinder::bind_concurrent_with_options`]).
+pub struct BindOptions {
+    error_policy: ErrorPolicy,
+    shutdown: Option<OneshotReceiver<()>>,
+    concurrency_limit: usize,
+    ordering: CallOrdering,
+}
+
+impl Default for BindOptions {
+    fn default() -> Self {
+        BindOptions {
+            error_policy: ErrorPolicy::default(),
+            shutdown: None,
+            concurrency_limit: DEFAULT_CONCURRENCY_LIMIT,
+            ordering: CallOrdering::default(),
+        }
+    }
+}
+
+impl BindOptions {
+    /// Create the default options: the binding stops on the first error and can only be stopped
+    /// by closing the channel.
+    pub fn new() -> BindOptions {
+        BindOptions::default()
+    }
+
+    /// Set the [`ErrorPolicy`].
+    pub fn error_policy(mut self, error_policy: ErrorPolicy) -> BindOptions {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Set the maximum number of messages, which are handled or wait for their turn at the same
+    /// time. A limit of `0` is treated as `1`.
+    ///
+    /// This option is only used by a [`ConcurrentBinder`].
+    pub fn concurrency_limit(mut self, concurrency_limit: usize) -> BindOptions {
+        self.concurrency_limit = concurrency_limit.max(1);
+        self
+    }
+
+    /// Set the [`CallOrdering`].
+    ///
+    /// This option is only used by a [`ConcurrentBinder`].
+    pub fn ordering(mut self, ordering: CallOrdering) -> BindOptions {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Create a [`ShutdownHandle`] to stop the binding explicitly.
+    ///
+    /// If this method is called multiple times then only the last handle can stop the binding.
+    pub fn shutdown_handle(&mut self) -> ShutdownHandle {
+        let (sender, receiver) = oneshot_channel();
+        self.shutdown = Some(receiver);
+        ShutdownHandle(sender)
+    }
 }
 
 #[async_trait]
 pub trait Binder: Sized {
     async fn bind(self, dbus: DBus, object_path: ObjectPath) -> DBusResult<()> {
-        let (sender, receiver) = channel(128);
-        dbus.add_method_call(object_path, sender)?;
-        self.bind_by_receiver(dbus, receiver).await
+        self.bind_with_options(dbus, object_path, BindOptions::default())
+            .await
+    }
+
+    async fn bind_by_receiver(self, dbus: DBus, receiver: Receiver<Message>) -> DBusResult<()> {
+        self.bind_by_receiver_with_options(dbus, receiver, BindOptions::default())
+            .await
+    }
+
+    async fn bind_with_options(
+        self,
+        dbus: DBus,
+        object_path: ObjectPath,
+        options: BindOptions,
+    ) -> DBusResult<()> {
+        let (sender, receiver) = channel(dbus.get_handler_channel_size());
+        dbus.add_method_call_with_flags(object_path, sender)?;
+        self.bind_by_stream_with_options(dbus, receiver, options)
+            .await
     }
 
-    async fn bind_by_receiver(self, dbus: DBus, receiver: Receiver<Message>) -> DBusResult<()>;
+    /// Handle the messages of the receiver with the given options. The flags of the messages are
+    /// unknown, so the messages are handled as if no flag is set.
+    async fn bind_by_receiver_with_options(
+        self,
+        dbus: DBus,
+        receiver: Receiver<Message>,
+        options: BindOptions,
+    ) -> DBusResult<()> {
+        let stream = receiver.map(|msg| (msg, MessageFlags::empty()));
+        self.bind_by_stream_with_options(dbus, stream, options)
+            .await
+    }
+
+    /// Handle the messages of the stream, which yields the messages together with their
+    /// [`MessageFlags`] (see [`DBus::add_method_call_with_flags`]), with the given options.
+    ///
+    /// [`MessageFlags`]: dbus_message_parser::message::MessageFlags
+    /// [`DBus::add_method_call_with_flags`]: crate::DBus::add_method_call_with_flags
+    async fn bind_by_stream_with_options<S>(
+        self,
+        dbus: DBus,
+        stream: S,
+        options: BindOptions,
+    ) -> DBusResult<()>
+    where
+        S: Stream<Item = (Message, MessageFlags)> + Send + Unpin + 'static;
+
+    /// Bind the object to the [`ObjectPath`] in a new task and return a [`Binding`] handle.
+    ///
+    /// The [`ObjectPath`] is unregistered, if the handle is dropped.
+    ///
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    fn spawn(self, dbus: DBus, object_path: ObjectPath) -> DBusResult<Binding>
+    where
+        Self: Send + 'static,
+    {
+        self.spawn_with_options(dbus, object_path, BindOptions::default())
+    }
+
+    /// Bind the object to the [`ObjectPath`] with the given options in a new task and return a
+    /// [`Binding`] handle.
+    ///
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    fn spawn_with_options(
+        self,
+        dbus: DBus,
+        object_path: ObjectPath,
+        options: BindOptions,
+    ) -> DBusResult<Binding>
+    where
+        Self: Send + 'static,
+    {
+        let (sender, receiver) = channel(dbus.get_handler_channel_size());
+        dbus.add_method_call_with_flags(object_path.clone(), sender.clone())?;
+        let binding = self.bind_by_stream_with_options(dbus.clone(), receiver, options);
+        Ok(Binding::new(dbus, object_path, sender, binding))
+    }
+}
+
+/// Wait until the binding should be stopped.
+async fn shutdown(receiver: Option<OneshotReceiver<()>>) {
+    if let Some(receiver) = receiver {
+        if receiver.await.is_ok() {
+            return;
+        }
+    }
+    // There is no handle anymore, so the binding cannot be stopped.
+    pending().await
+}
+
+pub(crate) fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Handle the `Message` with the given function and apply the [`ErrorPolicy`], if the function
+/// returns an error or panics.
+async fn handle_message<F, R>(
+    dbus: &DBus,
+    msg: Message,
+    flags: MessageFlags,
+    error_policy: ErrorPolicy,
+    handle: F,
+) -> DBusResult<()>
+where
+    F: FnOnce(DBus, Message, MessageFlags) -> R,
+    R: Future<Output = DBusResult<()>>,
+{
+    // Keep the header of a method call to be able to reply with an error.
+    let (header, msg): (Option<MessageHeader>, Message) = match error_policy {
+        ErrorPolicy::Reply | ErrorPolicy::Map(_) if msg.get_type() == MessageType::MethodCall => {
+            match msg.split() {
+                Ok((header, body)) => (Some(header.clone()), Message::new(header, body)),
+                Err(e) => {
+                    error!("Could not get the signature of the method call: {}", e);
+                    return Ok(());
+                }
+            }
+        }
+        _ => (None, msg),
+    };
+
+    // Record if the handler replies by itself, so the caller does not get a second reply.
+    let (handler_dbus, replied) = match &header {
+        Some(header) => {
+            let (dbus, replied) = dbus.track_reply(header);
+            (dbus, Some(replied))
+        }
+        None => (dbus.clone(), None),
+    };
+    let result = AssertUnwindSafe(handle(handler_dbus, msg, flags))
+        .catch_unwind()
+        .await;
+    let error = match result {
+        Ok(Ok(())) => return Ok(()),
+        Ok(Err(e)) => e,
+        Err(panic) => DBusError::HandlerPanic(panic_message(panic)),
+    };
+
+    let (name, message) = match error_policy {
+        ErrorPolicy::Stop => return Err(error),
+        ErrorPolicy::Reply => (
+            "org.freedesktop.DBus.Error.Failed".try_into().unwrap(),
+            error.to_string(),
+        ),
+        ErrorPolicy::Map(map) => map(&error),
+    };
+    error!("Could not handle message: {}", error);
+    if matches!(replied, Some(replied) if replied.load(Ordering::Relaxed)) {
+        return Ok(());
+    }
+    match header {
+        Some(header) if !flags.contains(MessageFlags::NO_REPLY_EXPECTED) => {
+            dbus.send(header.error(name, message)).await
+        }
+        _ => Ok(()),
+    }
 }
 
-// TODO: Wait until specialization https://github.com/rust-lang/rust/issues/31844 is finished to
-// define a default impl with Deletable
 #[async_trait]
 impl<T> Binder for T
 where
     T: Handler,
 {
-    async fn bind_by_receiver(
+    async fn bind_by_stream_with_options<S>(
         mut self,
         dbus: DBus,
-        mut receiver: Receiver<Message>,
-    ) -> DBusResult<()> {
-        while let Some(msg) = receiver.next().await {
-            self.handle(&dbus, msg).await?;
+        stream: S,
+        options: BindOptions,
+    ) -> DBusResult<()>
+    where
+        S: Stream<Item = (Message, MessageFlags)> + Send + Unpin + 'static,
+    {
+        let mut receiver = stream.take_until(Box::pin(shutdown(options.shutdown)));
+        while let Some((msg, flags)) = receiver.next().await {
+            let handler = &mut self;
+            handle_message(
+                &dbus,
+                msg,
+                flags,
+                options.error_policy,
+                |dbus, msg, flags| async move { handler.handle_with_flags(&dbus, msg, flags).await },
+            )
+            .await?;
         }
         Ok(())
     }
 }
 
-// TODO: Wait until specialization https://github.com/rust-lang/rust/issues/31844 is finished to
-// define a default impl with Deletable
 #[async_trait]
 impl<T> Binder for Arc<Mutex<T>>
 where
     T: Handler,
 {
-    async fn bind_by_receiver(self, dbus: DBus, mut receiver: Receiver<Message>) -> DBusResult<()> {
-        while let Some(msg) = receiver.next().await {
+    async fn bind_by_stream_with_options<S>(
+        self,
+        dbus: DBus,
+        stream: S,
+        options: BindOptions,
+    ) -> DBusResult<()>
+    where
+        S: Stream<Item = (Message, MessageFlags)> + Send + Unpin + 'static,
+    {
+        let mut receiver = stream.take_until(Box::pin(shutdown(options.shutdown)));
+        while let Some((msg, flags)) = receiver.next().await {
             let mut guard = self.lock().await;
-            guard.handle(&dbus, msg).await?;
+            handle_message(
+                &dbus,
+                msg,
+                flags,
+                options.error_policy,
+                |dbus, msg, flags| async move { guard.handle_with_flags(&dbus, msg, flags).await },
+            )
+            .await?;
         }
         Ok(())
     }
 }
+
+/// A trait to bind a [`SharedHandler`], which handles multiple messages at the same time.
+///
+/// The messages are handled concurrently by the future of the binding, so a slow method does
+/// not block the other callers. If the future is dropped then the messages, which are handled,
+/// are cancelled. At most [`BindOptions::concurrency_limit`] messages are handled or wait for their turn at the
+/// same time.
+///
+/// If the [`ErrorPolicy`] is `Stop` then the binding stops receiving messages on the first error,
+/// waits until the messages, which are already handled, are finished and returns the error. The
+/// messages, which wait for their turn, are dropped. If the binding is stopped by a
+/// [`ShutdownHandle`] or the channel is closed then all received messages are handled, before the
+/// binding returns.
+#[async_trait]
+pub trait ConcurrentBinder: Sized {
+    async fn bind_concurrent(self, dbus: DBus, object_path: ObjectPath) -> DBusResult<()> {
+        self.bind_concurrent_with_options(dbus, object_path, BindOptions::default())
+            .await
+    }
+
+    async fn bind_concurrent_with_options(
+        self,
+        dbus: DBus,
+        object_path: ObjectPath,
+        options: BindOptions,
+    ) -> DBusResult<()> {
+        let (sender, receiver) = channel(dbus.get_handler_channel_size());
+        dbus.add_method_call_with_flags(object_path, sender)?;
+        self.bind_concurrent_by_stream_with_options(dbus, receiver, options)
+            .await
+    }
+
+    /// Handle the messages of the receiver with the given options. The flags of the messages are
+    /// unknown, so the messages are handled as if no flag is set.
+    async fn bind_concurrent_by_receiver_with_options(
+        self,
+        dbus: DBus,
+        receiver: Receiver<Message>,
+        options: BindOptions,
+    ) -> DBusResult<()> {
+        let stream = receiver.map(|msg| (msg, MessageFlags::empty()));
+        self.bind_concurrent_by_stream_with_options(dbus, stream, options)
+            .await
+    }
+
+    /// Handle the messages of the stream, which yields the messages together with their
+    /// [`MessageFlags`] (see [`DBus::add_method_call_with_flags`]), with the given options.
+    ///
+    /// [`MessageFlags`]: dbus_message_parser::message::MessageFlags
+    /// [`DBus::add_method_call_with_flags`]: crate::DBus::add_method_call_with_flags
+    async fn bind_concurrent_by_stream_with_options<S>(
+        self,
+        dbus: DBus,
+        stream: S,
+        options: BindOptions,
+    ) -> DBusResult<()>
+    where
+        S: Stream<Item = (Message, MessageFlags)> + Send + Unpin + 'static;
+
+    /// Bind the object to the [`ObjectPath`] in a new task and return a [`Binding`] handle.
+    ///
+    /// The [`ObjectPath`] is unregistered, if the handle is dropped.
+    ///
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    fn spawn_concurrent(self, dbus: DBus, object_path: ObjectPath) -> DBusResult<Binding>
+    where
+        Self: Send + 'static,
+    {
+        self.spawn_concurrent_with_options(dbus, object_path, BindOptions::default())
+    }
+
+    /// Bind the object to the [`ObjectPath`] with the given options in a new task and return a
+    /// [`Binding`] handle.
+    ///
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    fn spawn_concurrent_with_options(
+        self,
+        dbus: DBus,
+        object_path: ObjectPath,
+        options: BindOptions,
+    ) -> DBusResult<Binding>
+    where
+        Self: Send + 'static,
+    {
+        let (sender, receiver) = channel(dbus.get_handler_channel_size());
+        dbus.add_method_call_with_flags(object_path.clone(), sender.clone())?;
+        let binding = self.bind_concurrent_by_stream_with_options(dbus.clone(), receiver, options);
+        Ok(Binding::new(dbus, object_path, sender, binding))
+    }
+}
+
+/// Handle the `Message` with the shared handler. Returns the sender of the message, if the
+/// messages are ordered per sender.
+async fn handle_shared_message<T>(
+    handler: Arc<T>,
+    dbus: DBus,
+    (msg, flags): (Message, MessageFlags),
+    key: Option<String>,
+    error_policy: ErrorPolicy,
+) -> (Option<String>, DBusResult<()>)
+where
+    T: SharedHandler + 'static,
+{
+    let result = handle_message(
+        &dbus,
+        msg,
+        flags,
+        error_policy,
+        |dbus, msg, flags| async move { handler.handle_with_flags(&dbus, msg, flags).await },
+    )
+    .await;
+    (key, result)
+}
+
+#[async_trait]
+impl<T> ConcurrentBinder for T
+where
+    T: SharedHandler + 'static,
+{
+    async fn bind_concurrent_by_stream_with_options<S>(
+        self,
+        dbus: DBus,
+        stream: S,
+        options: BindOptions,
+    ) -> DBusResult<()>
+    where
+        S: Stream<Item = (Message, MessageFlags)> + Send + Unpin + 'static,
+    {
+        let handler = Arc::new(self);
+        let mut receiver = stream.take_until(Box::pin(shutdown(options.shutdown)));
+        let mut running = FuturesUnordered::new();
+        // The messages, which wait until the previous message of the same sender is handled.
+        // A sender is in the map as long as one of its messages is handled.
+        let mut queues: HashMap<String, VecDeque<(Message, MessageFlags)>> = HashMap::new();
+        // The number of messages, which are handled or wait for their turn.
+        let mut pending = 0;
+        let mut closed = false;
+        // The first error, if the error policy is `Stop`.
+        let mut error = None;
+        loop {
+            let next = if !closed && pending < options.concurrency_limit {
+                select! {
+                    msg = receiver.next() => Either::Left(msg),
+                    result = running.select_next_some() => Either::Right(result),
+                }
+            } else if let Some(result) = running.next().await {
+                Either::Right(result)
+            } else {
+                break;
+            };
+            match next {
+                Either::Left(msg) => {
+                    let msg = if let Some(msg) = msg {
+                        msg
+                    } else {
+                        closed = true;
+                        continue;
+                    };
+                    pending += 1;
+                    let key = match options.ordering {
+                        CallOrdering::Unordered => None,
+                        CallOrdering::PerSender => Some(
+                            msg.0
+                                .get_sender()
+                                .map(|sender| sender.as_ref().to_string())
+                                .unwrap_or_default(),
+                        ),
+                    };
+                    if let Some(key) = key {
+                        if let Some(queue) = queues.get_mut(&key) {
+                            queue.push_back(msg);
+                        } else {
+                            queues.insert(key.clone(), VecDeque::new());
+                            running.push(handle_shared_message(
+                                handler.clone(),
+                                dbus.clone(),
+                                msg,
+                                Some(key),
+                                options.error_policy,
+                            ));
+                        }
+                    } else {
+                        running.push(handle_shared_message(
+                            handler.clone(),
+                            dbus.clone(),
+                            msg,
+                            None,
+                            options.error_policy,
+                        ));
+                    }
+                }
+                Either::Right((key, result)) => {
+                    pending -= 1;
+                    if let Err(e) = result {
+                        // Stop receiving and wait until the other messages are handled.
+                        closed = true;
+                        queues.clear();
+                        error.get_or_insert(e);
+                        continue;
+                    }
+                    if let Some(key) = key {
+                        let msg = queues.get_mut(&key).and_then(|queue| queue.pop_front());
+                        if let Some(msg) = msg {
+                            running.push(handle_shared_message(
+                                handler.clone(),
+                                dbus.clone(),
+                                msg,
+                                Some(key),
+                                options.error_policy,
+                            ));
+                        } else {
+                            queues.remove(&key);
+                        }
+                    }
+                }
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
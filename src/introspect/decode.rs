@@ -0,0 +1,248 @@
+use super::{
+    Access, Annotation, Arg, Direction, Interface, IntrospectError, Method, Node, Property, Signal,
+};
+use quick_xml::{events::Event, Reader};
+use std::{collections::HashMap, str::from_utf8};
+
+/// An element of the XML document, which is relevant for the introspection data.
+enum Element {
+    Start(String, HashMap<String, String>),
+    End,
+}
+
+struct Decoder<'a> {
+    reader: Reader<&'a [u8]>,
+    buffer: Vec<u8>,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(xml: &'a str) -> Decoder<'a> {
+        let mut reader = Reader::from_str(xml);
+        reader.expand_empty_elements(true).trim_text(true);
+        Decoder {
+            reader,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Get the next start or end tag. All other events (text, comments, doctype, ...) are skipped.
+    fn next(&mut self) -> Result<Element, IntrospectError> {
+        loop {
+            self.buffer.clear();
+            match self.reader.read_event(&mut self.buffer)? {
+                Event::Start(start) => {
+                    let name = from_utf8(start.name())?.to_owned();
+                    let mut attributes = HashMap::new();
+                    for attribute in start.attributes() {
+                        let attribute = attribute.map_err(quick_xml::Error::from)?;
+                        let key = from_utf8(attribute.key)?.to_owned();
+                        let value = attribute.unescape_and_decode_value(&self.reader)?;
+                        attributes.insert(key, value);
+                    }
+                    return Ok(Element::Start(name, attributes));
+                }
+                Event::End(_) => return Ok(Element::End),
+                Event::Eof => return Err(IntrospectError::UnexpectedEof),
+                _ => {}
+            }
+        }
+    }
+
+    /// Skip all elements until the end tag of the current element.
+    fn skip(&mut self) -> Result<(), IntrospectError> {
+        let mut depth = 0;
+        loop {
+            match self.next()? {
+                Element::Start(_, _) => depth += 1,
+                Element::End => {
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                    depth -= 1;
+                }
+            }
+        }
+    }
+
+    fn annotation(
+        &mut self,
+        mut attributes: HashMap<String, String>,
+    ) -> Result<Annotation, IntrospectError> {
+        let name = required(&mut attributes, "annotation", "name")?;
+        let value = required(&mut attributes, "annotation", "value")?;
+        self.skip()?;
+        Ok(Annotation { name, value })
+    }
+
+    fn arg(&mut self, mut attributes: HashMap<String, String>) -> Result<Arg, IntrospectError> {
+        let name = attributes.remove("name");
+        let r#type = required(&mut attributes, "arg", "type")?;
+        let direction = match attributes.remove("direction") {
+            Some(direction) => match direction.as_str() {
+                "in" => Some(Direction::In),
+                "out" => Some(Direction::Out),
+                _ => return Err(IntrospectError::Direction(direction)),
+            },
+            None => None,
+        };
+        let mut annotations = Vec::new();
+        loop {
+            match self.next()? {
+                Element::Start(name, attributes) if name == "annotation" => {
+                    annotations.push(self.annotation(attributes)?)
+                }
+                Element::Start(_, _) => self.skip()?,
+                Element::End => break,
+            }
+        }
+        Ok(Arg {
+            name,
+            r#type,
+            direction,
+            annotations,
+        })
+    }
+
+    /// Decode the arguments and the annotations of a method or a signal.
+    fn args(&mut self) -> Result<(Vec<Arg>, Vec<Annotation>), IntrospectError> {
+        let mut args = Vec::new();
+        let mut annotations = Vec::new();
+        loop {
+            match self.next()? {
+                Element::Start(name, attributes) => match name.as_str() {
+                    "arg" => args.push(self.arg(attributes)?),
+                    "annotation" => annotations.push(self.annotation(attributes)?),
+                    _ => self.skip()?,
+                },
+                Element::End => return Ok((args, annotations)),
+            }
+        }
+    }
+
+    fn method(
+        &mut self,
+        mut attributes: HashMap<String, String>,
+    ) -> Result<Method, IntrospectError> {
+        let name = required(&mut attributes, "method", "name")?;
+        let (args, annotations) = self.args()?;
+        Ok(Method {
+            name,
+            args,
+            annotations,
+        })
+    }
+
+    fn signal(
+        &mut self,
+        mut attributes: HashMap<String, String>,
+    ) -> Result<Signal, IntrospectError> {
+        let name = required(&mut attributes, "signal", "name")?;
+        let (args, annotations) = self.args()?;
+        Ok(Signal {
+            name,
+            args,
+            annotations,
+        })
+    }
+
+    fn property(
+        &mut self,
+        mut attributes: HashMap<String, String>,
+    ) -> Result<Property, IntrospectError> {
+        let name = required(&mut attributes, "property", "name")?;
+        let r#type = required(&mut attributes, "property", "type")?;
+        let access = required(&mut attributes, "property", "access")?;
+        let access = match access.as_str() {
+            "read" => Access::Read,
+            "write" => Access::Write,
+            "readwrite" => Access::ReadWrite,
+            _ => return Err(IntrospectError::Access(access)),
+        };
+        let mut annotations = Vec::new();
+        loop {
+            match self.next()? {
+                Element::Start(name, attributes) if name == "annotation" => {
+                    annotations.push(self.annotation(attributes)?)
+                }
+                Element::Start(_, _) => self.skip()?,
+                Element::End => break,
+            }
+        }
+        Ok(Property {
+            name,
+            r#type,
+            access,
+            annotations,
+        })
+    }
+
+    fn interface(
+        &mut self,
+        mut attributes: HashMap<String, String>,
+    ) -> Result<Interface, IntrospectError> {
+        let mut interface = Interface {
+            name: required(&mut attributes, "interface", "name")?,
+            methods: Vec::new(),
+            signals: Vec::new(),
+            properties: Vec::new(),
+            annotations: Vec::new(),
+        };
+        loop {
+            match self.next()? {
+                Element::Start(name, attributes) => match name.as_str() {
+                    "method" => interface.methods.push(self.method(attributes)?),
+                    "signal" => interface.signals.push(self.signal(attributes)?),
+                    "property" => interface.properties.push(self.property(attributes)?),
+                    "annotation" => interface.annotations.push(self.annotation(attributes)?),
+                    _ => self.skip()?,
+                },
+                Element::End => return Ok(interface),
+            }
+        }
+    }
+
+    fn node(&mut self, mut attributes: HashMap<String, String>) -> Result<Node, IntrospectError> {
+        let mut node = Node::new(attributes.remove("name"));
+        loop {
+            match self.next()? {
+                Element::Start(name, attributes) => match name.as_str() {
+                    "interface" => node.interfaces.push(self.interface(attributes)?),
+                    "node" => node.nodes.push(self.node(attributes)?),
+                    _ => self.skip()?,
+                },
+                Element::End => return Ok(node),
+            }
+        }
+    }
+}
+
+fn required(
+    attributes: &mut HashMap<String, String>,
+    element: &'static str,
+    attribute: &'static str,
+) -> Result<String, IntrospectError> {
+    attributes
+        .remove(attribute)
+        .ok_or(IntrospectError::MissingAttribute(element, attribute))
+}
+
+impl Node {
+    /// Decode the [introspection data] from a XML document.
+    ///
+    /// Unknown elements (for example documentation elements) are ignored.
+    ///
+    /// [introspection data]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
+    pub fn decode(xml: &str) -> Result<Node, IntrospectError> {
+        let mut decoder = Decoder::new(xml);
+        match decoder.next()? {
+            Element::Start(name, attributes) => {
+                if name == "node" {
+                    decoder.node(attributes)
+                } else {
+                    Err(IntrospectError::UnexpectedElement("node", name))
+                }
+            }
+            Element::End => Err(IntrospectError::UnexpectedEof),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use super::{Annotation, Arg, Interface, Method, Node, Property, Signal};
+use quick_xml::escape::escape as escape_bytes;
+use std::{borrow::Cow, str::from_utf8};
+
+const XML: &str = "<!DOCTYPE node PUBLIC \"-//freedesktop//DTD \
+    D-BUS Object Introspection 1.0//EN\" \"http://www.freedesktop.org/\
+    standards/dbus/1.0/introspect.dtd\">\n";
+
+fn escape(value: &str) -> Cow<'_, str> {
+    match escape_bytes(value.as_bytes()) {
+        // The escaping only replaces ASCII characters, so the result is always UTF-8.
+        Cow::Owned(value) => Cow::Owned(String::from_utf8(value).unwrap()),
+        Cow::Borrowed(value) => Cow::Borrowed(from_utf8(value).unwrap()),
+    }
+}
+
+fn indent(xml: &mut String, depth: usize) {
+    for _ in 0..depth {
+        xml.push_str("  ");
+    }
+}
+
+/// Close the start tag. If there are no children then it is an empty-element tag.
+fn close_start_tag(xml: &mut String, is_empty: bool) {
+    if is_empty {
+        xml.push_str("/>\n");
+    } else {
+        xml.push_str(">\n");
+    }
+}
+
+fn end_tag(xml: &mut String, depth: usize, name: &str, is_empty: bool) {
+    if !is_empty {
+        indent(xml, depth);
+        xml.push_str("</");
+        xml.push_str(name);
+        xml.push_str(">\n");
+    }
+}
+
+impl Annotation {
+    fn encode(&self, xml: &mut String, depth: usize) {
+        indent(xml, depth);
+        *xml += &format!(
+            "<annotation name=\"{}\" value=\"{}\"/>\n",
+            escape(&self.name),
+            escape(&self.value)
+        );
+    }
+}
+
+fn encode_annotations(annotations: &[Annotation], xml: &mut String, depth: usize) {
+    for annotation in annotations {
+        annotation.encode(xml, depth);
+    }
+}
+
+impl Arg {
+    fn encode(&self, xml: &mut String, depth: usize) {
+        indent(xml, depth);
+        xml.push_str("<arg");
+        if let Some(name) = &self.name {
+            *xml += &format!(" name=\"{}\"", escape(name));
+        }
+        *xml += &format!(" type=\"{}\"", escape(&self.r#type));
+        if let Some(direction) = &self.direction {
+            *xml += &format!(" direction=\"{}\"", direction.as_str());
+        }
+        let is_empty = self.annotations.is_empty();
+        close_start_tag(xml, is_empty);
+        encode_annotations(&self.annotations, xml, depth + 1);
+        end_tag(xml, depth, "arg", is_empty);
+    }
+}
+
+/// Encode a method or a signal.
+fn encode_member(
+    element: &str,
+    name: &str,
+    args: &[Arg],
+    annotations: &[Annotation],
+    xml: &mut String,
+    depth: usize,
+) {
+    indent(xml, depth);
+    *xml += &format!("<{} name=\"{}\"", element, escape(name));
+    let is_empty = args.is_empty() && annotations.is_empty();
+    close_start_tag(xml, is_empty);
+    for arg in args {
+        arg.encode(xml, depth + 1);
+    }
+    encode_annotations(annotations, xml, depth + 1);
+    end_tag(xml, depth, element, is_empty);
+}
+
+impl Method {
+    fn encode(&self, xml: &mut String, depth: usize) {
+        encode_member(
+            "method",
+            &self.name,
+            &self.args,
+            &self.annotations,
+            xml,
+            depth,
+        );
+    }
+}
+
+impl Signal {
+    fn encode(&self, xml: &mut String, depth: usize) {
+        encode_member(
+            "signal",
+            &self.name,
+            &self.args,
+            &self.annotations,
+            xml,
+            depth,
+        );
+    }
+}
+
+impl Property {
+    fn encode(&self, xml: &mut String, depth: usize) {
+        indent(xml, depth);
+        *xml += &format!(
+            "<property name=\"{}\" type=\"{}\" access=\"{}\"",
+            escape(&self.name),
+            escape(&self.r#type),
+            self.access.as_str()
+        );
+        let is_empty = self.annotations.is_empty();
+        close_start_tag(xml, is_empty);
+        encode_annotations(&self.annotations, xml, depth + 1);
+        end_tag(xml, depth, "property", is_empty);
+    }
+}
+
+impl Interface {
+    fn encode(&self, xml: &mut String, depth: usize) {
+        indent(xml, depth);
+        *xml += &format!("<interface name=\"{}\"", escape(&self.name));
+        let is_empty = self.methods.is_empty()
+            && self.signals.is_empty()
+            && self.properties.is_empty()
+            && self.annotations.is_empty();
+        close_start_tag(xml, is_empty);
+        for method in &self.methods {
+            method.encode(xml, depth + 1);
+        }
+        for signal in &self.signals {
+            signal.encode(xml, depth + 1);
+        }
+        for property in &self.properties {
+            property.encode(xml, depth + 1);
+        }
+        encode_annotations(&self.annotations, xml, depth + 1);
+        end_tag(xml, depth, "interface", is_empty);
+    }
+}
+
+impl Node {
+    fn encode_node(&self, xml: &mut String, depth: usize) {
+        indent(xml, depth);
+        xml.push_str("<node");
+        if let Some(name) = &self.name {
+            *xml += &format!(" name=\"{}\"", escape(name));
+        }
+        // The root node is never an empty-element tag.
+        let is_empty = depth != 0 && self.interfaces.is_empty() && self.nodes.is_empty();
+        close_start_tag(xml, is_empty);
+        for interface in &self.interfaces {
+            interface.encode(xml, depth + 1);
+        }
+        for node in &self.nodes {
+            node.encode_node(xml, depth + 1);
+        }
+        end_tag(xml, depth, "node", is_empty);
+    }
+
+    /// Encode the [introspection data] to a XML document.
+    ///
+    /// [introspection data]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
+    pub fn encode(&self) -> String {
+        let mut xml = XML.to_string();
+        self.encode_node(&mut xml, 0);
+        xml
+    }
+}
@@ -0,0 +1,59 @@
+use quick_xml::Error as XmlError;
+use std::str::Utf8Error;
+use thiserror::Error;
+
+/// The direction of an argument of a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+impl Direction {
+    pub(super) fn as_str(&self) -> &'static str {
+        match self {
+            Direction::In => "in",
+            Direction::Out => "out",
+        }
+    }
+}
+
+/// The access of a property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl Access {
+    pub(super) fn as_str(&self) -> &'static str {
+        match self {
+            Access::Read => "read",
+            Access::Write => "write",
+            Access::ReadWrite => "readwrite",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum IntrospectError {
+    #[error("Could not parse XML: {0}")]
+    XmlError(#[from] XmlError),
+    #[error("XML is not UTF-8: {0}")]
+    Utf8Error(#[from] Utf8Error),
+    #[error("Unexpected end of the XML document")]
+    UnexpectedEof,
+    #[error("Unexpected element: expected '{0}' got '{1}'")]
+    UnexpectedElement(&'static str, String),
+    #[error("Element '{0}' has no attribute '{1}'")]
+    MissingAttribute(&'static str, &'static str),
+    #[error("Unknown direction of an argument: {0}")]
+    Direction(String),
+    #[error("Unknown access of a property: {0}")]
+    Access(String),
+    #[error("Node name is not a valid object path element: {0}")]
+    NodeName(String),
+    #[error("The body of the introspection reply is not a single string")]
+    Body,
+}
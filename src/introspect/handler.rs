@@ -1,15 +1,11 @@
-use crate::{DBus, DBusResult};
+use super::Node;
+use crate::{runtime::spawn, DBus, DBusResult};
 use dbus_message_parser::{message::Message, value::Value};
 use futures::{
     channel::mpsc::{channel, Receiver},
     StreamExt,
 };
 use std::convert::TryInto;
-use tokio::spawn;
-
-const XML: &str = "<!DOCTYPE node PUBLIC \"-//freedesktop//DTD \
-    D-BUS Object Introspection 1.0//EN\" \"http://www.freedesktop.org/\
-    standards/dbus/1.0/introspect.dtd\">\n<node>\n";
 
 async fn introspect(dbus: DBus, mut receiver: Receiver<Message>) {
     while let Some(msg) = receiver.next().await {
@@ -24,7 +20,7 @@ async fn introspect(dbus: DBus, mut receiver: Receiver<Message>) {
                 // Check if the signature of the message is correct.
                 if !msg.get_signature().unwrap().is_empty() {
                     let msg = msg.invalid_args("Too many arguments".to_string());
-                    if let Err(e) = dbus.send(msg) {
+                    if let Err(e) = dbus.send(msg).await {
                         error!("could not send message: {}", e);
                         return;
                     }
@@ -38,13 +34,12 @@ async fn introspect(dbus: DBus, mut receiver: Receiver<Message>) {
                             let msg = match msg.method_return() {
                                 Ok(mut msg) => {
                                     // Create the XML response.
-                                    // Start with the header.
-                                    let mut xml = XML.to_string();
+                                    let mut node = Node::new(None);
                                     // Add all nodes to the XML body.
                                     for l in list {
-                                        xml += &format!("  <node name=\"{}\"/>\n", l);
+                                        node.nodes.push(Node::new(Some(l)));
                                     }
-                                    xml += "</node>";
+                                    let xml = node.encode();
                                     // Add the return value.
                                     // Send the return message.
                                     msg.add_value(Value::String(xml));
@@ -52,7 +47,7 @@ async fn introspect(dbus: DBus, mut receiver: Receiver<Message>) {
                                 }
                                 Err(msg) => msg,
                             };
-                            if let Err(e) = dbus.send(msg) {
+                            if let Err(e) = dbus.send(msg).await {
                                 error!("could not send message: {}", e);
                                 return;
                             }
@@ -65,7 +60,7 @@ async fn introspect(dbus: DBus, mut receiver: Receiver<Message>) {
             }
             _ => {
                 if let Some(msg) = msg.unknown_member() {
-                    if let Err(e) = dbus.send(msg) {
+                    if let Err(e) = dbus.send(msg).await {
                         error!("could not send message: {}", e);
                         return;
                     }
@@ -75,14 +70,12 @@ async fn introspect(dbus: DBus, mut receiver: Receiver<Message>) {
     }
 }
 
-pub(super) fn add_introspect(dbus: DBus) -> DBusResult<()> {
+pub(crate) fn add_introspect(dbus: DBus, channel_size: usize) -> DBusResult<()> {
     // If introspectable is true then add the introspectable interface handler.
-    let (sender, receiver) = channel(1024);
+    let (sender, receiver) = channel(channel_size);
     let interface = "org.freedesktop.DBus.Introspectable".try_into().unwrap();
     // Try to add the interface handler.
-    if let Err(e) = dbus.add_method_call_interface(interface, sender) {
-        return Err(e);
-    }
+    dbus.add_method_call_interface(interface, sender)?;
 
     // Spawn the introspectable handler.
     spawn(introspect(dbus, receiver));
@@ -0,0 +1,12 @@
+//! The data model of the [introspection data].
+//!
+//! [introspection data]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
+mod decode;
+mod encode;
+mod r#enum;
+mod handler;
+mod r#struct;
+
+pub(crate) use handler::add_introspect;
+pub use r#enum::{Access, Direction, IntrospectError};
+pub use r#struct::{Annotation, Arg, Interface, Method, Node, Property, Signal};
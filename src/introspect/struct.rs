@@ -0,0 +1,92 @@
+use super::{Access, Direction};
+
+/// This represents an [annotation] of an interface, a method, a signal, a property or an
+/// argument.
+///
+/// [annotation]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub name: String,
+    pub value: String,
+}
+
+/// This represents an argument of a method or a signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arg {
+    pub name: Option<String>,
+    pub r#type: String,
+    pub direction: Option<Direction>,
+    pub annotations: Vec<Annotation>,
+}
+
+/// This represents a method of an interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Method {
+    pub name: String,
+    pub args: Vec<Arg>,
+    pub annotations: Vec<Annotation>,
+}
+
+/// This represents a signal of an interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signal {
+    pub name: String,
+    pub args: Vec<Arg>,
+    pub annotations: Vec<Annotation>,
+}
+
+/// This represents a property of an interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    pub name: String,
+    pub r#type: String,
+    pub access: Access,
+    pub annotations: Vec<Annotation>,
+}
+
+/// This represents an interface of an object.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    pub methods: Vec<Method>,
+    pub signals: Vec<Signal>,
+    pub properties: Vec<Property>,
+    pub annotations: Vec<Annotation>,
+}
+
+/// This represents a [node] of the introspection data.
+///
+/// The root node has normally no name. The child nodes have a name relative to the parent node.
+///
+/// [node]: https://dbus.freedesktop.org/doc/dbus-specification.html#introspection-format
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Node {
+    pub name: Option<String>,
+    pub interfaces: Vec<Interface>,
+    pub nodes: Vec<Node>,
+}
+
+impl Node {
+    /// Create a [`Node`] without any interfaces and child nodes.
+    pub fn new(name: Option<String>) -> Node {
+        Node {
+            name,
+            interfaces: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Get the interface by the name, if there is one.
+    pub fn get_interface(&self, name: &str) -> Option<&Interface> {
+        self.interfaces
+            .iter()
+            .find(|interface| interface.name == name)
+    }
+
+    /// Get the child node by the name, if there is one.
+    pub fn get_node(&self, name: &str) -> Option<&Node> {
+        self.nodes
+            .iter()
+            .find(|node| node.name.as_deref() == Some(name))
+    }
+}
@@ -3,20 +3,61 @@ extern crate log;
 #[macro_use(bitflags)]
 extern crate bitflags;
 
+pub mod activation;
+mod binding;
+pub mod blocking;
+mod builder;
+#[cfg(any(feature = "test-support", feature = "daemon"))]
+mod bus;
+mod call_options;
+#[cfg(feature = "codegen")]
+pub mod codegen;
 mod command;
 mod connection;
+#[cfg(feature = "daemon")]
+pub mod daemon;
 mod dbus;
 mod error;
+mod error_mapping;
+pub mod export;
 mod handler;
-mod introspect;
+pub mod introspect;
+mod listener;
+pub mod marshal;
 mod name_flag;
+mod object_server;
 mod peer;
+mod reply_handler;
+mod runtime;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod signal_emitter;
 mod stream;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 
 type Uuid = [u8; 16];
 
+pub use binding::Binding;
+pub use builder::{
+    AuthMechanism, DBusBuilder, PendingCallPolicy, ReceivePolicy, DEFAULT_HANDLER_CHANNEL_SIZE,
+    DEFAULT_PENDING_CALL_LIMIT, DEFAULT_RECEIVE_QUEUE_SIZE, DEFAULT_SEND_QUEUE_SIZE,
+};
+pub use call_options::CallOptions;
 pub use dbus::DBus;
 pub use error::{DBusError, DBusResult};
-pub use handler::{Binder, Handler};
+pub use error_mapping::{ErrorMapping, StandardError};
+pub use handler::{
+    BindOptions, Binder, CallOrdering, ConcurrentBinder, ErrorPolicy, Handler, SharedHandler,
+    ShutdownHandle, DEFAULT_CONCURRENCY_LIMIT,
+};
+pub use listener::DBusListener;
 pub use name_flag::DBusNameFlag;
+pub use object_server::{MethodResult, MethodTable, ObjectServer};
 pub use peer::handle_peer;
+pub use reply_handler::{DBusErrorReply, Reply, ReplyHandler, ReplyResult};
+#[cfg(feature = "runtime-tokio")]
+pub use runtime::TokioCompat;
+pub use runtime::{JoinError, JoinHandle};
+pub use signal_emitter::SignalEmitter;
+pub use stream::{ProtocolError, Transport, MAXIMUM_MESSAGE_SIZE};
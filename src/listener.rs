@@ -0,0 +1,47 @@
+use crate::{
+    runtime::{unix_listener_from_std, UnixListener},
+    stream::{create_guid, Stream, StreamError},
+    DBus, DBusBuilder, DBusResult, JoinHandle,
+};
+use hex::encode;
+use std::os::unix::net::UnixListener as StdUnixListener;
+
+/// A listening Unix Domain Stream socket, which accepts peer-to-peer connections without a message
+/// bus, for example a socket, which is inherited through socket activation (see
+/// [`activation::listen_fds`]).
+///
+/// The listener is created by [`DBusBuilder::listen_with_unix_listener`] and every accepted
+/// connection is built with the options of the builder.
+///
+/// [`activation::listen_fds`]: crate::activation::listen_fds
+/// [`DBusBuilder::listen_with_unix_listener`]: crate::DBusBuilder::listen_with_unix_listener
+pub struct DBusListener {
+    listener: UnixListener,
+    guid: String,
+    builder: DBusBuilder,
+}
+
+impl DBusListener {
+    pub(crate) fn new(
+        unix_listener: StdUnixListener,
+        builder: DBusBuilder,
+    ) -> DBusResult<DBusListener> {
+        let listener = unix_listener_from_std(unix_listener).map_err(StreamError::from)?;
+        Ok(DBusListener {
+            listener,
+            guid: encode(create_guid()),
+            builder: builder.hello(false),
+        })
+    }
+
+    /// Accept the next connection of a peer and answer its authentication.
+    ///
+    /// The `EXTERNAL` mechanism only accepts the UID of the peer credentials of the socket. If the
+    /// peer cannot be authenticated then an error is returned and the listener can accept the
+    /// next connection.
+    pub async fn accept(&self) -> DBusResult<(DBus, JoinHandle<()>)> {
+        let unix_stream = self.listener.accept().await.map_err(StreamError::from)?;
+        let stream = Stream::accept_unix_stream(unix_stream, &self.guid, &self.builder).await?;
+        DBus::from_stream(None, stream, self.builder.clone()).await
+    }
+}
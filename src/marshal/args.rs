@@ -0,0 +1,88 @@
+use super::{FromDBus, MarshalError, ToDBus};
+use dbus_message_parser::{
+    message::{Message, MessageHeader},
+    value::Value,
+};
+use std::convert::TryInto;
+
+/// The arguments of a message, which can be converted to the body of the message.
+pub trait ToArgs {
+    /// Convert the arguments to the values of the body.
+    fn to_args(self) -> Result<Vec<Value>, MarshalError>;
+}
+
+/// The arguments of a message, which can be converted from the body of the message.
+pub trait FromArgs: Sized {
+    /// Convert the values of the body to the arguments. If the number or the types of the values
+    /// are different then an error is returned.
+    fn from_args(body: Vec<Value>) -> Result<Self, MarshalError>;
+}
+
+impl ToArgs for () {
+    fn to_args(self) -> Result<Vec<Value>, MarshalError> {
+        Ok(Vec::new())
+    }
+}
+
+impl FromArgs for () {
+    fn from_args(body: Vec<Value>) -> Result<Self, MarshalError> {
+        if body.is_empty() {
+            Ok(())
+        } else {
+            Err(MarshalError::ArgumentCount(0, body.len()))
+        }
+    }
+}
+
+macro_rules! args {
+    ($len:expr; $($name:ident),+) => {
+        impl<$($name: ToDBus),+> ToArgs for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn to_args(self) -> Result<Vec<Value>, MarshalError> {
+                let ($($name,)+) = self;
+                Ok(vec![$($name.to_dbus()?),+])
+            }
+        }
+
+        impl<$($name: FromDBus),+> FromArgs for ($($name,)+) {
+            fn from_args(body: Vec<Value>) -> Result<Self, MarshalError> {
+                if body.len() != $len {
+                    return Err(MarshalError::ArgumentCount($len, body.len()));
+                }
+                let mut body = body.into_iter();
+                // The unwrap function call will never panic because the length is checked.
+                Ok(($($name::from_dbus(body.next().unwrap())?,)+))
+            }
+        }
+    };
+}
+
+args!(1; A);
+args!(2; A, B);
+args!(3; A, B, C);
+args!(4; A, B, C, D);
+args!(5; A, B, C, D, E);
+args!(6; A, B, C, D, E, F);
+args!(7; A, B, C, D, E, F, G);
+args!(8; A, B, C, D, E, F, G, H);
+
+/// Create a method return message for the given method call header with the given arguments.
+///
+/// If the arguments could not be converted then an error message is returned.
+pub fn method_return<A: ToArgs>(header: &MessageHeader, args: A) -> Message {
+    match header.method_return() {
+        Ok(mut msg) => match args.to_args() {
+            Ok(body) => {
+                for value in body {
+                    msg.add_value(value);
+                }
+                msg
+            }
+            Err(e) => header.error(
+                "org.freedesktop.DBus.Error.Failed".try_into().unwrap(),
+                e.to_string(),
+            ),
+        },
+        Err(msg) => msg,
+    }
+}
@@ -0,0 +1,47 @@
+use super::{FromDBus, MarshalError, ToDBus};
+use dbus_message_parser::value::{ObjectPath, Type, Value};
+
+macro_rules! basic {
+    ($rust:ty, $variant:ident, $type:ident) => {
+        impl ToDBus for $rust {
+            fn signature() -> Type {
+                Type::$type
+            }
+
+            fn to_dbus(self) -> Result<Value, MarshalError> {
+                Ok(Value::$variant(self))
+            }
+        }
+
+        impl FromDBus for $rust {
+            fn from_dbus(value: Value) -> Result<Self, MarshalError> {
+                match value {
+                    Value::$variant(value) => Ok(value),
+                    value => Err(MarshalError::TypeMismatch(Type::$type, value)),
+                }
+            }
+        }
+    };
+}
+
+basic!(u8, Byte, Byte);
+basic!(bool, Boolean, Boolean);
+basic!(i16, Int16, Int16);
+basic!(u16, Uint16, Uint16);
+basic!(i32, Int32, Int32);
+basic!(u32, Uint32, Uint32);
+basic!(i64, Int64, Int64);
+basic!(u64, Uint64, Uint64);
+basic!(f64, Double, Double);
+basic!(String, String, String);
+basic!(ObjectPath, ObjectPath, ObjectPath);
+
+impl ToDBus for &str {
+    fn signature() -> Type {
+        Type::String
+    }
+
+    fn to_dbus(self) -> Result<Value, MarshalError> {
+        Ok(Value::String(self.to_string()))
+    }
+}
@@ -0,0 +1,182 @@
+use super::{FromDBus, MarshalError, ToDBus};
+use dbus_message_parser::value::{Array, Struct, Type, Value};
+use std::{collections::HashMap, convert::TryFrom, hash::Hash};
+
+/// A DBus variant, which can contain a value of any type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant(pub Value);
+
+impl Variant {
+    /// Create a variant, which contains the given value.
+    pub fn new<T: ToDBus>(value: T) -> Result<Variant, MarshalError> {
+        Ok(Variant(value.to_dbus()?))
+    }
+
+    /// Get the value of the variant as the given type.
+    pub fn get<T: FromDBus>(self) -> Result<T, MarshalError> {
+        T::from_dbus(self.0)
+    }
+}
+
+impl ToDBus for Variant {
+    fn signature() -> Type {
+        Type::Variant
+    }
+
+    fn to_dbus(self) -> Result<Value, MarshalError> {
+        Ok(Value::Variant(Box::new(self.0)))
+    }
+}
+
+impl FromDBus for Variant {
+    fn from_dbus(value: Value) -> Result<Self, MarshalError> {
+        match value {
+            Value::Variant(value) => Ok(Variant(*value)),
+            value => Err(MarshalError::TypeMismatch(Type::Variant, value)),
+        }
+    }
+}
+
+/// Get the elements of an array with the given element type.
+fn array_elements(value: Value, type_: Type) -> Result<Vec<Value>, MarshalError> {
+    match value {
+        Value::Array(array) if array.get_type() == &type_ => Ok(array.into()),
+        value => Err(MarshalError::TypeMismatch(
+            Type::Array(Box::new(type_)),
+            value,
+        )),
+    }
+}
+
+impl<T: ToDBus> ToDBus for Vec<T> {
+    fn signature() -> Type {
+        Type::Array(Box::new(T::signature()))
+    }
+
+    fn to_dbus(self) -> Result<Value, MarshalError> {
+        let array = self
+            .into_iter()
+            .map(T::to_dbus)
+            .collect::<Result<Vec<Value>, MarshalError>>()?;
+        Ok(Value::Array(Array::new(array, T::signature())?))
+    }
+}
+
+impl<T: FromDBus + ToDBus> FromDBus for Vec<T> {
+    fn from_dbus(value: Value) -> Result<Self, MarshalError> {
+        array_elements(value, T::signature())?
+            .into_iter()
+            .map(T::from_dbus)
+            .collect()
+    }
+}
+
+/// An [`Option`] is represented as an array, which has zero or one element.
+impl<T: ToDBus> ToDBus for Option<T> {
+    fn signature() -> Type {
+        Type::Array(Box::new(T::signature()))
+    }
+
+    fn to_dbus(self) -> Result<Value, MarshalError> {
+        let array = match self {
+            Some(value) => vec![value.to_dbus()?],
+            None => Vec::new(),
+        };
+        Ok(Value::Array(Array::new(array, T::signature())?))
+    }
+}
+
+impl<T: FromDBus + ToDBus> FromDBus for Option<T> {
+    fn from_dbus(value: Value) -> Result<Self, MarshalError> {
+        let mut array = array_elements(value, T::signature())?;
+        match array.len() {
+            0 => Ok(None),
+            1 => Ok(Some(T::from_dbus(array.remove(0))?)),
+            len => Err(MarshalError::OptionLength(len)),
+        }
+    }
+}
+
+/// A [`HashMap`] is represented as an array of dict entries (`a{..}`).
+///
+/// [`HashMap`]: std::collections::HashMap
+impl<K: ToDBus, V: ToDBus> ToDBus for HashMap<K, V> {
+    fn signature() -> Type {
+        Type::Array(Box::new(dict_entry_signature::<K, V>()))
+    }
+
+    fn to_dbus(self) -> Result<Value, MarshalError> {
+        let mut array = Vec::with_capacity(self.len());
+        for (key, value) in self {
+            let dict_entry = (key.to_dbus()?, value.to_dbus()?);
+            array.push(Value::DictEntry(Box::new(dict_entry)));
+        }
+        let array = Array::new(array, dict_entry_signature::<K, V>())?;
+        Ok(Value::Array(array))
+    }
+}
+
+impl<K, V> FromDBus for HashMap<K, V>
+where
+    K: FromDBus + ToDBus + Eq + Hash,
+    V: FromDBus + ToDBus,
+{
+    fn from_dbus(value: Value) -> Result<Self, MarshalError> {
+        let array = array_elements(value, dict_entry_signature::<K, V>())?;
+        let mut map = HashMap::with_capacity(array.len());
+        for dict_entry in array {
+            // The type of the elements is already checked by the array.
+            if let Value::DictEntry(dict_entry) = dict_entry {
+                let (key, value) = *dict_entry;
+                map.insert(K::from_dbus(key)?, V::from_dbus(value)?);
+            }
+        }
+        Ok(map)
+    }
+}
+
+fn dict_entry_signature<K: ToDBus, V: ToDBus>() -> Type {
+    Type::DictEntry(Box::new((K::signature(), V::signature())))
+}
+
+macro_rules! tuple {
+    ($len:expr; $($name:ident),+) => {
+        /// A tuple is represented as a struct.
+        impl<$($name: ToDBus),+> ToDBus for ($($name,)+) {
+            fn signature() -> Type {
+                Type::Struct(vec![$($name::signature()),+])
+            }
+
+            #[allow(non_snake_case)]
+            fn to_dbus(self) -> Result<Value, MarshalError> {
+                let ($($name,)+) = self;
+                let fields = vec![$($name.to_dbus()?),+];
+                Ok(Value::Struct(Struct::try_from(fields)?))
+            }
+        }
+
+        impl<$($name: FromDBus + ToDBus),+> FromDBus for ($($name,)+) {
+            fn from_dbus(value: Value) -> Result<Self, MarshalError> {
+                let fields: Vec<Value> = match value {
+                    Value::Struct(fields) => fields.into(),
+                    value => return Err(MarshalError::TypeMismatch(Self::signature(), value)),
+                };
+                if fields.len() != $len {
+                    return Err(MarshalError::StructFieldCount($len, fields.len()));
+                }
+                let mut fields = fields.into_iter();
+                // The unwrap function call will never panic because the length is checked.
+                Ok(($($name::from_dbus(fields.next().unwrap())?,)+))
+            }
+        }
+    };
+}
+
+tuple!(1; A);
+tuple!(2; A, B);
+tuple!(3; A, B, C);
+tuple!(4; A, B, C, D);
+tuple!(5; A, B, C, D, E);
+tuple!(6; A, B, C, D, E, F);
+tuple!(7; A, B, C, D, E, F, G);
+tuple!(8; A, B, C, D, E, F, G, H);
@@ -0,0 +1,20 @@
+use dbus_message_parser::value::{ArrayError, StructError, Type, TypeError, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MarshalError {
+    #[error("Unexpected value: expected '{0}' got {1:?}")]
+    TypeMismatch(Type, Value),
+    #[error("Unexpected number of arguments: expected {0} got {1}")]
+    ArgumentCount(usize, usize),
+    #[error("Unexpected number of struct fields: expected {0} got {1}")]
+    StructFieldCount(usize, usize),
+    #[error("Array with a single optional element has {0} elements")]
+    OptionLength(usize),
+    #[error("Could not create array: {0}")]
+    ArrayError(#[from] ArrayError),
+    #[error("Could not create struct: {0}")]
+    StructError(#[from] StructError),
+    #[error("Could not get type: {0}")]
+    TypeError(#[from] TypeError),
+}
@@ -0,0 +1,49 @@
+//! Convert Rust types to and from DBus [`Value`]s.
+//!
+//! The [`ToDBus`] and [`FromDBus`] traits are implemented for the basic types, [`String`],
+//! [`ObjectPath`], [`Vec`] (as array), [`HashMap`] (as array of dict entries), tuples (as struct),
+//! [`Option`] (as array with zero or one element) and [`Variant`].
+//!
+//! The [`ToArgs`] and [`FromArgs`] traits are implemented for tuples of these types and are used
+//! for the whole body of a message (see [`DBus::call_method`]).
+//!
+//! [`Value`]: dbus_message_parser::value::Value
+//! [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+//! [`HashMap`]: std::collections::HashMap
+//! [`DBus::call_method`]: crate::DBus::call_method
+mod args;
+mod basic;
+mod container;
+mod r#enum;
+
+pub use args::{method_return, FromArgs, ToArgs};
+pub use container::Variant;
+pub use r#enum::MarshalError;
+
+use dbus_message_parser::value::{Type, Value};
+
+/// A type, which can be converted to a DBus [`Value`].
+///
+/// [`Value`]: dbus_message_parser::value::Value
+pub trait ToDBus {
+    /// The DBus type of the value. This is needed for empty arrays.
+    fn signature() -> Type;
+
+    /// Convert the value to a DBus [`Value`].
+    ///
+    /// This can only fail, if the maximum depth of the containers is exceeded.
+    ///
+    /// [`Value`]: dbus_message_parser::value::Value
+    fn to_dbus(self) -> Result<Value, MarshalError>;
+}
+
+/// A type, which can be converted from a DBus [`Value`].
+///
+/// [`Value`]: dbus_message_parser::value::Value
+pub trait FromDBus: Sized {
+    /// Convert a DBus [`Value`] to the type. If the [`Value`] has a different type then an error
+    /// is returned.
+    ///
+    /// [`Value`]: dbus_message_parser::value::Value
+    fn from_dbus(value: Value) -> Result<Self, MarshalError>;
+}
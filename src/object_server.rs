@@ -0,0 +1,285 @@
+use crate::{Binding, DBus, DBusError, DBusResult, Handler, SharedHandler, SignalEmitter};
+use async_trait::async_trait;
+use dbus_message_parser::{
+    message::{Message, MessageFlags, MessageHeader, MessageType},
+    value::{Error as ErrorName, Interface, Member, Type, Value},
+};
+use futures::future::BoxFuture;
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+};
+
+/// The result of a method of a [`MethodTable`].
+///
+/// `Ok` contains the return values of the method and `Err` contains the name and the message of
+/// the error.
+pub type MethodResult = Result<Vec<Value>, (ErrorName, String)>;
+
+type Method =
+    Box<dyn Fn(DBus, MessageHeader, Vec<Value>) -> BoxFuture<'static, MethodResult> + Send + Sync>;
+
+struct MethodEntry {
+    signature: Vec<Type>,
+    method: Method,
+}
+
+/// A table of the methods and the signals of an interface, which can be added to an
+/// [`ObjectServer`].
+pub struct MethodTable {
+    interface: Interface,
+    methods: HashMap<String, MethodEntry>,
+    signals: HashMap<String, Vec<Type>>,
+}
+
+impl MethodTable {
+    /// Create an empty table for the given [`Interface`].
+    ///
+    /// [`Interface`]: dbus_message_parser::value::Interface
+    pub fn new(interface: Interface) -> MethodTable {
+        MethodTable {
+            interface,
+            methods: HashMap::new(),
+            signals: HashMap::new(),
+        }
+    }
+
+    /// Add a method with the expected signature of the arguments.
+    ///
+    /// The method is only called if the arguments have the expected signature, so the method can
+    /// match the arguments directly. If there is already a method with the same name then it will
+    /// be replaced.
+    pub fn add_method<F, R>(&mut self, member: Member, signature: Vec<Type>, method: F)
+    where
+        F: Fn(DBus, MessageHeader, Vec<Value>) -> R + Send + Sync + 'static,
+        R: Future<Output = MethodResult> + Send + 'static,
+    {
+        let method: Method =
+            Box::new(move |dbus, header, body| Box::pin(method(dbus, header, body)));
+        let entry = MethodEntry { signature, method };
+        self.methods.insert(member.as_ref().to_string(), entry);
+    }
+
+    /// Declare a signal with the signature of the arguments.
+    ///
+    /// The signals, which are emitted by an emitter of the [`ObjectServer`] (see
+    /// [`ObjectServer::signal_emitter`]), are checked against the declared signals. If there is
+    /// already a signal with the same name then it will be replaced.
+    ///
+    /// [`ObjectServer::signal_emitter`]: crate::ObjectServer::signal_emitter
+    pub fn add_signal(&mut self, member: Member, signature: Vec<Type>) {
+        self.signals.insert(member.as_ref().to_string(), signature);
+    }
+
+    /// Get the [`Interface`] of the table.
+    ///
+    /// [`Interface`]: dbus_message_parser::value::Interface
+    pub fn get_interface(&self) -> &Interface {
+        &self.interface
+    }
+}
+
+/// An object, which dispatches the `MethodCall` messages to the methods of the registered
+/// [`MethodTable`]s.
+///
+/// The server replies automatically with an `UnknownInterface`, `UnknownMember` or `InvalidArgs`
+/// error, if there is no matching method or the arguments have a different signature. The
+/// [`MethodResult`] of a method is sent as a `MethodReturn` or an `Error` message.
+///
+/// If a `MethodCall` has no interface then the method of the first interface, in the
+/// lexicographical order of the interface names, which has a method with the member name is called.
+///
+/// The server implements the [`Handler`] and the [`SharedHandler`] trait, so it can be bound to an
+/// object path with the [`Binder`] or the [`ConcurrentBinder`] trait.
+///
+/// [`Handler`]: crate::Handler
+/// [`SharedHandler`]: crate::SharedHandler
+/// [`Binder`]: crate::Binder
+/// [`ConcurrentBinder`]: crate::ConcurrentBinder
+#[derive(Default)]
+pub struct ObjectServer {
+    interfaces: BTreeMap<String, MethodTable>,
+}
+
+fn signature_string(signature: &[Type]) -> String {
+    Type::from_signature_to_string(signature).unwrap_or_default()
+}
+
+impl ObjectServer {
+    /// Create a server without any interfaces.
+    pub fn new() -> ObjectServer {
+        ObjectServer::default()
+    }
+
+    /// Add the [`MethodTable`] of an interface.
+    ///
+    /// If there is already a table for the same interface then it will be replaced and returned.
+    pub fn add_interface(&mut self, table: MethodTable) -> Option<MethodTable> {
+        let interface = table.interface.as_ref().to_string();
+        self.interfaces.insert(interface, table)
+    }
+
+    /// Delete the [`MethodTable`] of an interface.
+    pub fn delete_interface(&mut self, interface: &Interface) -> Option<MethodTable> {
+        self.interfaces.remove(interface.as_ref())
+    }
+
+    /// Create a [`SignalEmitter`] for the signals of the [`Interface`] of the object, which is
+    /// bound by the [`Binding`].
+    ///
+    /// The emitter returns an error for the signals, which are not declared by the
+    /// [`MethodTable`] of the interface (see [`MethodTable::add_signal`]) or which have a different
+    /// signature. If the [`Interface`] is not added to the server then
+    /// [`DBusError::InterfaceNotDeclared`] is returned.
+    ///
+    /// To create an emitter after the server is bound, the server can be shared with an [`Arc`]
+    /// and bound with the [`ConcurrentBinder`] trait.
+    ///
+    /// [`SignalEmitter`]: crate::SignalEmitter
+    /// [`Binding`]: crate::Binding
+    /// [`Interface`]: dbus_message_parser::value::Interface
+    /// [`DBusError::InterfaceNotDeclared`]: crate::DBusError::InterfaceNotDeclared
+    /// [`Arc`]: std::sync::Arc
+    /// [`ConcurrentBinder`]: crate::ConcurrentBinder
+    pub fn signal_emitter(
+        &self,
+        binding: &Binding,
+        interface: Interface,
+    ) -> DBusResult<SignalEmitter> {
+        match self.interfaces.get(interface.as_ref()) {
+            Some(table) => {
+                let signals = table.signals.clone();
+                Ok(binding.signal_emitter(interface).with_signals(signals))
+            }
+            None => Err(DBusError::InterfaceNotDeclared(interface)),
+        }
+    }
+
+    /// Handle a `MethodCall`, which is already split into the header and the body, with the
+    /// [`MessageFlags`] of the message.
+    ///
+    /// If the caller set the `NO_REPLY_EXPECTED` flag then the method is called, but no reply is
+    /// sent.
+    ///
+    /// [`MessageFlags`]: dbus_message_parser::message::MessageFlags
+    pub async fn handle_method_call(
+        &self,
+        dbus: &DBus,
+        header: MessageHeader,
+        body: Vec<Value>,
+        flags: MessageFlags,
+    ) -> DBusResult<()> {
+        let no_reply_expected = flags.contains(MessageFlags::NO_REPLY_EXPECTED);
+        let send = |msg| async move {
+            if no_reply_expected {
+                Ok(())
+            } else {
+                dbus.send(msg).await
+            }
+        };
+        let member = if let Some(member) = header.get_member() {
+            member
+        } else {
+            return Ok(());
+        };
+        let entry = match header.get_interface() {
+            Some(interface) => match self.interfaces.get(interface.as_ref()) {
+                Some(table) => table.methods.get(member.as_ref()),
+                None => {
+                    if let Some(msg) = header.unknown_interface() {
+                        send(msg).await?;
+                    }
+                    return Ok(());
+                }
+            },
+            // Without an interface the method of the first interface in the lexicographical
+            // order is called, so the dispatch does not depend on the insertion order.
+            None => self
+                .interfaces
+                .values()
+                .find_map(|table| table.methods.get(member.as_ref())),
+        };
+        let entry = if let Some(entry) = entry {
+            entry
+        } else {
+            if let Some(msg) = header.unknown_member() {
+                send(msg).await?;
+            }
+            return Ok(());
+        };
+        // Check if the signature of the arguments is correct.
+        let signature = header.get_signature().unwrap_or(&[]);
+        if signature != entry.signature.as_slice() {
+            let reason = format!(
+                "Invalid signature: expected '{}' got '{}'",
+                signature_string(&entry.signature),
+                signature_string(signature)
+            );
+            return send(header.invalid_args(reason)).await;
+        }
+        let result = (entry.method)(dbus.clone(), header.clone(), body).await;
+        let response = match result {
+            Ok(values) => match header.method_return() {
+                Ok(mut msg) => {
+                    for value in values {
+                        msg.add_value(value);
+                    }
+                    msg
+                }
+                Err(msg) => msg,
+            },
+            Err((name, message)) => header.error(name, message),
+        };
+        send(response).await
+    }
+
+    async fn handle_message(
+        &self,
+        dbus: &DBus,
+        msg: Message,
+        flags: MessageFlags,
+    ) -> DBusResult<()> {
+        if MessageType::MethodCall != msg.get_type() {
+            return Ok(());
+        }
+        match msg.split() {
+            Ok((header, body)) => self.handle_method_call(dbus, header, body, flags).await,
+            Err(e) => {
+                error!("Could not get the signature of the method call: {}", e);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for ObjectServer {
+    async fn handle(&mut self, dbus: &DBus, msg: Message) -> DBusResult<()> {
+        self.handle_message(dbus, msg, MessageFlags::empty()).await
+    }
+
+    async fn handle_with_flags(
+        &mut self,
+        dbus: &DBus,
+        msg: Message,
+        flags: MessageFlags,
+    ) -> DBusResult<()> {
+        self.handle_message(dbus, msg, flags).await
+    }
+}
+
+#[async_trait]
+impl SharedHandler for ObjectServer {
+    async fn handle(&self, dbus: &DBus, msg: Message) -> DBusResult<()> {
+        self.handle_message(dbus, msg, MessageFlags::empty()).await
+    }
+
+    async fn handle_with_flags(
+        &self,
+        dbus: &DBus,
+        msg: Message,
+        flags: MessageFlags,
+    ) -> DBusResult<()> {
+        self.handle_message(dbus, msg, flags).await
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{DBus, DBusResult, Uuid};
+use crate::{
+    runtime::{read_file, spawn},
+    DBus, DBusResult, Uuid,
+};
 use dbus_message_parser::{
     message::{Message, MessageHeader, MessageType},
     value::Value,
@@ -15,7 +18,6 @@ use std::{
     vec::IntoIter,
 };
 use thiserror::Error;
-use tokio::{fs::File, io::AsyncReadExt, spawn};
 
 #[derive(Debug, Error)]
 enum MachineIdError {
@@ -32,24 +34,20 @@ enum MachineIdError {
 }
 
 async fn read_machine_id_from_file(path: &str) -> Result<Uuid, MachineIdError> {
-    let mut file = File::open(path).await?;
-    let mut uuid_str: [u8; 32] = [0; 32];
+    let file = read_file(path).await?;
     let mut uuid: Uuid = [0; 16];
-    let mut new_line: [u8; 2] = [0; 2];
 
-    let read = file.read_exact(&mut uuid_str).await?;
-    if read == uuid_str.len() {
-        // Check if there is more bytes
-        let read = file.read(&mut new_line).await?;
-        let uuid_str = from_utf8(&uuid_str[..])?;
-        if (read == 0 || read == 1) && (new_line[0] == 0 || new_line[0] == b'\n') {
-            decode_to_slice(uuid_str, &mut uuid[..])?;
-            Ok(uuid)
-        } else {
-            Err(MachineIdError::FileTooLarge)
-        }
+    if file.len() < 32 {
+        return Err(MachineIdError::FileTooSmall);
+    }
+    let (uuid_str, rest) = file.split_at(32);
+    // Only a new line is allowed after the Machine ID.
+    if rest.is_empty() || rest == [0] || rest == [b'\n'] {
+        let uuid_str = from_utf8(uuid_str)?;
+        decode_to_slice(uuid_str, &mut uuid[..])?;
+        Ok(uuid)
     } else {
-        Err(MachineIdError::FileTooSmall)
+        Err(MachineIdError::FileTooLarge)
     }
 }
 
@@ -77,7 +75,7 @@ async fn get_machine_id(header: &MessageHeader) -> Message {
     match header.method_return() {
         Ok(mut msg) => match get_machine_id_from_file().await {
             Ok(uuid) => {
-                let uuid = encode(&uuid);
+                let uuid = encode(uuid);
                 msg.add_value(Value::String(uuid));
                 msg
             }
@@ -132,7 +130,7 @@ pub async fn handle_peer(
         }
     };
 
-    dbus.send(response)
+    dbus.send(response).await
 }
 
 async fn peer(dbus: DBus, mut receiver: Receiver<Message>) {
@@ -152,13 +150,11 @@ async fn peer(dbus: DBus, mut receiver: Receiver<Message>) {
     }
 }
 
-pub(super) fn add_peer(dbus: DBus) -> DBusResult<()> {
-    let (sender, receiver) = channel(1024);
+pub(super) fn add_peer(dbus: DBus, channel_size: usize) -> DBusResult<()> {
+    let (sender, receiver) = channel(channel_size);
     let interface = "org.freedesktop.DBus.Peer".try_into().unwrap();
     // Try to add the interface handler.
-    if let Err(e) = dbus.add_method_call_interface(interface, sender) {
-        return Err(e);
-    }
+    dbus.add_method_call_interface(interface, sender)?;
 
     spawn(peer(dbus, receiver));
     Ok(())
@@ -0,0 +1,151 @@
+use crate::{DBus, DBusResult, Handler};
+use async_trait::async_trait;
+use dbus_message_parser::{
+    message::{Message, MessageFlags, MessageHeader, MessageType},
+    value::{Error as ErrorName, Value},
+};
+use std::convert::TryInto;
+
+/// An error, which is sent as an `Error` message to the caller (see [`ReplyHandler`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DBusErrorReply {
+    name: ErrorName,
+    message: String,
+}
+
+impl DBusErrorReply {
+    /// Create an error reply with the given name and message.
+    pub fn new(name: ErrorName, message: String) -> DBusErrorReply {
+        DBusErrorReply { name, message }
+    }
+
+    /// Create an `org.freedesktop.DBus.Error.Failed` error reply.
+    pub fn failed(message: String) -> DBusErrorReply {
+        DBusErrorReply::new(
+            "org.freedesktop.DBus.Error.Failed".try_into().unwrap(),
+            message,
+        )
+    }
+
+    /// Create an `org.freedesktop.DBus.Error.InvalidArgs` error reply.
+    pub fn invalid_args(message: String) -> DBusErrorReply {
+        DBusErrorReply::new(
+            "org.freedesktop.DBus.Error.InvalidArgs".try_into().unwrap(),
+            message,
+        )
+    }
+
+    /// Get the name of the error.
+    pub fn get_name(&self) -> &ErrorName {
+        &self.name
+    }
+
+    /// Get the message of the error.
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// The reply of a [`ReplyHandler`].
+#[derive(Debug, Clone)]
+pub enum Reply {
+    /// Send the message as it is.
+    Message(Box<Message>),
+    /// Send a `MethodReturn` message with the values as the body.
+    Body(Vec<Value>),
+    /// Do not send any reply.
+    NoReply,
+}
+
+impl From<Vec<Value>> for Reply {
+    fn from(body: Vec<Value>) -> Self {
+        Reply::Body(body)
+    }
+}
+
+impl From<Message> for Reply {
+    fn from(msg: Message) -> Self {
+        Reply::Message(Box::new(msg))
+    }
+}
+
+/// The result of a [`ReplyHandler`].
+pub type ReplyResult = Result<Reply, DBusErrorReply>;
+
+/// A trait for a `MethodCall` handler, which returns the reply instead of sending it.
+///
+/// Every type, which implements this trait, implements the [`Handler`] trait. The `MethodReturn`
+/// or the `Error` message is sent automatically, except the caller set the `NO_REPLY_EXPECTED`
+/// flag. A warning is logged, if no reply is returned for a call, which expects a reply.
+/// Messages, which are not a `MethodCall`, are ignored.
+///
+/// [`Handler`]: crate::Handler
+#[async_trait]
+pub trait ReplyHandler: Send {
+    /// Handle the `MethodCall` and return the reply.
+    async fn handle(
+        &mut self,
+        dbus: &DBus,
+        header: &MessageHeader,
+        body: Vec<Value>,
+    ) -> ReplyResult;
+}
+
+#[async_trait]
+impl<T> Handler for T
+where
+    T: ReplyHandler,
+{
+    async fn handle(&mut self, dbus: &DBus, msg: Message) -> DBusResult<()> {
+        self.handle_with_flags(dbus, msg, MessageFlags::empty())
+            .await
+    }
+
+    async fn handle_with_flags(
+        &mut self,
+        dbus: &DBus,
+        msg: Message,
+        flags: MessageFlags,
+    ) -> DBusResult<()> {
+        if MessageType::MethodCall != msg.get_type() {
+            return Ok(());
+        }
+        let (header, body) = match msg.split() {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Could not get the signature of the method call: {}", e);
+                return Ok(());
+            }
+        };
+        let no_reply_expected = flags.contains(MessageFlags::NO_REPLY_EXPECTED);
+        let result = ReplyHandler::handle(self, dbus, &header, body).await;
+        let reply = match result {
+            Ok(Reply::Message(msg)) => *msg,
+            Ok(Reply::Body(body)) => match header.method_return() {
+                Ok(mut msg) => {
+                    for value in body {
+                        msg.add_value(value);
+                    }
+                    msg
+                }
+                Err(msg) => msg,
+            },
+            Ok(Reply::NoReply) => {
+                if !no_reply_expected {
+                    warn!(
+                        "ReplyHandler: no reply for the method call {:?} with the serial {}",
+                        header.get_member(),
+                        header.get_serial()
+                    );
+                }
+                return Ok(());
+            }
+            Err(error) => header.error(error.name, error.message),
+        };
+        if no_reply_expected {
+            Ok(())
+        } else {
+            dbus.send(reply).await
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use async_process::Command;
+#[cfg(feature = "daemon")]
+use async_std::net::TcpListener as AsyncStdTcpListener;
+use async_std::os::unix::net::UnixListener as AsyncStdUnixListener;
+use async_std::{
+    fs::read,
+    net::{TcpStream as AsyncStdTcpStream, ToSocketAddrs},
+    os::unix::net::UnixStream as AsyncStdUnixStream,
+    task::{block_on, sleep as async_std_sleep, spawn},
+};
+use futures::io::{AsyncRead, AsyncWrite};
+use std::os::unix::net::UnixListener as StdUnixListener;
+use std::{
+    future::Future,
+    io::{IoSlice, Result as IoResult},
+    net::{Shutdown, SocketAddr},
+    os::unix::{
+        io::{AsRawFd, RawFd},
+        net::UnixStream as StdUnixStream,
+    },
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// A socket, which can shut down the writing side.
+pub(crate) trait ShutdownWrite {
+    fn shutdown_write(&self) -> IoResult<()>;
+}
+
+impl ShutdownWrite for AsyncStdUnixStream {
+    fn shutdown_write(&self) -> IoResult<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+impl ShutdownWrite for AsyncStdTcpStream {
+    fn shutdown_write(&self) -> IoResult<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+/// Shuts down the writing side of the socket on close. The `async-std` sockets only flush on
+/// close, so the peer would not notice that the connection is closed.
+#[derive(Debug)]
+pub(crate) struct Socket<T>(T);
+
+impl<T> AsyncRead for Socket<T>
+where
+    T: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl<T> AsyncWrite for Socket<T>
+where
+    T: AsyncWrite + ShutdownWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.0).poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        match Pin::new(&mut self.0).poll_flush(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(self.0.shutdown_write()),
+            poll => poll,
+        }
+    }
+}
+
+impl<T> AsRawFd for Socket<T>
+where
+    T: AsRawFd,
+{
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+pub(crate) type UnixStream = Socket<AsyncStdUnixStream>;
+
+pub(crate) type TcpStream = Socket<AsyncStdTcpStream>;
+
+/// A listening Unix Domain Stream socket.
+pub(crate) struct UnixListener(AsyncStdUnixListener);
+
+impl UnixListener {
+    pub(crate) async fn accept(&self) -> IoResult<UnixStream> {
+        let (stream, _) = self.0.accept().await?;
+        Ok(Socket(stream))
+    }
+}
+
+/// A listening TCP socket.
+#[cfg(feature = "daemon")]
+pub(crate) struct TcpListener(AsyncStdTcpListener);
+
+#[cfg(feature = "daemon")]
+impl TcpListener {
+    pub(crate) async fn accept(&self) -> IoResult<TcpStream> {
+        let (stream, _) = self.0.accept().await?;
+        Ok(Socket(stream))
+    }
+
+    pub(crate) fn local_addr(&self) -> IoResult<SocketAddr> {
+        self.0.local_addr()
+    }
+}
+
+/// The executor of the runtime thread.
+pub(super) struct Executor;
+
+impl Executor {
+    pub(super) fn new() -> IoResult<Executor> {
+        Ok(Executor)
+    }
+
+    pub(super) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        block_on(future)
+    }
+}
+
+pub(super) fn spawn_detached<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    spawn(future);
+}
+
+pub(crate) async fn sleep(duration: Duration) {
+    async_std_sleep(duration).await
+}
+
+pub(crate) async fn read_file(path: &str) -> IoResult<Vec<u8>> {
+    read(path).await
+}
+
+pub(crate) async fn command_output(path: &str, argv: &[String]) -> IoResult<Vec<u8>> {
+    let output = Command::new(path).args(argv).output().await?;
+    Ok(output.stdout)
+}
+
+pub(crate) async fn connect_unix(path: &str) -> IoResult<UnixStream> {
+    Ok(Socket(AsyncStdUnixStream::connect(path).await?))
+}
+
+pub(crate) async fn connect_tcp(socket_addr: &SocketAddr) -> IoResult<TcpStream> {
+    Ok(Socket(AsyncStdTcpStream::connect(socket_addr).await?))
+}
+
+pub(crate) fn unix_stream_from_std(unix_stream: StdUnixStream) -> IoResult<UnixStream> {
+    unix_stream.set_nonblocking(true)?;
+    Ok(Socket(AsyncStdUnixStream::from(unix_stream)))
+}
+
+pub(crate) async fn lookup_host(host: &str, port: u16) -> IoResult<Vec<SocketAddr>> {
+    Ok((host, port).to_socket_addrs().await?.collect())
+}
+
+#[cfg(feature = "daemon")]
+pub(crate) async fn bind_unix(path: &str) -> IoResult<UnixListener> {
+    Ok(UnixListener(AsyncStdUnixListener::bind(path).await?))
+}
+
+pub(crate) fn unix_listener_from_std(unix_listener: StdUnixListener) -> IoResult<UnixListener> {
+    unix_listener.set_nonblocking(true)?;
+    Ok(UnixListener(AsyncStdUnixListener::from(unix_listener)))
+}
+
+#[cfg(feature = "daemon")]
+pub(crate) async fn bind_tcp(socket_addr: &SocketAddr) -> IoResult<TcpListener> {
+    Ok(TcpListener(AsyncStdTcpListener::bind(socket_addr).await?))
+}
@@ -0,0 +1,99 @@
+use super::imp::{sleep, spawn_detached};
+use crate::handler::panic_message;
+use futures::{
+    channel::oneshot::{channel, Receiver},
+    future::{select, AbortHandle, Abortable, Aborted, Either},
+    FutureExt,
+};
+use std::{
+    any::Any,
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use thiserror::Error;
+
+/// The error of a task, which did not run to completion.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JoinError {
+    #[error("Task was cancelled")]
+    Cancelled,
+    #[error("Task panicked: {0}")]
+    Panic(String),
+}
+
+type TaskResult<T> = Result<Result<T, Box<dyn Any + Send>>, Aborted>;
+
+/// A handle of a spawned task, which is returned by [`DBus::new`] and [`DBusBuilder::build`].
+///
+/// Awaiting the handle returns the output of the task. Dropping the handle detaches the task,
+/// the task keeps running. The handle is the same for all runtimes.
+///
+/// [`DBus::new`]: crate::DBus::new
+/// [`DBusBuilder::build`]: crate::DBusBuilder::build
+#[derive(Debug)]
+pub struct JoinHandle<T> {
+    receiver: Receiver<TaskResult<T>>,
+    abort_handle: AbortHandle,
+}
+
+impl<T> JoinHandle<T> {
+    /// Stop the task. Awaiting the handle afterwards returns [`JoinError::Cancelled`], if the
+    /// task did not finish before.
+    pub fn abort(&self) {
+        self.abort_handle.abort();
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = match self.receiver.poll_unpin(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        let result = match result {
+            Ok(Ok(Ok(output))) => Ok(output),
+            Ok(Ok(Err(panic))) => Err(JoinError::Panic(panic_message(panic))),
+            // The task was aborted or the runtime dropped the task.
+            Ok(Err(Aborted)) | Err(_) => Err(JoinError::Cancelled),
+        };
+        Poll::Ready(result)
+    }
+}
+
+/// Spawn the future on the runtime. A panic of the future is caught and returned by the
+/// [`JoinHandle`].
+pub(crate) fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let (sender, receiver) = channel();
+    let (abort_handle, abort_registration) = AbortHandle::new_pair();
+    let future = Abortable::new(AssertUnwindSafe(future).catch_unwind(), abort_registration);
+    spawn_detached(async move {
+        let _ = sender.send(future.await);
+    });
+    JoinHandle {
+        receiver,
+        abort_handle,
+    }
+}
+
+/// Wait for the future at most for the given duration. Returns `Err(())`, if the duration
+/// elapsed.
+pub(crate) async fn timeout<F>(duration: Duration, future: F) -> Result<F::Output, ()>
+where
+    F: Future,
+{
+    let future = Box::pin(future);
+    let sleep = Box::pin(sleep(duration));
+    match select(future, sleep).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(()),
+    }
+}
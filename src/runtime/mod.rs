@@ -0,0 +1,48 @@
+//! The async runtime, which is used to spawn tasks, to connect sockets, to read files and to
+//! sleep.
+//!
+//! The runtime is selected by one of the cargo features `runtime-tokio` (default),
+//! `runtime-async-std` or `runtime-smol`. If more than one of the features is enabled then the
+//! runtime is selected in this order.
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+mod async_std_runtime;
+mod join_handle;
+#[cfg(all(
+    feature = "runtime-smol",
+    not(any(feature = "runtime-tokio", feature = "runtime-async-std"))
+))]
+mod smol_runtime;
+mod thread;
+#[cfg(feature = "runtime-tokio")]
+mod tokio_runtime;
+
+#[cfg(not(any(
+    feature = "runtime-tokio",
+    feature = "runtime-async-std",
+    feature = "runtime-smol"
+)))]
+compile_error!(
+    "one of the features `runtime-tokio`, `runtime-async-std` or `runtime-smol` has to be enabled"
+);
+
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+use async_std_runtime as imp;
+#[cfg(all(
+    feature = "runtime-smol",
+    not(any(feature = "runtime-tokio", feature = "runtime-async-std"))
+))]
+use smol_runtime as imp;
+#[cfg(feature = "runtime-tokio")]
+use tokio_runtime as imp;
+
+#[cfg(feature = "daemon")]
+pub(crate) use imp::{bind_tcp, bind_unix, sleep, TcpListener};
+pub(crate) use imp::{
+    command_output, connect_tcp, connect_unix, lookup_host, read_file, unix_listener_from_std,
+    unix_stream_from_std, TcpStream, UnixListener, UnixStream,
+};
+pub(crate) use join_handle::{spawn, timeout};
+pub use join_handle::{JoinError, JoinHandle};
+pub(crate) use thread::RuntimeThread;
+#[cfg(feature = "runtime-tokio")]
+pub use tokio_runtime::TokioCompat;
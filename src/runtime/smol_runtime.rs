@@ -0,0 +1,108 @@
+use smol::net::unix::UnixListener as SmolUnixListener;
+#[cfg(feature = "daemon")]
+use smol::net::TcpListener as SmolTcpListener;
+use smol::{
+    block_on,
+    fs::read,
+    net::{resolve, unix::UnixStream as SmolUnixStream, TcpStream as SmolTcpStream},
+    process::Command,
+    spawn, Timer,
+};
+use std::os::unix::net::UnixListener as StdUnixListener;
+use std::{
+    convert::TryFrom, future::Future, io::Result as IoResult, net::SocketAddr,
+    os::unix::net::UnixStream as StdUnixStream, time::Duration,
+};
+
+pub(crate) type UnixStream = SmolUnixStream;
+
+pub(crate) type TcpStream = SmolTcpStream;
+
+/// A listening Unix Domain Stream socket.
+pub(crate) struct UnixListener(SmolUnixListener);
+
+impl UnixListener {
+    pub(crate) async fn accept(&self) -> IoResult<UnixStream> {
+        let (stream, _) = self.0.accept().await?;
+        Ok(stream)
+    }
+}
+
+/// A listening TCP socket.
+#[cfg(feature = "daemon")]
+pub(crate) struct TcpListener(SmolTcpListener);
+
+#[cfg(feature = "daemon")]
+impl TcpListener {
+    pub(crate) async fn accept(&self) -> IoResult<TcpStream> {
+        let (stream, _) = self.0.accept().await?;
+        Ok(stream)
+    }
+
+    pub(crate) fn local_addr(&self) -> IoResult<SocketAddr> {
+        self.0.local_addr()
+    }
+}
+
+/// The executor of the runtime thread.
+pub(super) struct Executor;
+
+impl Executor {
+    pub(super) fn new() -> IoResult<Executor> {
+        Ok(Executor)
+    }
+
+    pub(super) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        block_on(future)
+    }
+}
+
+pub(super) fn spawn_detached<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    spawn(future).detach();
+}
+
+pub(crate) async fn sleep(duration: Duration) {
+    Timer::after(duration).await;
+}
+
+pub(crate) async fn read_file(path: &str) -> IoResult<Vec<u8>> {
+    read(path).await
+}
+
+pub(crate) async fn command_output(path: &str, argv: &[String]) -> IoResult<Vec<u8>> {
+    let output = Command::new(path).args(argv).output().await?;
+    Ok(output.stdout)
+}
+
+pub(crate) async fn connect_unix(path: &str) -> IoResult<UnixStream> {
+    UnixStream::connect(path).await
+}
+
+pub(crate) async fn connect_tcp(socket_addr: &SocketAddr) -> IoResult<TcpStream> {
+    TcpStream::connect(socket_addr).await
+}
+
+pub(crate) fn unix_stream_from_std(unix_stream: StdUnixStream) -> IoResult<UnixStream> {
+    UnixStream::try_from(unix_stream)
+}
+
+pub(crate) async fn lookup_host(host: &str, port: u16) -> IoResult<Vec<SocketAddr>> {
+    resolve((host, port)).await
+}
+
+#[cfg(feature = "daemon")]
+pub(crate) async fn bind_unix(path: &str) -> IoResult<UnixListener> {
+    Ok(UnixListener(SmolUnixListener::bind(path)?))
+}
+
+pub(crate) fn unix_listener_from_std(unix_listener: StdUnixListener) -> IoResult<UnixListener> {
+    Ok(UnixListener(SmolUnixListener::try_from(unix_listener)?))
+}
+
+#[cfg(feature = "daemon")]
+pub(crate) async fn bind_tcp(socket_addr: &SocketAddr) -> IoResult<TcpListener> {
+    Ok(TcpListener(SmolTcpListener::bind(socket_addr).await?))
+}
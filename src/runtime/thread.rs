@@ -0,0 +1,84 @@
+use super::{
+    imp::{spawn_detached, Executor},
+    spawn, JoinError,
+};
+use futures::{
+    channel::{
+        mpsc::{unbounded, UnboundedSender},
+        oneshot::channel,
+    },
+    executor::block_on,
+    future::BoxFuture,
+    FutureExt, StreamExt,
+};
+use std::{
+    future::Future,
+    io::Result as IoResult,
+    thread::{Builder, JoinHandle as ThreadJoinHandle},
+};
+
+/// A runtime, which runs in its own thread. The futures are run in the thread, so they can use
+/// the IO and the timers of the runtime.
+pub(crate) struct RuntimeThread {
+    sender: Option<UnboundedSender<BoxFuture<'static, ()>>>,
+    thread: Option<ThreadJoinHandle<()>>,
+}
+
+impl RuntimeThread {
+    pub(crate) fn new() -> IoResult<RuntimeThread> {
+        let executor = Executor::new()?;
+        let (sender, mut receiver) = unbounded::<BoxFuture<'static, ()>>();
+        let thread = Builder::new()
+            .name("dbus-async".to_string())
+            .spawn(move || {
+                executor.block_on(async move {
+                    while let Some(future) = receiver.next().await {
+                        spawn_detached(future);
+                    }
+                })
+            })?;
+        Ok(RuntimeThread {
+            sender: Some(sender),
+            thread: Some(thread),
+        })
+    }
+
+    /// Run the future in the thread of the runtime and block the current thread until the future
+    /// is completed.
+    ///
+    /// This function must not be called inside an async context.
+    pub(crate) fn block_on<F>(&self, future: F) -> Result<F::Output, JoinError>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (sender, receiver) = channel();
+        let future = async move {
+            let _ = sender.send(spawn(future).await);
+        };
+        match &self.sender {
+            Some(runtime_sender) => {
+                if runtime_sender.unbounded_send(future.boxed()).is_err() {
+                    return Err(JoinError::Cancelled);
+                }
+            }
+            None => return Err(JoinError::Cancelled),
+        }
+        match block_on(receiver) {
+            Ok(result) => result,
+            Err(_) => Err(JoinError::Cancelled),
+        }
+    }
+}
+
+impl Drop for RuntimeThread {
+    fn drop(&mut self) {
+        // Close the channel, so the thread stops.
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                error!("Runtime thread panicked");
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use futures::io::{AsyncRead, AsyncWrite};
+use std::os::unix::net::UnixListener as StdUnixListener;
+use std::{
+    future::Future,
+    io::{IoSlice, Result as IoResult},
+    net::SocketAddr,
+    os::unix::{
+        io::{AsRawFd, RawFd},
+        net::UnixStream as StdUnixStream,
+    },
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+#[cfg(feature = "daemon")]
+use tokio::net::TcpListener as TokioTcpListener;
+use tokio::net::UnixListener as TokioUnixListener;
+use tokio::{
+    fs::read,
+    io::{AsyncRead as TokioAsyncRead, AsyncWrite as TokioAsyncWrite, ReadBuf},
+    net::{
+        lookup_host as tokio_lookup_host, TcpStream as TokioTcpStream,
+        UnixStream as TokioUnixStream,
+    },
+    process::Command,
+    runtime::{Builder, Runtime},
+    spawn,
+    time::sleep as tokio_sleep,
+};
+
+/// Implements the `futures` IO traits for a `tokio` IO object, for example a
+/// `tokio::io::DuplexStream` (see [`DBusBuilder::build_with_stream`]).
+///
+/// [`DBusBuilder::build_with_stream`]: crate::DBusBuilder::build_with_stream
+#[derive(Debug)]
+pub struct TokioCompat<T>(T);
+
+impl<T> TokioCompat<T> {
+    /// Wrap the `tokio` IO object.
+    pub fn new(inner: T) -> TokioCompat<T> {
+        TokioCompat(inner)
+    }
+
+    /// Get the `tokio` IO object.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> AsyncRead for TokioCompat<T>
+where
+    T: TokioAsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<IoResult<usize>> {
+        let mut read_buf = ReadBuf::new(buf);
+        match Pin::new(&mut self.0).poll_read(cx, &mut read_buf) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(read_buf.filled().len())),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T> AsyncWrite for TokioCompat<T>
+where
+    T: TokioAsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<IoResult<usize>> {
+        Pin::new(&mut self.0).poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+impl<T> AsRawFd for TokioCompat<T>
+where
+    T: AsRawFd,
+{
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+pub(crate) type UnixStream = TokioCompat<TokioUnixStream>;
+
+pub(crate) type TcpStream = TokioCompat<TokioTcpStream>;
+
+/// A listening Unix Domain Stream socket.
+pub(crate) struct UnixListener(TokioUnixListener);
+
+impl UnixListener {
+    pub(crate) async fn accept(&self) -> IoResult<UnixStream> {
+        let (stream, _) = self.0.accept().await?;
+        Ok(TokioCompat(stream))
+    }
+}
+
+/// A listening TCP socket.
+#[cfg(feature = "daemon")]
+pub(crate) struct TcpListener(TokioTcpListener);
+
+#[cfg(feature = "daemon")]
+impl TcpListener {
+    pub(crate) async fn accept(&self) -> IoResult<TcpStream> {
+        let (stream, _) = self.0.accept().await?;
+        Ok(TokioCompat(stream))
+    }
+
+    pub(crate) fn local_addr(&self) -> IoResult<SocketAddr> {
+        self.0.local_addr()
+    }
+}
+
+/// The executor of the runtime thread.
+pub(super) struct Executor(Runtime);
+
+impl Executor {
+    pub(super) fn new() -> IoResult<Executor> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        Ok(Executor(runtime))
+    }
+
+    pub(super) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.0.block_on(future)
+    }
+}
+
+pub(super) fn spawn_detached<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    spawn(future);
+}
+
+pub(crate) async fn sleep(duration: Duration) {
+    tokio_sleep(duration).await
+}
+
+pub(crate) async fn read_file(path: &str) -> IoResult<Vec<u8>> {
+    read(path).await
+}
+
+pub(crate) async fn command_output(path: &str, argv: &[String]) -> IoResult<Vec<u8>> {
+    let output = Command::new(path).args(argv).output().await?;
+    Ok(output.stdout)
+}
+
+pub(crate) async fn connect_unix(path: &str) -> IoResult<UnixStream> {
+    Ok(TokioCompat(TokioUnixStream::connect(path).await?))
+}
+
+pub(crate) async fn connect_tcp(socket_addr: &SocketAddr) -> IoResult<TcpStream> {
+    Ok(TokioCompat(TokioTcpStream::connect(socket_addr).await?))
+}
+
+pub(crate) fn unix_stream_from_std(unix_stream: StdUnixStream) -> IoResult<UnixStream> {
+    unix_stream.set_nonblocking(true)?;
+    Ok(TokioCompat(TokioUnixStream::from_std(unix_stream)?))
+}
+
+pub(crate) async fn lookup_host(host: &str, port: u16) -> IoResult<Vec<SocketAddr>> {
+    Ok(tokio_lookup_host((host, port)).await?.collect())
+}
+
+#[cfg(feature = "daemon")]
+pub(crate) async fn bind_unix(path: &str) -> IoResult<UnixListener> {
+    Ok(UnixListener(TokioUnixListener::bind(path)?))
+}
+
+pub(crate) fn unix_listener_from_std(unix_listener: StdUnixListener) -> IoResult<UnixListener> {
+    unix_listener.set_nonblocking(true)?;
+    Ok(UnixListener(TokioUnixListener::from_std(unix_listener)?))
+}
+
+#[cfg(feature = "daemon")]
+pub(crate) async fn bind_tcp(socket_addr: &SocketAddr) -> IoResult<TcpListener> {
+    Ok(TcpListener(TokioTcpListener::bind(socket_addr).await?))
+}
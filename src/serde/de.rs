@@ -0,0 +1,367 @@
+use super::SerdeError;
+use dbus_message_parser::value::{Type, Value};
+use serde::{
+    de::{
+        value::StringDeserializer, DeserializeSeed, Deserializer, EnumAccess, Error,
+        IntoDeserializer, MapAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+use std::vec::IntoIter;
+
+/// A [`Deserializer`], which converts a [`Value`] into the data.
+///
+/// [`Deserializer`]: serde::de::Deserializer
+/// [`Value`]: dbus_message_parser::value::Value
+pub(super) struct ValueDeserializer(pub(super) Value);
+
+/// Visit all values as a sequence and check that all values are consumed.
+fn visit_seq<'de, V: Visitor<'de>>(values: Vec<Value>, visitor: V) -> Result<V::Value, SerdeError> {
+    let len = values.len();
+    let mut seq = SeqDeserializer(values.into_iter());
+    let value = visitor.visit_seq(&mut seq)?;
+    let remaining = seq.0.len();
+    if remaining == 0 {
+        Ok(value)
+    } else {
+        Err(SerdeError::invalid_length(len, &"fewer elements"))
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Value::Byte(v) => visitor.visit_u8(v),
+            Value::Boolean(v) => visitor.visit_bool(v),
+            Value::Int16(v) => visitor.visit_i16(v),
+            Value::Uint16(v) => visitor.visit_u16(v),
+            Value::Int32(v) => visitor.visit_i32(v),
+            Value::Uint32(v) => visitor.visit_u32(v),
+            Value::Int64(v) => visitor.visit_i64(v),
+            Value::Uint64(v) => visitor.visit_u64(v),
+            Value::Double(v) => visitor.visit_f64(v),
+            Value::String(v) => visitor.visit_string(v),
+            Value::ObjectPath(v) => visitor.visit_str(v.as_ref()),
+            Value::Signature(v) => visitor.visit_string(Type::from_signature_to_string(&v)?),
+            Value::Array(array) => {
+                if let Type::DictEntry(_) = array.get_type() {
+                    let mut map = MapDeserializer {
+                        iter: Vec::from(array).into_iter(),
+                        value: None,
+                    };
+                    let value = visitor.visit_map(&mut map)?;
+                    let remaining = map.iter.len();
+                    if remaining == 0 {
+                        Ok(value)
+                    } else {
+                        Err(SerdeError::invalid_length(remaining, &"fewer entries"))
+                    }
+                } else {
+                    visit_seq(array.into(), visitor)
+                }
+            }
+            Value::Struct(fields) => visit_seq(fields.into(), visitor),
+            Value::DictEntry(dict_entry) => {
+                let (key, value) = *dict_entry;
+                visit_seq(vec![key, value], visitor)
+            }
+            Value::Variant(value) => ValueDeserializer(*value).deserialize_any(visitor),
+            #[cfg(target_family = "unix")]
+            Value::UnixFD(v) => visitor.visit_i32(v),
+        }
+    }
+
+    /// An [`Option`] is represented as an array, which has zero or one element.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Value::Array(array) => {
+                let mut array: Vec<Value> = array.into();
+                match array.len() {
+                    0 => visitor.visit_none(),
+                    // The unwrap function call will never panic because the length is checked.
+                    1 => visitor.visit_some(ValueDeserializer(array.pop().unwrap())),
+                    len => Err(SerdeError::invalid_length(len, &"zero or one element")),
+                }
+            }
+            Value::Variant(value) => ValueDeserializer(*value).deserialize_option(visitor),
+            value => visitor.visit_some(ValueDeserializer(value)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// An enum is represented as a struct, which contains the name of the variant and the
+    /// content of the variant. A unit variant can also be represented as a string.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.0 {
+            Value::Struct(fields) => {
+                let mut fields = Vec::from(fields).into_iter();
+                match (fields.next(), fields.next(), fields.next()) {
+                    (Some(Value::String(variant)), Some(content), None) => {
+                        visitor.visit_enum(EnumDeserializer {
+                            variant,
+                            content: Some(content),
+                        })
+                    }
+                    _ => Err(SerdeError::invalid_type(
+                        Unexpected::Other("struct"),
+                        &visitor,
+                    )),
+                }
+            }
+            Value::String(variant) => visitor.visit_enum(EnumDeserializer {
+                variant,
+                content: None,
+            }),
+            Value::Variant(value) => {
+                ValueDeserializer(*value).deserialize_enum(name, variants, visitor)
+            }
+            _ => Err(SerdeError::invalid_type(
+                Unexpected::Other("value"),
+                &visitor,
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqDeserializer(IntoIter<Value>);
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        match self.0.next() {
+            Some(value) => seed.deserialize(ValueDeserializer(value)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct MapDeserializer {
+    iter: IntoIter<Value>,
+    value: Option<Value>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        match self.iter.next() {
+            Some(Value::DictEntry(dict_entry)) => {
+                let (key, value) = *dict_entry;
+                self.value = Some(value);
+                seed.deserialize(ValueDeserializer(key)).map(Some)
+            }
+            Some(_) => Err(SerdeError::custom("array element is not a dict entry")),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ValueDeserializer(value)),
+            None => Err(SerdeError::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    content: Option<Value>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = SerdeError;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer), SerdeError> {
+        let variant: StringDeserializer<SerdeError> = self.variant.into_deserializer();
+        let value = seed.deserialize(variant)?;
+        Ok((value, VariantDeserializer(self.content)))
+    }
+}
+
+struct VariantDeserializer(Option<Value>);
+
+impl VariantDeserializer {
+    fn content(self) -> Result<ValueDeserializer, SerdeError> {
+        match self.0 {
+            Some(content) => Ok(ValueDeserializer(content)),
+            None => Err(SerdeError::invalid_type(
+                Unexpected::UnitVariant,
+                &"variant with content",
+            )),
+        }
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self.content()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        self.content()?.deserialize_any(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.content()?.deserialize_any(visitor)
+    }
+}
+
+/// A [`Deserializer`] for the whole body of a message. A tuple is deserialized from all values
+/// of the body and all other types are deserialized from a single value.
+///
+/// [`Deserializer`]: serde::de::Deserializer
+pub(super) struct BodyDeserializer(pub(super) Vec<Value>);
+
+impl BodyDeserializer {
+    fn single(mut self) -> Result<ValueDeserializer, SerdeError> {
+        if self.0.len() == 1 {
+            // The unwrap function call will never panic because the length is checked.
+            Ok(ValueDeserializer(self.0.pop().unwrap()))
+        } else {
+            Err(SerdeError::ArgumentCount(1, self.0.len()))
+        }
+    }
+
+    fn args<'de, V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        if self.0.len() == len {
+            visit_seq(self.0, visitor)
+        } else {
+            Err(SerdeError::ArgumentCount(len, self.0.len()))
+        }
+    }
+}
+
+macro_rules! single {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for BodyDeserializer {
+    type Error = SerdeError;
+
+    single! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_option deserialize_seq
+        deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if self.0.is_empty() {
+            visitor.visit_unit()
+        } else {
+            Err(SerdeError::ArgumentCount(0, self.0.len()))
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.args(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.args(len, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.single()?.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+}
@@ -0,0 +1,43 @@
+use dbus_message_parser::value::{ArrayError, StructError, Type, TypeError};
+use std::fmt::Display;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SerdeError {
+    #[error("{0}")]
+    Message(String),
+    #[error("Unexpected value: expected '{0}' got {1}")]
+    TypeMismatch(Type, &'static str),
+    #[error("Integer is out of range for '{0}'")]
+    OutOfRange(Type),
+    #[error("Unsupported type: {0}")]
+    Unsupported(&'static str),
+    #[error("The key of a map has to be a basic type: {0}")]
+    MapKey(Type),
+    #[error("Unknown variant of the enum {0}: {1}")]
+    UnknownVariant(&'static str, &'static str),
+    #[error("Unexpected number of fields: expected {0} got {1}")]
+    FieldCount(usize, usize),
+    #[error("Unexpected number of arguments: expected {0} got {1}")]
+    ArgumentCount(usize, usize),
+    #[error("The type is too deeply nested or recursive")]
+    Depth,
+    #[error("Could not create array: {0}")]
+    ArrayError(#[from] ArrayError),
+    #[error("Could not create struct: {0}")]
+    StructError(#[from] StructError),
+    #[error("Could not get type: {0}")]
+    TypeError(#[from] TypeError),
+}
+
+impl ::serde::ser::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Message(msg.to_string())
+    }
+}
+
+impl ::serde::de::Error for SerdeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        SerdeError::Message(msg.to_string())
+    }
+}
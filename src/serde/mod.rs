@@ -0,0 +1,105 @@
+//! Convert the [serde] data model to and from DBus [`Value`]s.
+//!
+//! This module is only available with the `serde` feature. The types are mapped as follows:
+//! * `bool`, `u8`, `i16`, `u16`, `i32`, `u32`, `i64`, `u64` and `f64` to the basic types.
+//!   `i8` is mapped to `n` and `f32` to `d`.
+//! * `char` and strings to `s`.
+//! * Sequences to arrays (`a..`) and maps to arrays of dict entries (`a{..}`).
+//! * Structs and tuples to structs (`(..)`).
+//! * `Option` to an array, which has zero or one element.
+//! * Enums to a struct, which contains the name of the variant and the content of the variant
+//!   (`(sv)`). The variant of a unit variant contains an empty array (`av`).
+//!
+//! A tuple (or unit) is mapped to the arguments of the body, all other types are a single
+//! argument.
+//!
+//! The signature is inferred from the [`Deserialize`] implementation of the type, so the type has
+//! to implement [`Serialize`] and [`Deserialize`] to be encoded. The inference calls the
+//! [`Deserialize`] implementation with default values (zero, empty strings and a single element
+//! for sequences and maps). Types, which reject these values, self-describing types (for example
+//! untagged enums) and recursive types are not supported.
+//!
+//! [serde]: https://serde.rs
+//! [`Value`]: dbus_message_parser::value::Value
+//! [`Serialize`]: serde::Serialize
+//! [`Deserialize`]: serde::Deserialize
+mod de;
+mod r#enum;
+mod ser;
+mod trace;
+
+pub use r#enum::SerdeError;
+
+use dbus_message_parser::{
+    message::Message,
+    value::{Type, Value},
+};
+use de::{BodyDeserializer, ValueDeserializer};
+use ser::Serializer;
+use serde::{de::DeserializeOwned, Serialize};
+use trace::trace;
+
+/// Get the signature of the body of the type `T`.
+pub fn signature<T: DeserializeOwned>() -> Result<Vec<Type>, SerdeError> {
+    Ok(trace::<T>(true)?.body)
+}
+
+/// Convert the data to a single [`Value`].
+///
+/// [`Value`]: dbus_message_parser::value::Value
+pub fn to_value<T: Serialize + DeserializeOwned>(value: &T) -> Result<Value, SerdeError> {
+    let traced = trace::<T>(false)?;
+    value.serialize(Serializer::new(&traced.body[0], &traced.enums))
+}
+
+/// Convert a single [`Value`] to the data.
+///
+/// [`Value`]: dbus_message_parser::value::Value
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, SerdeError> {
+    T::deserialize(ValueDeserializer(value))
+}
+
+/// Convert the data to the values of a body.
+pub fn to_body<T: Serialize + DeserializeOwned>(value: &T) -> Result<Vec<Value>, SerdeError> {
+    let traced = trace::<T>(true)?;
+    if !traced.args {
+        let value = value.serialize(Serializer::new(&traced.body[0], &traced.enums))?;
+        return Ok(vec![value]);
+    }
+    if traced.body.is_empty() {
+        return Ok(Vec::new());
+    }
+    let type_ = Type::Struct(traced.body);
+    match value.serialize(Serializer::new(&type_, &traced.enums))? {
+        Value::Struct(fields) => Ok(fields.into()),
+        _ => Err(SerdeError::TypeMismatch(type_, "tuple")),
+    }
+}
+
+/// Convert the values of a body to the data.
+pub fn from_body<T: DeserializeOwned>(body: Vec<Value>) -> Result<T, SerdeError> {
+    T::deserialize(BodyDeserializer(body))
+}
+
+/// Encode the data into the body of the [`Message`].
+///
+/// The values are appended to the body.
+///
+/// [`Message`]: dbus_message_parser::message::Message
+pub fn encode_body<T: Serialize + DeserializeOwned>(
+    msg: &mut Message,
+    value: &T,
+) -> Result<(), SerdeError> {
+    for value in to_body(value)? {
+        msg.add_value(value);
+    }
+    Ok(())
+}
+
+/// Decode the body of the [`Message`] into the data.
+///
+/// [`Message`]: dbus_message_parser::message::Message
+pub fn decode_body<T: DeserializeOwned>(msg: Message) -> Result<T, SerdeError> {
+    let (_, body) = msg.split()?;
+    from_body(body)
+}
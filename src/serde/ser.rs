@@ -0,0 +1,496 @@
+use super::{
+    trace::{enum_type, Enums},
+    SerdeError,
+};
+use dbus_message_parser::value::{Array, Struct, Type, Value};
+use serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use std::convert::{TryFrom, TryInto};
+
+/// A [`Serializer`], which converts the data into a [`Value`] of the given [`Type`].
+///
+/// [`Serializer`]: serde::ser::Serializer
+/// [`Value`]: dbus_message_parser::value::Value
+/// [`Type`]: dbus_message_parser::value::Type
+#[derive(Clone, Copy)]
+pub(super) struct Serializer<'a> {
+    type_: &'a Type,
+    enums: &'a Enums,
+}
+
+impl<'a> Serializer<'a> {
+    pub(super) fn new(type_: &'a Type, enums: &'a Enums) -> Serializer<'a> {
+        Serializer { type_, enums }
+    }
+
+    fn mismatch(&self, value: &'static str) -> SerdeError {
+        SerdeError::TypeMismatch(self.type_.clone(), value)
+    }
+
+    fn signed(self, v: i64) -> Result<Value, SerdeError> {
+        let value = match self.type_ {
+            Type::Byte => v.try_into().ok().map(Value::Byte),
+            Type::Int16 => v.try_into().ok().map(Value::Int16),
+            Type::Uint16 => v.try_into().ok().map(Value::Uint16),
+            Type::Int32 => v.try_into().ok().map(Value::Int32),
+            Type::Uint32 => v.try_into().ok().map(Value::Uint32),
+            Type::Int64 => Some(Value::Int64(v)),
+            Type::Uint64 => v.try_into().ok().map(Value::Uint64),
+            _ => return Err(self.mismatch("integer")),
+        };
+        value.ok_or_else(|| SerdeError::OutOfRange(self.type_.clone()))
+    }
+
+    fn unsigned(self, v: u64) -> Result<Value, SerdeError> {
+        let value = match self.type_ {
+            Type::Byte => v.try_into().ok().map(Value::Byte),
+            Type::Int16 => v.try_into().ok().map(Value::Int16),
+            Type::Uint16 => v.try_into().ok().map(Value::Uint16),
+            Type::Int32 => v.try_into().ok().map(Value::Int32),
+            Type::Uint32 => v.try_into().ok().map(Value::Uint32),
+            Type::Int64 => v.try_into().ok().map(Value::Int64),
+            Type::Uint64 => Some(Value::Uint64(v)),
+            _ => return Err(self.mismatch("integer")),
+        };
+        value.ok_or_else(|| SerdeError::OutOfRange(self.type_.clone()))
+    }
+
+    fn child<'b>(&self, type_: &'b Type) -> Serializer<'b>
+    where
+        'a: 'b,
+    {
+        Serializer {
+            type_,
+            enums: self.enums,
+        }
+    }
+
+    /// Get the content type of a variant and check that the enum is expected.
+    fn variant(
+        &self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Option<&'a Type>, SerdeError> {
+        if self.type_ != &enum_type() {
+            return Err(self.mismatch("enum"));
+        }
+        self.enums.content(name, index, variant)
+    }
+
+    fn fields(
+        &self,
+        type_: Option<&'a Type>,
+        len: usize,
+        variant: Option<&'static str>,
+    ) -> Result<StructSerializer<'a>, SerdeError> {
+        match type_ {
+            Some(Type::Struct(types)) => {
+                if types.len() == len {
+                    Ok(StructSerializer {
+                        types,
+                        enums: self.enums,
+                        fields: Vec::with_capacity(len),
+                        variant,
+                    })
+                } else {
+                    Err(SerdeError::FieldCount(types.len(), len))
+                }
+            }
+            _ => Err(self.mismatch("struct")),
+        }
+    }
+}
+
+/// Create the value of an enum.
+fn enum_value(variant: &'static str, content: Value) -> Value {
+    let fields = vec![
+        Value::String(variant.to_string()),
+        Value::Variant(Box::new(content)),
+    ];
+    // The unwrap function call will never panic because the struct is not empty.
+    Value::Struct(Struct::try_from(fields).unwrap())
+}
+
+impl<'a> serde::ser::Serializer for Serializer<'a> {
+    type Ok = Value;
+    type Error = SerdeError;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = StructSerializer<'a>;
+    type SerializeTupleStruct = StructSerializer<'a>;
+    type SerializeTupleVariant = StructSerializer<'a>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = StructSerializer<'a>;
+    type SerializeStructVariant = StructSerializer<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SerdeError> {
+        match self.type_ {
+            Type::Boolean => Ok(Value::Boolean(v)),
+            _ => Err(self.mismatch("bool")),
+        }
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, SerdeError> {
+        self.signed(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, SerdeError> {
+        self.signed(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, SerdeError> {
+        self.signed(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, SerdeError> {
+        self.signed(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, SerdeError> {
+        self.unsigned(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, SerdeError> {
+        self.unsigned(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, SerdeError> {
+        self.unsigned(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, SerdeError> {
+        self.unsigned(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, SerdeError> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, SerdeError> {
+        match self.type_ {
+            Type::Double => Ok(Value::Double(v)),
+            _ => Err(self.mismatch("float")),
+        }
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, SerdeError> {
+        self.serialize_str(&v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, SerdeError> {
+        match self.type_ {
+            Type::String => Ok(Value::String(v.to_string())),
+            _ => Err(self.mismatch("string")),
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, SerdeError> {
+        match self.type_ {
+            Type::Array(type_) if **type_ == Type::Byte => {
+                let array = v.iter().map(|b| Value::Byte(*b)).collect();
+                Ok(Value::Array(Array::new(array, Type::Byte)?))
+            }
+            _ => Err(self.mismatch("bytes")),
+        }
+    }
+
+    fn serialize_none(self) -> Result<Value, SerdeError> {
+        match self.type_ {
+            Type::Array(type_) => Ok(Value::Array(Array::new(Vec::new(), *type_.clone())?)),
+            _ => Err(self.mismatch("option")),
+        }
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, SerdeError> {
+        match self.type_ {
+            Type::Array(type_) => {
+                let value = value.serialize(self.child(type_))?;
+                Ok(Value::Array(Array::new(vec![value], *type_.clone())?))
+            }
+            _ => Err(self.mismatch("option")),
+        }
+    }
+
+    fn serialize_unit(self) -> Result<Value, SerdeError> {
+        Err(SerdeError::Unsupported("unit"))
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, SerdeError> {
+        Err(SerdeError::Unsupported("unit"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Value, SerdeError> {
+        match self.variant(name, index, variant)? {
+            None => {
+                // A unit variant has no content, so the variant contains an empty array.
+                let content = Value::Array(Array::new(Vec::new(), Type::Variant)?);
+                Ok(enum_value(variant, content))
+            }
+            Some(_) => Err(self.mismatch("unit variant")),
+        }
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        match self.variant(name, index, variant)? {
+            Some(type_) => {
+                let content = value.serialize(self.child(type_))?;
+                Ok(enum_value(variant, content))
+            }
+            None => Err(self.mismatch("newtype variant")),
+        }
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer<'a>, SerdeError> {
+        match self.type_ {
+            Type::Array(type_) => Ok(SeqSerializer {
+                type_,
+                enums: self.enums,
+                values: Vec::with_capacity(len.unwrap_or(0)),
+            }),
+            _ => Err(self.mismatch("sequence")),
+        }
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<StructSerializer<'a>, SerdeError> {
+        self.fields(Some(self.type_), len, None)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'a>, SerdeError> {
+        self.fields(Some(self.type_), len, None)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'a>, SerdeError> {
+        let type_ = self.variant(name, index, variant)?;
+        self.fields(type_, len, Some(variant))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer<'a>, SerdeError> {
+        match self.type_ {
+            Type::Array(type_) => match type_.as_ref() {
+                Type::DictEntry(types) => Ok(MapSerializer {
+                    type_,
+                    key_type: &types.0,
+                    value_type: &types.1,
+                    enums: self.enums,
+                    key: None,
+                    entries: Vec::with_capacity(len.unwrap_or(0)),
+                }),
+                _ => Err(self.mismatch("map")),
+            },
+            _ => Err(self.mismatch("map")),
+        }
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'a>, SerdeError> {
+        self.fields(Some(self.type_), len, None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructSerializer<'a>, SerdeError> {
+        let type_ = self.variant(name, index, variant)?;
+        self.fields(type_, len, Some(variant))
+    }
+}
+
+pub(super) struct SeqSerializer<'a> {
+    type_: &'a Type,
+    enums: &'a Enums,
+    values: Vec<Value>,
+}
+
+impl<'a> SerializeSeq for SeqSerializer<'a> {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let value = value.serialize(Serializer::new(self.type_, self.enums))?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(Value::Array(Array::new(self.values, self.type_.clone())?))
+    }
+}
+
+pub(super) struct StructSerializer<'a> {
+    types: &'a [Type],
+    enums: &'a Enums,
+    fields: Vec<Value>,
+    /// The name of the variant, if the struct is the content of an enum.
+    variant: Option<&'static str>,
+}
+
+impl<'a> StructSerializer<'a> {
+    fn field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        match self.types.get(self.fields.len()) {
+            Some(type_) => {
+                let value = value.serialize(Serializer::new(type_, self.enums))?;
+                self.fields.push(value);
+                Ok(())
+            }
+            None => Err(SerdeError::FieldCount(
+                self.types.len(),
+                self.fields.len() + 1,
+            )),
+        }
+    }
+
+    fn finish(self) -> Result<Value, SerdeError> {
+        if self.types.len() != self.fields.len() {
+            return Err(SerdeError::FieldCount(self.types.len(), self.fields.len()));
+        }
+        let value = Value::Struct(Struct::try_from(self.fields)?);
+        match self.variant {
+            Some(variant) => Ok(enum_value(variant, value)),
+            None => Ok(value),
+        }
+    }
+}
+
+impl<'a> SerializeTuple for StructSerializer<'a> {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeTupleStruct for StructSerializer<'a> {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeTupleVariant for StructSerializer<'a> {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeStruct for StructSerializer<'a> {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+impl<'a> SerializeStructVariant for StructSerializer<'a> {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.field(value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        self.finish()
+    }
+}
+
+pub(super) struct MapSerializer<'a> {
+    type_: &'a Type,
+    key_type: &'a Type,
+    value_type: &'a Type,
+    enums: &'a Enums,
+    key: Option<Value>,
+    entries: Vec<Value>,
+}
+
+impl<'a> SerializeMap for MapSerializer<'a> {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), SerdeError> {
+        let key = key.serialize(Serializer::new(self.key_type, self.enums))?;
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or(SerdeError::Unsupported("map value without key"))?;
+        let value = value.serialize(Serializer::new(self.value_type, self.enums))?;
+        self.entries.push(Value::DictEntry(Box::new((key, value))));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(Value::Array(Array::new(self.entries, self.type_.clone())?))
+    }
+}
@@ -0,0 +1,511 @@
+use super::SerdeError;
+use dbus_message_parser::value::Type;
+use serde::de::{
+    value::StrDeserializer, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess,
+    IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use std::collections::HashMap;
+
+/// The maximum depth of the nested types, which will be traced. This prevents an endless
+/// recursion for recursive types.
+const MAXIMUM_DEPTH: u8 = 64;
+
+/// An enum is represented as a struct, which contains the name of the variant and the content
+/// of the variant.
+pub(super) fn enum_type() -> Type {
+    Type::Struct(vec![Type::String, Type::Variant])
+}
+
+/// The content types of the variants of all traced enums.
+///
+/// The outer `Option` is `None`, if the variant was not traced yet and the inner `Option` is
+/// `None`, if the variant is a unit variant.
+#[derive(Default)]
+pub(super) struct Enums(HashMap<&'static str, Vec<Option<Option<Type>>>>);
+
+impl Enums {
+    /// Get the content type of a variant. Returns `None`, if the variant is a unit variant.
+    pub(super) fn content(
+        &self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Option<&Type>, SerdeError> {
+        match self
+            .0
+            .get(name)
+            .and_then(|variants| variants.get(index as usize))
+        {
+            Some(Some(content)) => Ok(content.as_ref()),
+            _ => Err(SerdeError::UnknownVariant(name, variant)),
+        }
+    }
+}
+
+/// The result of the tracing of a type.
+pub(super) struct Traced {
+    /// The types of the arguments of the body.
+    pub(super) body: Vec<Type>,
+    /// `true` if the type is a tuple (or unit), so every field is an argument of the body.
+    pub(super) args: bool,
+    pub(super) enums: Enums,
+}
+
+#[derive(Default)]
+struct State {
+    enums: Enums,
+    /// `true` if a variant was traced, which was not traced before.
+    changed: bool,
+    /// `true` if the top level type is a tuple or unit.
+    args: bool,
+}
+
+/// A [`Deserializer`], which does not deserialize any data but records the type, which is
+/// requested by the [`Deserialize`] implementation.
+///
+/// [`Deserializer`]: serde::de::Deserializer
+/// [`Deserialize`]: serde::de::Deserialize
+struct Tracer<'a> {
+    state: &'a mut State,
+    type_: &'a mut Option<Type>,
+    depth: u8,
+    /// `true` if it is the top level type of the body.
+    top: bool,
+}
+
+fn take(type_: Option<Type>) -> Result<Type, SerdeError> {
+    type_.ok_or(SerdeError::Unsupported("type without a value"))
+}
+
+fn is_basic(type_: &Type) -> bool {
+    !matches!(
+        type_,
+        Type::Variant | Type::Array(_) | Type::Struct(_) | Type::DictEntry(_)
+    )
+}
+
+/// Trace the type `T`. Every variant of every enum is traced, so the type is traced multiple
+/// times until every variant is known.
+pub(super) fn trace<T: DeserializeOwned>(body: bool) -> Result<Traced, SerdeError> {
+    let mut state = State::default();
+    loop {
+        state.changed = false;
+        state.args = false;
+        let mut type_ = None;
+        let tracer = Tracer {
+            state: &mut state,
+            type_: &mut type_,
+            depth: 0,
+            top: body,
+        };
+        T::deserialize(tracer)?;
+        if state.changed {
+            continue;
+        }
+        let body = if state.args {
+            match type_ {
+                Some(Type::Struct(fields)) => fields,
+                _ => Vec::new(),
+            }
+        } else {
+            vec![take(type_)?]
+        };
+        // Check if the signature is valid (depth and length).
+        Type::from_signature_to_string(&body)?;
+        return Ok(Traced {
+            body,
+            args: state.args,
+            enums: state.enums,
+        });
+    }
+}
+
+impl<'a> Tracer<'a> {
+    fn check_depth(&self) -> Result<(), SerdeError> {
+        if self.depth < MAXIMUM_DEPTH {
+            Ok(())
+        } else {
+            Err(SerdeError::Depth)
+        }
+    }
+
+    fn fields<'de, V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        self.check_depth()?;
+        // Only the arguments of the body can be empty.
+        let args = self.top && self.state.args;
+        let mut seq = TraceSeq {
+            state: self.state,
+            depth: self.depth + 1,
+            remaining: len,
+            types: Vec::with_capacity(len),
+        };
+        let value = visitor.visit_seq(&mut seq)?;
+        if seq.types.is_empty() && !args {
+            return Err(SerdeError::Unsupported("empty struct"));
+        }
+        *self.type_ = Some(Type::Struct(seq.types));
+        Ok(value)
+    }
+
+    fn unit<'de, V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        if self.top {
+            self.state.args = true;
+            *self.type_ = Some(Type::Struct(Vec::new()));
+            visitor.visit_unit()
+        } else {
+            Err(SerdeError::Unsupported("unit"))
+        }
+    }
+}
+
+macro_rules! basic {
+    ($method:ident, $visit:ident, $value:expr, $type:expr) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+            *self.type_ = Some($type);
+            visitor.$visit($value)
+        }
+    };
+}
+
+impl<'de, 'a> Deserializer<'de> for Tracer<'a> {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, SerdeError> {
+        Err(SerdeError::Unsupported("self-describing type"))
+    }
+
+    basic!(deserialize_bool, visit_bool, false, Type::Boolean);
+    basic!(deserialize_i8, visit_i8, 0, Type::Int16);
+    basic!(deserialize_i16, visit_i16, 0, Type::Int16);
+    basic!(deserialize_i32, visit_i32, 0, Type::Int32);
+    basic!(deserialize_i64, visit_i64, 0, Type::Int64);
+    basic!(deserialize_u8, visit_u8, 0, Type::Byte);
+    basic!(deserialize_u16, visit_u16, 0, Type::Uint16);
+    basic!(deserialize_u32, visit_u32, 0, Type::Uint32);
+    basic!(deserialize_u64, visit_u64, 0, Type::Uint64);
+    basic!(deserialize_f32, visit_f32, 0.0, Type::Double);
+    basic!(deserialize_f64, visit_f64, 0.0, Type::Double);
+    basic!(deserialize_char, visit_char, ' ', Type::String);
+    basic!(deserialize_str, visit_str, "", Type::String);
+    basic!(
+        deserialize_string,
+        visit_string,
+        String::new(),
+        Type::String
+    );
+    basic!(deserialize_identifier, visit_str, "", Type::String);
+    basic!(
+        deserialize_bytes,
+        visit_bytes,
+        &[],
+        Type::Array(Box::new(Type::Byte))
+    );
+    basic!(
+        deserialize_byte_buf,
+        visit_byte_buf,
+        Vec::new(),
+        Type::Array(Box::new(Type::Byte))
+    );
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.check_depth()?;
+        let mut type_ = None;
+        let tracer = Tracer {
+            state: self.state,
+            type_: &mut type_,
+            depth: self.depth + 1,
+            top: false,
+        };
+        let value = visitor.visit_some(tracer)?;
+        *self.type_ = Some(Type::Array(Box::new(take(type_)?)));
+        Ok(value)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.unit(visitor)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.check_depth()?;
+        let mut seq = TraceSeq {
+            state: self.state,
+            depth: self.depth + 1,
+            remaining: 1,
+            types: Vec::with_capacity(1),
+        };
+        let value = visitor.visit_seq(&mut seq)?;
+        let type_ = take(seq.types.pop())?;
+        *self.type_ = Some(Type::Array(Box::new(type_)));
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        if self.top {
+            self.state.args = true;
+        }
+        self.fields(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.check_depth()?;
+        let mut map = TraceMap {
+            state: self.state,
+            depth: self.depth + 1,
+            done: false,
+            key: None,
+            value: None,
+        };
+        let value = visitor.visit_map(&mut map)?;
+        let key = take(map.key)?;
+        if !is_basic(&key) {
+            return Err(SerdeError::MapKey(key));
+        }
+        let dict_entry = Type::DictEntry(Box::new((key, take(map.value)?)));
+        *self.type_ = Some(Type::Array(Box::new(dict_entry)));
+        Ok(value)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.fields(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.check_depth()?;
+        if variants.is_empty() {
+            return Err(SerdeError::Unsupported("enum without variants"));
+        }
+        let traced = self
+            .state
+            .enums
+            .0
+            .entry(name)
+            .or_insert_with(|| vec![None; variants.len()]);
+        // Trace the first variant, which was not traced yet.
+        let index = traced.iter().position(Option::is_none).unwrap_or(0);
+        let access = TraceEnum {
+            state: self.state,
+            depth: self.depth + 1,
+            name,
+            variant: variants[index],
+            index,
+        };
+        let value = visitor.visit_enum(access)?;
+        *self.type_ = Some(enum_type());
+        Ok(value)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, SerdeError> {
+        Err(SerdeError::Unsupported("ignored value"))
+    }
+}
+
+struct TraceSeq<'a> {
+    state: &'a mut State,
+    depth: u8,
+    remaining: usize,
+    types: Vec<Type>,
+}
+
+impl<'de, 'a> SeqAccess<'de> for TraceSeq<'a> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let mut type_ = None;
+        let tracer = Tracer {
+            state: self.state,
+            type_: &mut type_,
+            depth: self.depth,
+            top: false,
+        };
+        let value = seed.deserialize(tracer)?;
+        self.types.push(take(type_)?);
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+struct TraceMap<'a> {
+    state: &'a mut State,
+    depth: u8,
+    done: bool,
+    key: Option<Type>,
+    value: Option<Type>,
+}
+
+impl<'de, 'a> MapAccess<'de> for TraceMap<'a> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        let tracer = Tracer {
+            state: self.state,
+            type_: &mut self.key,
+            depth: self.depth,
+            top: false,
+        };
+        seed.deserialize(tracer).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let tracer = Tracer {
+            state: self.state,
+            type_: &mut self.value,
+            depth: self.depth,
+            top: false,
+        };
+        seed.deserialize(tracer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(if self.done { 0 } else { 1 })
+    }
+}
+
+struct TraceEnum<'a> {
+    state: &'a mut State,
+    depth: u8,
+    name: &'static str,
+    variant: &'static str,
+    index: usize,
+}
+
+impl<'a> TraceEnum<'a> {
+    fn record(self, content: Option<Type>) {
+        if let Some(variants) = self.state.enums.0.get_mut(self.name) {
+            let traced = &mut variants[self.index];
+            if traced.is_none() {
+                *traced = Some(content);
+                self.state.changed = true;
+            }
+        }
+    }
+
+    fn fields<'de, V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, SerdeError> {
+        let mut seq = TraceSeq {
+            state: &mut *self.state,
+            depth: self.depth,
+            remaining: len,
+            types: Vec::with_capacity(len),
+        };
+        let value = visitor.visit_seq(&mut seq)?;
+        if seq.types.is_empty() {
+            return Err(SerdeError::Unsupported("empty struct"));
+        }
+        let content = Type::Struct(seq.types);
+        self.record(Some(content));
+        Ok(value)
+    }
+}
+
+impl<'de, 'a> EnumAccess<'de> for TraceEnum<'a> {
+    type Error = SerdeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self), SerdeError> {
+        let variant: StrDeserializer<SerdeError> = self.variant.into_deserializer();
+        let value = seed.deserialize(variant)?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a> VariantAccess<'de> for TraceEnum<'a> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        self.record(None);
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        let mut type_ = None;
+        let tracer = Tracer {
+            state: &mut *self.state,
+            type_: &mut type_,
+            depth: self.depth,
+            top: false,
+        };
+        let value = seed.deserialize(tracer)?;
+        self.record(Some(take(type_)?));
+        Ok(value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.fields(len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.fields(fields.len(), visitor)
+    }
+}
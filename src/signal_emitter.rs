@@ -0,0 +1,135 @@
+use crate::{
+    marshal::{MarshalError, ToArgs},
+    DBus, DBusError, DBusResult,
+};
+use dbus_message_parser::{
+    message::{Message, MessageFlags, MessageHeader, MessageHeaderFields, MessageType},
+    value::{Bus, Interface, Member, ObjectPath, Type, Value},
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// An emitter of the signals of an interface of a bound object.
+///
+/// The emitter is created by [`Binding::signal_emitter`] or [`ObjectServer::signal_emitter`] and
+/// sets the [`ObjectPath`] of the binding and the [`Interface`] of the signals automatically.
+///
+/// If the emitter is created by the [`ObjectServer`] then emitting a signal returns an error, if
+/// the signal is not declared by the [`MethodTable`] of the interface (see
+/// [`MethodTable::add_signal`]) or the arguments have a different signature than the declared
+/// signal.
+///
+/// [`Binding::signal_emitter`]: crate::Binding::signal_emitter
+/// [`ObjectServer::signal_emitter`]: crate::ObjectServer::signal_emitter
+/// [`ObjectServer`]: crate::ObjectServer
+/// [`MethodTable`]: crate::MethodTable
+/// [`MethodTable::add_signal`]: crate::MethodTable::add_signal
+/// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+/// [`Interface`]: dbus_message_parser::value::Interface
+#[derive(Clone)]
+pub struct SignalEmitter {
+    dbus: DBus,
+    object_path: ObjectPath,
+    interface: Interface,
+    signals: Option<Arc<HashMap<String, Vec<Type>>>>,
+}
+
+impl SignalEmitter {
+    pub(crate) fn new(dbus: DBus, object_path: ObjectPath, interface: Interface) -> SignalEmitter {
+        SignalEmitter {
+            dbus,
+            object_path,
+            interface,
+            signals: None,
+        }
+    }
+
+    /// Check the emitted signals against the declared signals and their signatures.
+    pub(crate) fn with_signals(mut self, signals: HashMap<String, Vec<Type>>) -> SignalEmitter {
+        self.signals = Some(Arc::new(signals));
+        self
+    }
+
+    /// Get the [`ObjectPath`] of the signals.
+    ///
+    /// [`ObjectPath`]: dbus_message_parser::value::ObjectPath
+    pub fn get_object_path(&self) -> &ObjectPath {
+        &self.object_path
+    }
+
+    /// Get the [`Interface`] of the signals.
+    ///
+    /// [`Interface`]: dbus_message_parser::value::Interface
+    pub fn get_interface(&self) -> &Interface {
+        &self.interface
+    }
+
+    /// Emit a broadcast signal with the given arguments.
+    pub async fn emit<A>(&self, member: Member, args: A) -> DBusResult<()>
+    where
+        A: ToArgs,
+    {
+        self.send(None, member, args).await
+    }
+
+    /// Emit a unicast signal with the given arguments to the destination.
+    pub async fn emit_to<A>(&self, destination: Bus, member: Member, args: A) -> DBusResult<()>
+    where
+        A: ToArgs,
+    {
+        self.send(Some(destination), member, args).await
+    }
+
+    async fn send<A>(&self, destination: Option<Bus>, member: Member, args: A) -> DBusResult<()>
+    where
+        A: ToArgs,
+    {
+        let body = args.to_args()?;
+        self.check(&member, &body)?;
+        let fields = MessageHeaderFields {
+            path: Some(self.object_path.clone()),
+            interface: Some(self.interface.clone()),
+            member: Some(member),
+            destination,
+            ..Default::default()
+        };
+        // The unwrap function call will never panic because all required fields are set.
+        let header = MessageHeader::new(
+            true,
+            MessageType::Signal,
+            MessageFlags::NO_REPLY_EXPECTED,
+            1,
+            0,
+            fields,
+        )
+        .unwrap();
+        self.dbus.send(Message::new(header, body)).await
+    }
+
+    /// Check if the signal is declared with the same signature.
+    fn check(&self, member: &Member, body: &[Value]) -> DBusResult<()> {
+        let signals = if let Some(signals) = &self.signals {
+            signals
+        } else {
+            return Ok(());
+        };
+        let signature = signals
+            .get(member.as_ref())
+            .ok_or_else(|| DBusError::SignalNotDeclared(self.interface.clone(), member.clone()))?;
+        let got = body
+            .iter()
+            .map(Value::get_type)
+            .collect::<Result<Vec<Type>, _>>()
+            .map_err(MarshalError::from)?;
+        if signature == &got {
+            Ok(())
+        } else {
+            let expected = Type::from_signature_to_string(signature).map_err(MarshalError::from)?;
+            let got = Type::from_signature_to_string(&got).map_err(MarshalError::from)?;
+            Err(DBusError::SignalSignature {
+                member: member.clone(),
+                expected,
+                got,
+            })
+        }
+    }
+}
@@ -1,25 +1,33 @@
-use super::{handshake::Handshake, Stream, StreamError};
-use crate::Uuid;
+use super::{credentials::get_peer_uid, handshake::Handshake, Stream, StreamError, Transport};
+use crate::{
+    runtime::{
+        command_output, connect_tcp, connect_unix, lookup_host, read_file, unix_stream_from_std,
+        TcpStream, UnixStream,
+    },
+    DBusBuilder, Uuid,
+};
 use async_recursion::async_recursion;
 use dbus_server_address_parser::{Address, Family, NonceTcp, Tcp, Unix, UnixType, Unixexec};
 use std::{
+    convert::TryInto,
     net::{IpAddr, SocketAddr},
+    os::unix::net::UnixStream as StdUnixStream,
     str::from_utf8,
 };
-use tokio::{
-    fs::File,
-    io::AsyncReadExt,
-    net::{lookup_host, TcpStream, UnixStream},
-    process::Command,
-};
 
 impl Stream {
-    async fn unix(unix: &Unix) -> Result<Stream, StreamError> {
+    async fn unix(unix: &Unix, builder: &DBusBuilder) -> Result<Stream, StreamError> {
         match &unix.r#type {
             UnixType::Path(path) => {
                 debug!("Connect to {}", path);
-                let mut connection = UnixStream::connect(path).await?;
-                Handshake::handshake(&mut connection, true, &None).await?;
+                let mut connection = connect_unix(path).await?;
+                Handshake::handshake(
+                    &mut connection,
+                    &builder.auth_mechanisms,
+                    builder.negotiate_unix_fd,
+                    &None,
+                )
+                .await?;
                 Ok(Stream::Unix(connection))
             }
             UnixType::Abstract(_) => Err(StreamError::UnixAbstractNotSupported),
@@ -28,15 +36,12 @@ impl Stream {
     }
 
     #[async_recursion]
-    async fn unixexec(unixexec: &Unixexec) -> Result<Stream, StreamError> {
-        // TODO: missing argv0 support by the Tokio API
-        let output = Command::new(&unixexec.path)
-            .args(&unixexec.argv)
-            .output()
-            .await?;
-        match from_utf8(&output.stdout) {
+    async fn unixexec(unixexec: &Unixexec, builder: &DBusBuilder) -> Result<Stream, StreamError> {
+        // TODO: missing argv0 support by the runtime APIs
+        let stdout = command_output(&unixexec.path, &unixexec.argv).await?;
+        match from_utf8(&stdout) {
             Ok(addressses) => {
-                let (_, stream) = Stream::new(addressses).await?;
+                let (_, stream) = Stream::new(addressses, builder).await?;
                 Ok(stream)
             }
             Err(e) => Err(StreamError::UnixexecStdout(e)),
@@ -58,14 +63,15 @@ impl Stream {
         socket_addr: &SocketAddr,
         family: &Option<Family>,
         nonce: &Option<Uuid>,
+        builder: &DBusBuilder,
     ) -> Result<TcpStream, StreamError> {
         if !Stream::tcp_family_match(socket_addr, family) {
             return Err(StreamError::TcpResolveIpAddress);
         }
 
         debug!("Connect to {}", socket_addr);
-        let mut tcp_stream = TcpStream::connect(socket_addr).await?;
-        Handshake::handshake(&mut tcp_stream, false, nonce).await?;
+        let mut tcp_stream = connect_tcp(socket_addr).await?;
+        Handshake::handshake(&mut tcp_stream, &builder.auth_mechanisms, false, nonce).await?;
         Ok(tcp_stream)
     }
 
@@ -74,10 +80,11 @@ impl Stream {
         port: u16,
         family: &Option<Family>,
         nonce: &Option<Uuid>,
+        builder: &DBusBuilder,
     ) -> Result<Stream, StreamError> {
         if let Ok(ip_addr) = host.parse::<IpAddr>() {
             let socket_addr = SocketAddr::new(ip_addr, port);
-            match Stream::tcp_connect_address(&socket_addr, family, nonce).await {
+            match Stream::tcp_connect_address(&socket_addr, family, nonce, builder).await {
                 Ok(tcp_stream) => Ok(Stream::Tcp(tcp_stream)),
                 Err(e) => {
                     error!("Could not connect to {}: {}", socket_addr, e);
@@ -85,9 +92,8 @@ impl Stream {
                 }
             }
         } else {
-            let host_port = format!("{}:{}", host, port);
-            for socket_addr in lookup_host(host_port).await? {
-                match Stream::tcp_connect_address(&socket_addr, family, nonce).await {
+            for socket_addr in lookup_host(host, port).await? {
+                match Stream::tcp_connect_address(&socket_addr, family, nonce, builder).await {
                     Ok(tcp_stream) => return Ok(Stream::Tcp(tcp_stream)),
                     Err(e) => error!("Could not connect to {}: {}", socket_addr, e),
                 }
@@ -97,35 +103,26 @@ impl Stream {
         }
     }
 
-    async fn tcp(tcp: &Tcp) -> Result<Stream, StreamError> {
+    async fn tcp(tcp: &Tcp, builder: &DBusBuilder) -> Result<Stream, StreamError> {
         let host = tcp.host.as_ref().unwrap();
         let port = tcp.port.unwrap();
         let family = &tcp.family;
 
-        Stream::tcp_connect(host, port, family, &None).await
+        Stream::tcp_connect(host, port, family, &None, builder).await
     }
 
     async fn nonce_tcp_read_nonce(nonce_tcp: &NonceTcp) -> Result<Uuid, StreamError> {
-        let mut nonce: Uuid = [0; 16];
-
         let noncefile = nonce_tcp.noncefile.as_ref().unwrap();
-        let mut noncefile = File::open(noncefile).await?;
-
-        let read = noncefile.read_exact(&mut nonce).await?;
-        if read == nonce.len() {
-            // Check if there is more bytes
-            let read = noncefile.read(&mut nonce).await?;
-            if read == 0 {
-                Ok(nonce)
-            } else {
-                Err(StreamError::NonceTcpFileTooLarge)
-            }
-        } else {
-            Err(StreamError::NonceTcpFileTooSmall)
+        let nonce = read_file(noncefile).await?;
+
+        match nonce.as_slice().try_into() {
+            Ok(nonce) => Ok(nonce),
+            Err(_) if nonce.len() < 16 => Err(StreamError::NonceTcpFileTooSmall),
+            Err(_) => Err(StreamError::NonceTcpFileTooLarge),
         }
     }
 
-    async fn nonce_tcp(nonce_tcp: &NonceTcp) -> Result<Stream, StreamError> {
+    async fn nonce_tcp(nonce_tcp: &NonceTcp, builder: &DBusBuilder) -> Result<Stream, StreamError> {
         let host = nonce_tcp.host.as_ref().unwrap();
         let port = nonce_tcp.port.unwrap();
         let family = &nonce_tcp.family;
@@ -133,29 +130,81 @@ impl Stream {
         let nonce = Stream::nonce_tcp_read_nonce(nonce_tcp).await?;
         let nonce = Some(nonce);
 
-        Stream::tcp_connect(host, port, family, &nonce).await
+        Stream::tcp_connect(host, port, family, &nonce, builder).await
     }
 
-    async fn connect(address: &Address) -> Result<Stream, StreamError> {
+    async fn connect(address: &Address, builder: &DBusBuilder) -> Result<Stream, StreamError> {
         if !address.is_connectable() {
             return Err(StreamError::AddressNotConnectable);
         }
 
         match address {
-            Address::Unix(unix) => Stream::unix(unix).await,
-            Address::Unixexec(unixexec) => Stream::unixexec(unixexec).await,
-            Address::Tcp(tcp) => Stream::tcp(tcp).await,
-            Address::NonceTcp(nonce_tcp) => Stream::nonce_tcp(nonce_tcp).await,
+            Address::Unix(unix) => Stream::unix(unix, builder).await,
+            Address::Unixexec(unixexec) => Stream::unixexec(unixexec, builder).await,
+            Address::Tcp(tcp) => Stream::tcp(tcp, builder).await,
+            Address::NonceTcp(nonce_tcp) => Stream::nonce_tcp(nonce_tcp, builder).await,
             Address::Autolaunch(_) => Err(StreamError::AutolaunchNotSupported),
             Address::Launchd(_) => Err(StreamError::LaunchdNotSupported),
             x => panic!("This should not happen: {}", x),
         }
     }
 
-    pub async fn new(addressses: &str) -> Result<(Address, Stream), StreamError> {
+    /// Run the handshake on the given duplex stream.
+    pub async fn from_transport<T>(
+        mut transport: T,
+        builder: &DBusBuilder,
+    ) -> Result<Stream, StreamError>
+    where
+        T: Transport + 'static,
+    {
+        // Unix file descriptors can only be passed through Unix Domain Stream sockets.
+        Handshake::handshake(&mut transport, &builder.auth_mechanisms, false, &None).await?;
+        Ok(Stream::Custom(Box::new(transport)))
+    }
+
+    /// Run the handshake on the given connected Unix Domain Stream socket, for example a socket,
+    /// which is inherited from the service manager.
+    pub async fn from_unix_stream(
+        unix_stream: StdUnixStream,
+        builder: &DBusBuilder,
+    ) -> Result<Stream, StreamError> {
+        let mut connection = unix_stream_from_std(unix_stream)?;
+        Handshake::handshake(
+            &mut connection,
+            &builder.auth_mechanisms,
+            builder.negotiate_unix_fd,
+            &None,
+        )
+        .await?;
+        Ok(Stream::Unix(connection))
+    }
+
+    /// Answer the handshake of a client, which connected to a listening Unix Domain Stream
+    /// socket. The `EXTERNAL` mechanism is checked against the peer credentials of the socket.
+    pub(crate) async fn accept_unix_stream(
+        unix_stream: UnixStream,
+        guid: &str,
+        builder: &DBusBuilder,
+    ) -> Result<Stream, StreamError> {
+        let peer_uid = get_peer_uid(&unix_stream)?;
+        let (stream, _) = Handshake::accept(
+            unix_stream,
+            guid,
+            &builder.auth_mechanisms,
+            false,
+            Some(peer_uid),
+        )
+        .await?;
+        Ok(Stream::Custom(Box::new(stream)))
+    }
+
+    pub async fn new(
+        addressses: &str,
+        builder: &DBusBuilder,
+    ) -> Result<(Address, Stream), StreamError> {
         let addressses = Address::decode(addressses)?;
         for address in addressses.iter() {
-            match Stream::connect(address).await {
+            match Stream::connect(address, builder).await {
                 Ok(connect) => return Ok((address.clone(), connect)),
                 Err(e) => {
                     error!("Could not connect to {}: {}", address, e);
@@ -0,0 +1,43 @@
+use std::{
+    io::{Error as IoError, Result as IoResult},
+    mem::size_of,
+    os::unix::io::AsRawFd,
+};
+
+/// Get the UID of the process on the other side of the Unix Domain Stream socket.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn get_peer_uid<T: AsRawFd>(socket: &T) -> IoResult<u32> {
+    let mut ucred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = size_of::<libc::ucred>() as libc::socklen_t;
+    let result = unsafe {
+        libc::getsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut ucred as *mut libc::ucred as *mut libc::c_void,
+            &mut len,
+        )
+    };
+    if result == 0 && len as usize == size_of::<libc::ucred>() {
+        Ok(ucred.uid)
+    } else {
+        Err(IoError::last_os_error())
+    }
+}
+
+/// Get the UID of the process on the other side of the Unix Domain Stream socket.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn get_peer_uid<T: AsRawFd>(socket: &T) -> IoResult<u32> {
+    let mut uid = 0;
+    let mut gid = 0;
+    let result = unsafe { libc::getpeereid(socket.as_raw_fd(), &mut uid, &mut gid) };
+    if result == 0 {
+        Ok(uid)
+    } else {
+        Err(IoError::last_os_error())
+    }
+}
@@ -1,21 +1,58 @@
 use super::{
+    frame::ProtocolError,
     handshake::HandshakeError,
     message::{message_sink, message_stream},
 };
-use dbus_message_parser::message::Message;
+use crate::{
+    runtime::{spawn, JoinHandle, TcpStream, UnixStream},
+    DBusBuilder,
+};
+use dbus_message_parser::message::{Message, MessageFlags};
 use dbus_server_address_parser::DecodeError;
-use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
-use std::{io::Error as IoError, str::Utf8Error};
-use thiserror::Error;
-use tokio::{
-    net::{TcpStream, UnixStream},
-    spawn,
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    io::Error as IoError,
+    str::Utf8Error,
+    sync::{atomic::AtomicUsize, Arc},
 };
+use thiserror::Error;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+
+/// A duplex stream, which can be used to connect to the DBus daemon (see
+/// [`DBusBuilder::build_with_stream`]).
+///
+/// [`DBusBuilder::build_with_stream`]: crate::DBusBuilder::build_with_stream
+pub trait Transport: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T> Transport for T where T: AsyncRead + AsyncWrite + Send + Unpin {}
 
-#[derive(Debug)]
 pub enum Stream {
     Unix(UnixStream),
     Tcp(TcpStream),
+    Custom(Box<dyn Transport>),
+}
+
+/// The channels and the tasks of a started [`Stream`].
+pub struct StreamHandle {
+    /// The channel to the sink task. The flags replace the flags of the message, if they are given.
+    pub message_sink: Sender<(Message, Option<MessageFlags>)>,
+    /// The channel from the stream task. The flags are read from the fixed part of the header.
+    pub message_stream: Receiver<Result<(Message, MessageFlags), ProtocolError>>,
+    /// The task, which writes the messages to the socket.
+    pub sink_task: JoinHandle<()>,
+    /// The task, which reads the messages from the socket.
+    pub stream_task: JoinHandle<()>,
+}
+
+impl Debug for Stream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Stream::Unix(unix_stream) => f.debug_tuple("Unix").field(unix_stream).finish(),
+            Stream::Tcp(tcp_stream) => f.debug_tuple("Tcp").field(tcp_stream).finish(),
+            Stream::Custom(_) => f.debug_tuple("Custom").finish(),
+        }
+    }
 }
 
 #[derive(Debug, Error)]
@@ -47,28 +84,60 @@ pub enum StreamError {
 }
 
 impl Stream {
-    pub fn start(self) -> (UnboundedSender<Message>, UnboundedReceiver<Message>) {
+    /// Spawn the stream and the sink task. The handles of the tasks are returned, so it is
+    /// possible to wait until all messages are written and to stop the stream task.
+    pub fn start(self, builder: &DBusBuilder, dropped_signals: Arc<AtomicUsize>) -> StreamHandle {
         // Create all necessary channels.
-        let (message_sink_sender, message_sink_receiver) = unbounded::<Message>();
-        let (message_stream_sender, message_stream_receiver) = unbounded::<Message>();
+        let (message_sink_sender, message_sink_receiver) =
+            channel::<(Message, Option<MessageFlags>)>(builder.send_queue_size);
+        let (message_stream_sender, message_stream_receiver) = channel(builder.receive_queue_size);
+        let receive_policy = builder.receive_policy;
+        let maximum_message_size = builder.maximum_message_size;
 
-        match self {
+        let (sink_task, stream_task) = match self {
             Stream::Unix(unix_stream) => {
-                let (stream, sink) = unix_stream.into_split();
-                // Spawn the sink task.
-                spawn(message_stream(stream, message_stream_sender));
-                // Spawn the stream task.
-                spawn(message_sink(message_sink_receiver, sink));
+                let (stream, sink) = unix_stream.split();
+                let stream_task = spawn(message_stream(
+                    stream,
+                    message_stream_sender,
+                    receive_policy,
+                    maximum_message_size,
+                    dropped_signals,
+                ));
+                let sink_task = spawn(message_sink(message_sink_receiver, sink));
+                (sink_task, stream_task)
             }
             Stream::Tcp(tcp_stream) => {
-                let (stream, sink) = tcp_stream.into_split();
-                // Spawn the sink task.
-                spawn(message_stream(stream, message_stream_sender));
-                // Spawn the stream task.
-                spawn(message_sink(message_sink_receiver, sink));
+                let (stream, sink) = tcp_stream.split();
+                let stream_task = spawn(message_stream(
+                    stream,
+                    message_stream_sender,
+                    receive_policy,
+                    maximum_message_size,
+                    dropped_signals,
+                ));
+                let sink_task = spawn(message_sink(message_sink_receiver, sink));
+                (sink_task, stream_task)
             }
-        }
+            Stream::Custom(transport) => {
+                let (stream, sink) = transport.split();
+                let stream_task = spawn(message_stream(
+                    stream,
+                    message_stream_sender,
+                    receive_policy,
+                    maximum_message_size,
+                    dropped_signals,
+                ));
+                let sink_task = spawn(message_sink(message_sink_receiver, sink));
+                (sink_task, stream_task)
+            }
+        };
 
-        (message_sink_sender, message_stream_receiver)
+        StreamHandle {
+            message_sink: message_sink_sender,
+            message_stream: message_stream_receiver,
+            sink_task,
+            stream_task,
+        }
     }
 }
@@ -0,0 +1,382 @@
+use bytes::{Buf, BytesMut};
+use dbus_message_parser::{
+    decode::DecodeError,
+    message::{Message, MessageFlags},
+};
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::{
+    convert::TryInto,
+    io::{Error as IoError, ErrorKind as IoErrorKind, IoSlice},
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// The length of the fixed part of the message header.
+pub(super) const FIXED_HEADER_LENGTH: usize = 16;
+
+/// The offset of the flags in the fixed part of the message header.
+pub(crate) const FLAGS_OFFSET: usize = 2;
+
+/// The [maximum size] of a message in bytes.
+///
+/// [maximum size]: https://dbus.freedesktop.org/doc/dbus-specification.html#message-protocol-messages
+pub const MAXIMUM_MESSAGE_SIZE: usize = 134_217_728;
+
+/// The maximum size of the header fields in bytes, which is the maximum size of an array.
+pub(super) const MAXIMUM_FIELDS_SIZE: usize = 67_108_864;
+
+/// The only supported version of the protocol.
+const PROTOCOL_VERSION: u8 = 1;
+
+/// The maximum number of messages, which are written with one vectored write.
+pub(super) const MAXIMUM_BATCH_SIZE: usize = 64;
+
+/// An enum representing all violations of the protocol by the received data. If a violation is
+/// detected then the connection is closed.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ProtocolError {
+    #[error("Unknown endianness: {0}")]
+    Endianness(u8),
+    #[error("Unknown message type: {0}")]
+    MessageType(u8),
+    #[error("Unsupported protocol version: {0}")]
+    Version(u8),
+    #[error("Serial must not be zero")]
+    Serial,
+    #[error("Header fields are too large: {0} bytes")]
+    FieldsTooLarge(usize),
+    #[error("Message is too large: {size} > {maximum} bytes")]
+    TooLarge { size: usize, maximum: usize },
+    #[error("Could not decode message: {0}")]
+    Decode(Arc<DecodeError>),
+}
+
+#[derive(Debug, Error)]
+pub(crate) enum FrameError {
+    #[error("IO Error: {0}")]
+    IoError(#[from] IoError),
+    #[error("Protocol Error: {0}")]
+    ProtocolError(#[from] ProtocolError),
+}
+
+/// Check the fixed part of the header and get the total size of the message.
+///
+/// If the size is greater than `maximum_size` then [`ProtocolError::TooLarge`] is returned.
+pub(super) fn get_message_size(
+    header: &[u8; FIXED_HEADER_LENGTH],
+    maximum_size: usize,
+) -> Result<usize, ProtocolError> {
+    let is_le = match header[0] {
+        b'l' => true,
+        b'B' => false,
+        endianness => return Err(ProtocolError::Endianness(endianness)),
+    };
+    let read_u32 = |offset: usize| {
+        // The unwrap function call will never panic because the slice has always 4 bytes.
+        let bytes = header[offset..offset + 4].try_into().unwrap();
+        if is_le {
+            u32::from_le_bytes(bytes) as usize
+        } else {
+            u32::from_be_bytes(bytes) as usize
+        }
+    };
+    if !(1..=4).contains(&header[1]) {
+        return Err(ProtocolError::MessageType(header[1]));
+    }
+    if header[3] != PROTOCOL_VERSION {
+        return Err(ProtocolError::Version(header[3]));
+    }
+    let body_size = read_u32(4);
+    if read_u32(8) == 0 {
+        return Err(ProtocolError::Serial);
+    }
+    let fields_size = read_u32(12);
+    if MAXIMUM_FIELDS_SIZE < fields_size {
+        return Err(ProtocolError::FieldsTooLarge(fields_size));
+    }
+    // The header is padded to a multiple of 8 bytes.
+    let header_size = (FIXED_HEADER_LENGTH + fields_size + 7) & !7;
+    let size = header_size.saturating_add(body_size);
+    if maximum_size < size {
+        Err(ProtocolError::TooLarge {
+            size,
+            maximum: maximum_size,
+        })
+    } else {
+        Ok(size)
+    }
+}
+
+/// Read exactly one message from the stream.
+///
+/// The fixed part of the header is checked first to get the total size of the message, so
+/// nothing is buffered if the message is larger than `maximum_size`. Then the rest of the message
+/// is read into a preallocated buffer and decoded once. The flags are returned together with the
+/// message, because the message does not provide a getter for them.
+pub(crate) async fn read_message<T>(
+    stream: &mut T,
+    maximum_size: usize,
+) -> Result<(Message, MessageFlags), FrameError>
+where
+    T: AsyncReadExt + Unpin,
+{
+    let mut header = [0; FIXED_HEADER_LENGTH];
+    stream.read_exact(&mut header).await?;
+    let size = get_message_size(&header, maximum_size)?;
+    let flags = MessageFlags::from_bits_truncate(header[FLAGS_OFFSET]);
+
+    let mut buffer = BytesMut::with_capacity(size);
+    buffer.extend_from_slice(&header);
+    buffer.resize(size, 0);
+    stream
+        .read_exact(&mut buffer[FIXED_HEADER_LENGTH..])
+        .await?;
+
+    match Message::decode(buffer.freeze()) {
+        Ok((msg, _)) => Ok((msg, flags)),
+        Err(e) => Err(ProtocolError::Decode(Arc::new(e)).into()),
+    }
+}
+
+/// Write all buffers to the sink with vectored writes. The list of buffers is empty afterwards,
+/// so it can be reused.
+///
+/// At most [`MAXIMUM_BATCH_SIZE`] buffers are written at once, so the slices are kept on the stack
+/// and no memory is allocated.
+pub(super) async fn write_buffers<T>(
+    sink: &mut T,
+    buffers: &mut Vec<BytesMut>,
+) -> Result<(), IoError>
+where
+    T: AsyncWriteExt + Unpin,
+{
+    let mut first = 0;
+    loop {
+        // Skip the buffers, which are already written.
+        while first < buffers.len() && buffers[first].is_empty() {
+            first += 1;
+        }
+        if first == buffers.len() {
+            break;
+        }
+
+        let mut slices = [IoSlice::new(&[]); MAXIMUM_BATCH_SIZE];
+        let count = (buffers.len() - first).min(MAXIMUM_BATCH_SIZE);
+        for (slice, buffer) in slices.iter_mut().zip(&buffers[first..]) {
+            *slice = IoSlice::new(buffer);
+        }
+        let mut size = sink.write_vectored(&slices[..count]).await?;
+        if size == 0 {
+            return Err(IoError::new(
+                IoErrorKind::WriteZero,
+                "failed to write the buffers",
+            ));
+        }
+
+        for buffer in buffers[first..].iter_mut() {
+            if size == 0 {
+                break;
+            }
+            let advance = size.min(buffer.len());
+            buffer.advance(advance);
+            size -= advance;
+        }
+    }
+    buffers.clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_message_size, read_message, write_buffers, FrameError, ProtocolError,
+        FIXED_HEADER_LENGTH, MAXIMUM_BATCH_SIZE, MAXIMUM_MESSAGE_SIZE,
+    };
+    use bytes::BytesMut;
+    use dbus_message_parser::{
+        message::{Message, MessageFlags},
+        value::Value,
+    };
+    use futures::executor::block_on;
+    use std::convert::TryInto;
+
+    fn encode_signal(value: &str) -> Vec<u8> {
+        let mut msg = Message::signal(
+            "/org/example".try_into().unwrap(),
+            "org.example.Interface".try_into().unwrap(),
+            "Signal".try_into().unwrap(),
+        );
+        msg.set_serial(1);
+        msg.add_value(Value::String(value.to_string()));
+        msg.encode().unwrap().to_vec()
+    }
+
+    fn read(bytes: &[u8], maximum_size: usize) -> Result<(Message, MessageFlags), FrameError> {
+        let mut stream = bytes;
+        block_on(read_message(&mut stream, maximum_size))
+    }
+
+    fn is_protocol_error(
+        result: Result<(Message, MessageFlags), FrameError>,
+        error: ProtocolError,
+    ) -> bool {
+        matches!(result, Err(FrameError::ProtocolError(e)) if e == error)
+    }
+
+    /// A simple pseudo random number generator (xorshift), so the fuzz tests are reproducible.
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn bytes(&mut self, len: usize) -> Vec<u8> {
+            (0..len).map(|_| self.next() as u8).collect()
+        }
+    }
+
+    #[test]
+    fn message_size() {
+        let bytes = encode_signal("Hello world");
+        let header: [u8; FIXED_HEADER_LENGTH] = bytes[..FIXED_HEADER_LENGTH].try_into().unwrap();
+        assert_eq!(
+            get_message_size(&header, MAXIMUM_MESSAGE_SIZE).unwrap(),
+            bytes.len()
+        );
+    }
+
+    #[test]
+    fn message_size_header() {
+        let bytes = encode_signal("Hello world");
+        let header: [u8; FIXED_HEADER_LENGTH] = bytes[..FIXED_HEADER_LENGTH].try_into().unwrap();
+        let check = |index: usize, value: u8| {
+            let mut header = header;
+            header[index] = value;
+            get_message_size(&header, MAXIMUM_MESSAGE_SIZE)
+        };
+        assert_eq!(check(0, b'x'), Err(ProtocolError::Endianness(b'x')));
+        assert_eq!(check(1, 0), Err(ProtocolError::MessageType(0)));
+        assert_eq!(check(1, 5), Err(ProtocolError::MessageType(5)));
+        assert_eq!(check(3, 2), Err(ProtocolError::Version(2)));
+        let mut header_serial = header;
+        header_serial[8..12].copy_from_slice(&[0; 4]);
+        assert_eq!(
+            get_message_size(&header_serial, MAXIMUM_MESSAGE_SIZE),
+            Err(ProtocolError::Serial)
+        );
+        let mut header_fields = header;
+        header_fields[12..16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            get_message_size(&header_fields, MAXIMUM_MESSAGE_SIZE),
+            Err(ProtocolError::FieldsTooLarge(u32::MAX as usize))
+        );
+    }
+
+    #[test]
+    fn read_messages() {
+        let mut bytes = encode_signal("first");
+        bytes.extend(encode_signal("second"));
+        let mut stream = bytes.as_slice();
+        let (first, flags) = block_on(read_message(&mut stream, MAXIMUM_MESSAGE_SIZE)).unwrap();
+        assert_eq!(flags, MessageFlags::NO_REPLY_EXPECTED);
+        let (second, _) = block_on(read_message(&mut stream, MAXIMUM_MESSAGE_SIZE)).unwrap();
+        assert_eq!(first.get_body(), &[Value::String("first".to_string())]);
+        assert_eq!(second.get_body(), &[Value::String("second".to_string())]);
+        assert!(matches!(
+            block_on(read_message(&mut stream, MAXIMUM_MESSAGE_SIZE)),
+            Err(FrameError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn fuzz_truncated() {
+        let bytes = encode_signal("Hello world");
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                read(&bytes[..len], MAXIMUM_MESSAGE_SIZE),
+                Err(FrameError::IoError(_))
+            ));
+        }
+        assert!(read(&bytes, MAXIMUM_MESSAGE_SIZE).is_ok());
+    }
+
+    #[test]
+    fn fuzz_oversized() {
+        let bytes = encode_signal("Hello world");
+        // The limit is checked before the rest of the message is read.
+        let error = ProtocolError::TooLarge {
+            size: bytes.len(),
+            maximum: bytes.len() - 1,
+        };
+        assert!(is_protocol_error(
+            read(&bytes[..FIXED_HEADER_LENGTH], bytes.len() - 1),
+            error
+        ));
+
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+        for _ in 0..1000 {
+            let mut header = bytes[..FIXED_HEADER_LENGTH].to_vec();
+            let body_size = (random.next() as u32).max(MAXIMUM_MESSAGE_SIZE as u32);
+            header[4..8].copy_from_slice(&body_size.to_le_bytes());
+            assert!(matches!(
+                read(&header, MAXIMUM_MESSAGE_SIZE),
+                Err(FrameError::ProtocolError(ProtocolError::TooLarge { .. }))
+            ));
+        }
+    }
+
+    #[test]
+    fn fuzz_garbage() {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..10_000 {
+            let len = (random.next() % 256) as usize;
+            let bytes = random.bytes(len);
+            // The result does not matter, but it must not panic.
+            let _ = read(&bytes, MAXIMUM_MESSAGE_SIZE);
+        }
+    }
+
+    #[test]
+    fn fuzz_mutated() {
+        let bytes = encode_signal("Hello world");
+        let mut random = Random(0xdead_beef_cafe_f00d);
+        for _ in 0..10_000 {
+            let mut mutated = bytes.clone();
+            for _ in 0..(random.next() % 4 + 1) {
+                let index = (random.next() as usize) % mutated.len();
+                mutated[index] = random.next() as u8;
+            }
+            // The result does not matter, but it must not panic.
+            let _ = read(&mutated, MAXIMUM_MESSAGE_SIZE);
+        }
+    }
+
+    #[test]
+    fn write_and_read_messages() {
+        let first = encode_signal("first");
+        let second = encode_signal("second");
+        let mut buffers = vec![
+            BytesMut::from(first.as_slice()),
+            BytesMut::new(),
+            BytesMut::from(second.as_slice()),
+        ];
+        let mut sink = Vec::new();
+        block_on(write_buffers(&mut sink, &mut buffers)).unwrap();
+        assert!(buffers.is_empty());
+        assert_eq!(sink, [first, second].concat());
+    }
+
+    #[test]
+    fn write_more_than_batch() {
+        let signal = encode_signal("signal");
+        let mut buffers = vec![BytesMut::from(signal.as_slice()); 2 * MAXIMUM_BATCH_SIZE + 1];
+        let mut sink = Vec::new();
+        block_on(write_buffers(&mut sink, &mut buffers)).unwrap();
+        assert!(buffers.is_empty());
+        assert_eq!(sink, signal.repeat(2 * MAXIMUM_BATCH_SIZE + 1));
+    }
+}
@@ -1,8 +1,12 @@
-use crate::Uuid;
-use hex::encode;
-use std::io::Error as IoError;
+use crate::{AuthMechanism, Uuid};
+use futures::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use hex::{decode, encode};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    io::Error as IoError,
+};
 use thiserror::Error;
-use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufStream};
 
 #[derive(Debug, Error)]
 pub enum HandshakeError {
@@ -14,13 +18,27 @@ pub enum HandshakeError {
     AuthenticationError(String),
     #[error("Authentication error: {0}")]
     NegotiateUnixFdError(String),
+    #[error("Client did not send the zero byte")]
+    ZeroByte,
+    #[error("Client closed the connection during the authentication")]
+    Closed,
     #[error("IO Error: {0}")]
     IoError(#[from] IoError),
 }
 
 const NEW_LINE: &str = "\r\n";
 
-pub(super) struct Handshake<T>(BufStream<T>);
+/// Create a random GUID of the server, which is sent during the authentication.
+pub(crate) fn create_guid() -> Uuid {
+    let mut guid = [0; 16];
+    for chunk in guid.chunks_mut(8) {
+        let hash = RandomState::new().build_hasher().finish();
+        chunk.copy_from_slice(&hash.to_le_bytes());
+    }
+    guid
+}
+
+pub(crate) struct Handshake<T>(BufReader<T>);
 
 impl<T> Handshake<T>
 where
@@ -37,8 +55,9 @@ where
     }
 
     async fn write_line(&mut self, line: &str) -> Result<(), IoError> {
+        // The writes are not buffered, so write the line at once.
+        let line = format!("{}{}", line, NEW_LINE);
         self.0.write_all(line.as_bytes()).await?;
-        self.0.write_all(NEW_LINE.as_bytes()).await?;
         self.0.flush().await?;
         Ok(())
     }
@@ -70,6 +89,11 @@ where
         let response = self.request("NEGOTIATE_UNIX_FD").await?;
         if response == "AGREE_UNIX_FD" {
             Ok(())
+        } else if response.starts_with("ERROR") {
+            // The passing of Unix file descriptors is optional, so the connection can be used
+            // without it.
+            debug!("Unix file descriptors are not supported: {}", response);
+            Ok(())
         } else {
             Err(HandshakeError::NegotiateUnixFdError(response))
         }
@@ -99,18 +123,23 @@ where
         }
     }
 
-    async fn authenticate(&mut self) -> Result<(), HandshakeError> {
-        for mechanism in self.list_available_mechanisms().await? {
-            match mechanism.as_str() {
-                "EXTERNAL" => match self.auth_external().await {
-                    Ok(_) => return Ok(()),
-                    Err(e) => error!("Could not authenticate (EXTERNAL): {}", e),
-                },
-                "ANONYMOUS" => match self.auth_anonymous().await {
-                    Ok(_) => return Ok(()),
-                    Err(e) => error!("Could not authenticate (ANONYMOUS): {}", e),
-                },
-                x => error!("Authentication is not supported: {}", x),
+    async fn authenticate(&mut self, mechanisms: &[AuthMechanism]) -> Result<(), HandshakeError> {
+        let available_mechanisms = self.list_available_mechanisms().await?;
+        for mechanism in mechanisms {
+            if !available_mechanisms
+                .iter()
+                .any(|available| available == mechanism.as_str())
+            {
+                debug!("Authentication is not available: {}", mechanism.as_str());
+                continue;
+            }
+            let result = match mechanism {
+                AuthMechanism::External => self.auth_external().await,
+                AuthMechanism::Anonymous => self.auth_anonymous().await,
+            };
+            match result {
+                Ok(_) => return Ok(()),
+                Err(e) => error!("Could not authenticate ({}): {}", mechanism.as_str(), e),
             }
         }
 
@@ -122,25 +151,26 @@ where
     }
 
     async fn new(stream: T, nonce: &Option<Uuid>) -> Result<Handshake<T>, IoError> {
-        let mut buf_stream = BufStream::new(stream);
+        let mut buf_reader = BufReader::new(stream);
         if let Some(nonce) = nonce {
-            buf_stream.write_all(nonce).await?;
+            buf_reader.write_all(nonce).await?;
         }
         // Write a zero to the socket.
         let zero: [u8; 1] = [0; 1];
-        buf_stream.write_all(&zero[..]).await?;
-        Ok(Handshake(buf_stream))
+        buf_reader.write_all(&zero[..]).await?;
+        Ok(Handshake(buf_reader))
     }
 
     /// Connect to the Unix Domain Stream socket.
     pub(super) async fn handshake(
         stream: &mut T,
+        mechanisms: &[AuthMechanism],
         negotiate_unix_fd: bool,
         nonce: &Option<Uuid>,
     ) -> Result<(), HandshakeError> {
         let mut handshake = Handshake::new(stream, nonce).await?;
 
-        handshake.authenticate().await?;
+        handshake.authenticate(mechanisms).await?;
 
         if negotiate_unix_fd {
             handshake.negotiate_unix_fd().await?;
@@ -150,3 +180,97 @@ where
         Ok(())
     }
 }
+
+impl<T> Handshake<T>
+where
+    T: AsyncWrite + AsyncRead + Unpin,
+{
+    /// Get the rejected line with the offered mechanisms.
+    fn rejected(mechanisms: &[AuthMechanism]) -> String {
+        let mut line = "REJECTED".to_string();
+        for mechanism in mechanisms {
+            line.push(' ');
+            line.push_str(mechanism.as_str());
+        }
+        line
+    }
+
+    /// Decode the UID, which the client sent with the `EXTERNAL` mechanism.
+    fn decode_uid(hex: &str) -> Option<u32> {
+        let uid = decode(hex).ok()?;
+        let uid = String::from_utf8(uid).ok()?;
+        uid.parse().ok()
+    }
+
+    /// Answer the handshake of a client as the server.
+    ///
+    /// If `peer_uid` is given then the `EXTERNAL` mechanism only accepts this UID, otherwise
+    /// the UID of the client is trusted.
+    ///
+    /// Returns the buffered stream, which can contain the first messages of the client, and the
+    /// UID of the client, if it authenticated with the `EXTERNAL` mechanism.
+    pub(crate) async fn accept(
+        stream: T,
+        guid: &str,
+        mechanisms: &[AuthMechanism],
+        unix_fd: bool,
+        peer_uid: Option<u32>,
+    ) -> Result<(BufReader<T>, Option<u32>), HandshakeError> {
+        let mut handshake = Handshake(BufReader::new(stream));
+        let mut zero = [0; 1];
+        handshake.0.read_exact(&mut zero).await?;
+        if zero[0] != 0 {
+            return Err(HandshakeError::ZeroByte);
+        }
+
+        let mut authenticated = None;
+        loop {
+            let line = handshake.read_line().await?;
+            if line.is_empty() {
+                return Err(HandshakeError::Closed);
+            }
+            let mut words = line.split(' ');
+            let response = match (words.next(), words.next(), words.next()) {
+                (Some("AUTH"), Some(mechanism), initial_response) if authenticated.is_none() => {
+                    match mechanism {
+                        "EXTERNAL" if mechanisms.contains(&AuthMechanism::External) => {
+                            match initial_response.and_then(Handshake::<T>::decode_uid) {
+                                Some(uid) if peer_uid.is_none() || peer_uid == Some(uid) => {
+                                    authenticated = Some(Some(uid));
+                                    format!("OK {}", guid)
+                                }
+                                _ => Handshake::<T>::rejected(mechanisms),
+                            }
+                        }
+                        "ANONYMOUS" if mechanisms.contains(&AuthMechanism::Anonymous) => {
+                            authenticated = Some(None);
+                            format!("OK {}", guid)
+                        }
+                        _ => Handshake::<T>::rejected(mechanisms),
+                    }
+                }
+                (Some("AUTH"), None, None) | (Some("CANCEL"), _, _) | (Some("ERROR"), _, _)
+                    if authenticated.is_none() =>
+                {
+                    Handshake::<T>::rejected(mechanisms)
+                }
+                (Some("NEGOTIATE_UNIX_FD"), None, None) if authenticated.is_some() => {
+                    if unix_fd {
+                        "AGREE_UNIX_FD".to_string()
+                    } else {
+                        "ERROR Unix file descriptors are not supported".to_string()
+                    }
+                }
+                (Some("BEGIN"), None, None) => {
+                    if let Some(uid) = authenticated {
+                        return Ok((handshake.0, uid));
+                    } else {
+                        "ERROR Not authenticated".to_string()
+                    }
+                }
+                _ => "ERROR Unknown command".to_string(),
+            };
+            handshake.write_line(&response).await?;
+        }
+    }
+}
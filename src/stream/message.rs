@@ -1,95 +1,131 @@
-use bytes::{Buf, BytesMut};
-use dbus_message_parser::{decode::DecodeError, message::Message};
-use futures::{
-    channel::mpsc::{UnboundedReceiver, UnboundedSender},
-    stream::StreamExt,
+use super::frame::{
+    read_message, write_buffers, FrameError, ProtocolError, FLAGS_OFFSET, MAXIMUM_BATCH_SIZE,
 };
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use crate::ReceivePolicy;
+use bytes::BytesMut;
+use dbus_message_parser::message::{Message, MessageFlags, MessageType};
+use futures::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tokio::sync::mpsc::{error::TrySendError, Receiver, Sender};
+
+/// Encode the message and add it to the buffers, which are written next. If flags are given then
+/// they replace the flags of the encoded message. If the message cannot be encoded then it is
+/// dropped.
+fn encode_message(msg: Message, flags: Option<MessageFlags>, buffers: &mut Vec<BytesMut>) {
+    match msg.encode() {
+        Ok(mut buffer) => {
+            if let (Some(flags), Some(byte)) = (flags, buffer.get_mut(FLAGS_OFFSET)) {
+                *byte = flags.bits();
+            }
+            buffers.push(buffer)
+        }
+        Err(e) => error!("message_sink: could not encode message: {:?}", e),
+    }
+}
 
 /// The message sink task. This task takes messages from the channel and send it through the DBus
 /// socket.
-pub async fn message_sink<T>(mut message_receiver: UnboundedReceiver<Message>, mut sink: T)
-where
+///
+/// All messages, which are queued, are written together with vectored writes and flushed.
+pub async fn message_sink<T>(
+    mut message_receiver: Receiver<(Message, Option<MessageFlags>)>,
+    mut sink: T,
+) where
     T: AsyncWriteExt + Unpin,
 {
+    let mut buffers = Vec::with_capacity(MAXIMUM_BATCH_SIZE);
     // Get the next Message to send to the DBus socket
-    while let Some(msg) = message_receiver.next().await {
-        // Try to encode
-        let mut buffer = match msg.encode() {
-            Ok(buffer) => buffer,
-            Err(e) => {
-                error!("message_sink: {:?}", e);
-                return;
+    while let Some((msg, flags)) = message_receiver.recv().await {
+        encode_message(msg, flags, &mut buffers);
+        // Take all messages, which are already queued.
+        while buffers.len() < MAXIMUM_BATCH_SIZE {
+            match message_receiver.try_recv() {
+                Ok((msg, flags)) => encode_message(msg, flags, &mut buffers),
+                Err(_) => break,
             }
-        };
+        }
+
+        if let Err(e) = write_buffers(&mut sink, &mut buffers).await {
+            error!("message_sink: {:?}", e);
+            return;
+        }
+        // Flush the batch, so a buffering transport does not hold back the messages.
+        if let Err(e) = sink.flush().await {
+            error!("message_sink: {:?}", e);
+            return;
+        }
+    }
+    // Shut down the writing side, so the DBus daemon knows the connection is closed.
+    if let Err(e) = sink.close().await {
+        error!("message_sink: {:?}", e);
+    }
+}
 
-        while !buffer.is_empty() {
-            match sink.write(buffer.as_mut()).await {
-                Ok(size) => {
-                    buffer.advance(size);
-                }
-                Err(e) => {
-                    error!("message_sink: {:?}", e);
-                    return;
-                }
+/// Send a received message to the connection task.
+///
+/// If the queue is full then the function waits, except for signals with the
+/// [`ReceivePolicy::DropSignals`] policy. Returns `false`, if the connection task is closed.
+async fn forward_message(
+    message_sink: &Sender<Result<(Message, MessageFlags), ProtocolError>>,
+    msg: Message,
+    flags: MessageFlags,
+    receive_policy: ReceivePolicy,
+    dropped_signals: &AtomicUsize,
+) -> bool {
+    if ReceivePolicy::DropSignals == receive_policy && MessageType::Signal == msg.get_type() {
+        match message_sink.try_send(Ok((msg, flags))) {
+            Ok(()) => true,
+            Err(TrySendError::Full(msg)) => {
+                dropped_signals.fetch_add(1, Ordering::Relaxed);
+                debug!("message_stream: drop signal: {:?}", msg);
+                true
+            }
+            Err(TrySendError::Closed(_)) => {
+                error!("message_stream: channel closed");
+                false
             }
         }
+    } else if let Err(e) = message_sink.send(Ok((msg, flags))).await {
+        error!("message_stream: {}", e);
+        false
+    } else {
+        true
     }
 }
 
 /// The message stream task. This task takes messages, which were received from the DBus socket.
-pub async fn message_stream<T>(mut stream: T, message_sink: UnboundedSender<Message>)
-where
+pub async fn message_stream<T>(
+    stream: T,
+    message_sink: Sender<Result<(Message, MessageFlags), ProtocolError>>,
+    receive_policy: ReceivePolicy,
+    maximum_message_size: usize,
+    dropped_signals: Arc<AtomicUsize>,
+) where
     T: AsyncReadExt + Unpin,
 {
-    let mut buffer_msg = BytesMut::new();
-    // Get the next Message received from the DBus socket
-    let mut buffer: [u8; 128] = [0; 128];
+    // Buffer the stream, so small messages do not need multiple system calls.
+    let mut stream = BufReader::new(stream);
     loop {
-        match stream.read(&mut buffer[..]).await {
-            Ok(size) => {
-                buffer_msg.extend_from_slice(&buffer[..size]);
-                if size == 0 {
-                    error!("message_stream: size == 0");
-                    return;
-                }
+        // Get the next Message received from the DBus socket
+        let (msg, flags) = match read_message(&mut stream, maximum_message_size).await {
+            Ok(result) => result,
+            Err(FrameError::ProtocolError(e)) => {
+                error!("message_stream: {}", e);
+                // Close the connection with the error.
+                let _ = message_sink.send(Err(e)).await;
+                return;
             }
             Err(e) => {
-                error!("message_stream: {:?}", e);
+                error!("message_stream: {}", e);
                 return;
             }
-        }
-
-        loop {
-            let bytes = buffer_msg.clone().freeze();
-            let result = Message::decode(bytes);
-            match result {
-                Ok((msg, offset)) => {
-                    buffer_msg.advance(offset);
-                    // Try to send the message to the server
-                    if let Err(e) = message_sink.unbounded_send(msg) {
-                        error!("message_stream: {}", e);
-                        return;
-                    }
-                    // Check if all bytes are decoded
-                    if buffer_msg.is_empty() {
-                        // Free the buffer
-                        buffer_msg = BytesMut::new();
-                        break;
-                    }
-                }
-                Err(DecodeError::NotEnoughBytes(u1, u2)) => {
-                    debug!(
-                        "message_stream: DecodeError::NotEnoughBytes({}, {})",
-                        u1, u2
-                    );
-                    break;
-                }
-                Err(e) => {
-                    error!("message_stream: {:?}", e);
-                    return;
-                }
-            }
+        };
+        // Try to send the message to the server
+        if !forward_message(&message_sink, msg, flags, receive_policy, &dropped_signals).await {
+            return;
         }
     }
 }
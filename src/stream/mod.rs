@@ -1,6 +1,18 @@
 mod connect;
+mod credentials;
 mod r#enum;
+mod frame;
 mod handshake;
 mod message;
 
-pub use r#enum::{Stream, StreamError};
+#[cfg(feature = "daemon")]
+pub(crate) use credentials::get_peer_uid;
+#[cfg(any(feature = "test-support", feature = "daemon"))]
+pub(crate) use frame::read_message;
+pub use frame::{ProtocolError, MAXIMUM_MESSAGE_SIZE};
+pub(crate) use handshake::create_guid;
+#[cfg(any(feature = "test-support", feature = "daemon"))]
+pub(crate) use handshake::Handshake;
+#[cfg(any(feature = "test-support", feature = "daemon"))]
+pub(crate) use message::message_sink;
+pub use r#enum::{Stream, StreamError, StreamHandle, Transport};
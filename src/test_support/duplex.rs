@@ -0,0 +1,145 @@
+use futures::io::{AsyncRead, AsyncWrite};
+use std::{
+    collections::VecDeque,
+    io::{Error as IoError, ErrorKind, Result as IoResult},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+/// One direction of a [`DuplexStream`].
+#[derive(Debug)]
+struct Pipe {
+    buffer: VecDeque<u8>,
+    capacity: usize,
+    /// The writing side is closed, so the reading side gets EOF after the buffer is empty.
+    is_closed: bool,
+    /// The reading side is dropped, so every write fails.
+    is_dropped: bool,
+    read_waker: Option<Waker>,
+    write_waker: Option<Waker>,
+}
+
+impl Pipe {
+    fn new(capacity: usize) -> Arc<Mutex<Pipe>> {
+        let pipe = Pipe {
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
+            is_closed: false,
+            is_dropped: false,
+            read_waker: None,
+            write_waker: None,
+        };
+        Arc::new(Mutex::new(pipe))
+    }
+
+    fn close(&mut self) {
+        self.is_closed = true;
+        if let Some(waker) = self.read_waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn drop_reader(&mut self) {
+        self.is_dropped = true;
+        if let Some(waker) = self.write_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// An in-memory duplex stream, which is created by [`duplex`].
+///
+/// The stream works with every runtime. If one side is closed or dropped then the other side
+/// reads EOF, after all written bytes are read.
+#[derive(Debug)]
+pub struct DuplexStream {
+    read: Arc<Mutex<Pipe>>,
+    write: Arc<Mutex<Pipe>>,
+}
+
+/// Create a pair of connected in-memory streams. Each direction buffers up to `capacity` bytes.
+///
+/// # Panics
+/// If `capacity` is zero.
+pub fn duplex(capacity: usize) -> (DuplexStream, DuplexStream) {
+    assert!(0 < capacity, "capacity has to be greater than zero");
+    let pipe_1 = Pipe::new(capacity);
+    let pipe_2 = Pipe::new(capacity);
+    let stream_1 = DuplexStream {
+        read: pipe_1.clone(),
+        write: pipe_2.clone(),
+    };
+    let stream_2 = DuplexStream {
+        read: pipe_2,
+        write: pipe_1,
+    };
+    (stream_1, stream_2)
+}
+
+impl AsyncRead for DuplexStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<IoResult<usize>> {
+        // The unwrap function call will only panic if the other side panicked.
+        let mut pipe = self.read.lock().unwrap();
+        if pipe.buffer.is_empty() && !buf.is_empty() {
+            if pipe.is_closed {
+                return Poll::Ready(Ok(0));
+            }
+            pipe.read_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        let size = buf.len().min(pipe.buffer.len());
+        for (byte, value) in buf.iter_mut().zip(pipe.buffer.drain(..size)) {
+            *byte = value;
+        }
+        if let Some(waker) = pipe.write_waker.take() {
+            waker.wake();
+        }
+        Poll::Ready(Ok(size))
+    }
+}
+
+impl AsyncWrite for DuplexStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<IoResult<usize>> {
+        // The unwrap function call will only panic if the other side panicked.
+        let mut pipe = self.write.lock().unwrap();
+        if pipe.is_dropped || pipe.is_closed {
+            return Poll::Ready(Err(IoError::from(ErrorKind::BrokenPipe)));
+        }
+        let size = buf.len().min(pipe.capacity - pipe.buffer.len());
+        if size == 0 && !buf.is_empty() {
+            pipe.write_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        pipe.buffer.extend(&buf[..size]);
+        if let Some(waker) = pipe.read_waker.take() {
+            waker.wake();
+        }
+        Poll::Ready(Ok(size))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<IoResult<()>> {
+        // The unwrap function call will only panic if the other side panicked.
+        self.write.lock().unwrap().close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for DuplexStream {
+    fn drop(&mut self) {
+        if let Ok(mut pipe) = self.write.lock() {
+            pipe.close();
+        }
+        if let Ok(mut pipe) = self.read.lock() {
+            pipe.drop_reader();
+        }
+    }
+}
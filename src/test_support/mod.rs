@@ -0,0 +1,76 @@
+//! An in-memory message bus for tests, which do not need a running DBus daemon.
+//!
+//! This module is only available with the `test-support` feature.
+//!
+//! ```
+//! use dbus_async::test_support::MockBus;
+//!
+//! # async fn test() -> dbus_async::DBusResult<()> {
+//! let bus = MockBus::new();
+//! let (service, _service_handle) = bus.connect().await?;
+//! let (client, _client_handle) = bus.connect().await?;
+//! # Ok(())
+//! # }
+//! ```
+mod duplex;
+
+pub use duplex::{duplex, DuplexStream};
+
+use crate::{
+    bus::{Authentication, Bus},
+    DBus, DBusBuilder, DBusResult, JoinHandle,
+};
+
+/// The number of bytes, which are buffered in each direction of a connection to the
+/// [`MockBus`].
+const DUPLEX_CAPACITY: usize = 64 * 1024;
+
+/// An in-memory message bus, which routes the messages between multiple [`DBus`] objects.
+///
+/// The bus implements the methods of the `org.freedesktop.DBus` interface, which are needed to
+/// own names and to receive signals: `Hello`, `RequestName`, `ReleaseName`, `GetNameOwner`,
+/// `NameHasOwner`, `ListNames`, `ListQueuedOwners`, `AddMatch`, `RemoveMatch`,
+/// `GetConnectionUnixUser` and `GetId`. The `NameOwnerChanged`, `NameAcquired` and `NameLost`
+/// signals are sent like by the DBus daemon.
+///
+/// The bus runs in its own task until the object is dropped, so the object has to be created
+/// inside the runtime.
+///
+/// [`DBus`]: crate::DBus
+pub struct MockBus {
+    bus: Bus,
+}
+
+impl MockBus {
+    /// Start a new bus without any connections.
+    pub fn new() -> MockBus {
+        MockBus { bus: Bus::new() }
+    }
+
+    /// Connect a new [`DBus`] object to the bus with the default options.
+    ///
+    /// [`DBus`]: crate::DBus
+    pub async fn connect(&self) -> DBusResult<(DBus, JoinHandle<()>)> {
+        self.connect_with_builder(DBusBuilder::new()).await
+    }
+
+    /// Connect a new [`DBus`] object to the bus with the options of the builder. The address of
+    /// the builder is ignored and the [`Hello()`] method is always called.
+    ///
+    /// [`DBus`]: crate::DBus
+    /// [`Hello()`]: https://dbus.freedesktop.org/doc/dbus-specification.html#bus-messages-hello
+    pub async fn connect_with_builder(
+        &self,
+        builder: DBusBuilder,
+    ) -> DBusResult<(DBus, JoinHandle<()>)> {
+        let (client, server) = duplex(DUPLEX_CAPACITY);
+        self.bus.accept(server, Authentication::Trusted);
+        builder.hello(true).build_with_stream(client).await
+    }
+}
+
+impl Default for MockBus {
+    fn default() -> Self {
+        MockBus::new()
+    }
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{block_on, create_ping};
+use dbus_async::{
+    activation::{listen_fds, ActivationError, LISTEN_FDS_START},
+    DBus, DBusBuilder,
+};
+use dbus_message_parser::message::MessageType;
+use futures::future::join;
+use std::{
+    env::{current_exe, set_var, var},
+    fs::remove_file,
+    os::unix::{
+        io::{IntoRawFd, RawFd},
+        net::{UnixListener, UnixStream},
+    },
+    process::{id, Command},
+};
+
+/// The environment variable, which is set, if the test runs in the child process.
+const CHILD: &str = "DBUS_ASYNC_ACTIVATION_CHILD";
+
+async fn ping(dbus: &DBus) {
+    let reply = dbus.call(create_ping()).await.unwrap();
+    assert_eq!(reply.get_type(), MessageType::MethodReturn);
+}
+
+/// Move the file descriptor above all file descriptors, which are passed in the test, so it does
+/// not collide with the target of another socket.
+fn park(fd: RawFd) -> RawFd {
+    let parked = unsafe { libc::fcntl(fd, libc::F_DUPFD, 100) };
+    assert!(parked >= 100);
+    unsafe { libc::close(fd) };
+    parked
+}
+
+/// Move the file descriptor to the target like the service manager.
+fn move_fd(fd: RawFd, target: RawFd) {
+    assert_eq!(unsafe { libc::dup2(fd, target) }, target);
+    unsafe { libc::close(fd) };
+}
+
+/// Simulate the service manager and take the passed sockets. This replaces the file descriptors
+/// `3`, `4` and `5`, so it runs in its own process.
+fn socket_activation_child() {
+    let path = format!("/tmp/dbus-async-activation-{}", id());
+    // The sockets have to be the file descriptors 3 and 4 and the file descriptor 5 is closed.
+    // The sockets are owned by the `ListenFd` objects.
+    let unix_listener = park(UnixListener::bind(&path).unwrap().into_raw_fd());
+    let unix_stream = park(UnixStream::connect(&path).unwrap().into_raw_fd());
+    move_fd(unix_listener, LISTEN_FDS_START);
+    move_fd(unix_stream, LISTEN_FDS_START + 1);
+    unsafe { libc::close(LISTEN_FDS_START + 2) };
+
+    // The sockets were passed to another process.
+    set_var("LISTEN_PID", "1");
+    set_var("LISTEN_FDS", "2");
+    set_var("LISTEN_FDNAMES", "peer:client");
+    assert!(matches!(
+        listen_fds(),
+        Err(ActivationError::ListenPidMismatch(1, _))
+    ));
+
+    // The third file descriptor is not open, so no socket is taken.
+    set_var("LISTEN_PID", id().to_string());
+    set_var("LISTEN_FDS", "3");
+    set_var("LISTEN_FDNAMES", "peer:client:closed");
+    assert!(matches!(listen_fds(), Err(ActivationError::IoError(_))));
+    assert_eq!(var("LISTEN_FDS").unwrap(), "3");
+
+    set_var("LISTEN_FDS", "2");
+    set_var("LISTEN_FDNAMES", "peer:client");
+    let mut passed = listen_fds().unwrap();
+    assert!(var("LISTEN_FDS").is_err());
+    assert_eq!(passed.len(), 2);
+    let client = passed.pop().unwrap();
+    let peer = passed.pop().unwrap();
+    assert_eq!(client.get_name(), Some("client"));
+    assert_eq!(peer.get_name(), Some("peer"));
+
+    block_on(async {
+        let listener = DBusBuilder::new()
+            .listen_with_unix_listener(peer.into_unix_listener())
+            .unwrap();
+        // The passed client socket is already connected to the passed listening socket.
+        let connect = DBusBuilder::new()
+            .hello(false)
+            .build_with_unix_stream(client.into_unix_stream());
+        let (server, client) = join(listener.accept(), connect).await;
+        let (server_1, _server_handle_1) = server.unwrap();
+        let (client_1, _client_handle_1) = client.unwrap();
+        ping(&client_1).await;
+        ping(&server_1).await;
+
+        let connect = DBusBuilder::new()
+            .address(&format!("unix:path={}", path))
+            .hello(false)
+            .build();
+        let (server, client) = join(listener.accept(), connect).await;
+        let (_server_2, _server_handle_2) = server.unwrap();
+        let (client_2, _client_handle_2) = client.unwrap();
+        ping(&client_2).await;
+    });
+
+    // The sockets can only be taken once.
+    assert!(listen_fds().unwrap().is_empty());
+    remove_file(&path).unwrap();
+}
+
+// The environment variables and the file descriptors are global, so the test runs in a child
+// process, which only runs this test.
+#[test]
+fn socket_activation() {
+    if var(CHILD).is_ok() {
+        socket_activation_child();
+        return;
+    }
+    let status = Command::new(current_exe().unwrap())
+        .args(["--exact", "socket_activation", "--test-threads=1"])
+        .env(CHILD, "1")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::{create_ping, create_signal, start_server};
+use dbus_async::{DBusBuilder, DBusError};
+use dbus_message_parser::message::MessageType;
+use std::{convert::TryInto, time::Duration};
+
+#[test]
+fn drop_flushes_messages() {
+    let (address, server) = start_server("blocking_drop_flushes_messages", Vec::new());
+    let dbus = DBusBuilder::new()
+        .address(&address)
+        .hello(false)
+        .build_blocking()
+        .unwrap();
+    for _ in 0..100 {
+        dbus.send(create_ping()).unwrap();
+    }
+    drop(dbus);
+    assert_eq!(server.join().unwrap().len(), 100);
+}
+
+#[test]
+fn close() {
+    let (address, server) = start_server("blocking_close", Vec::new());
+    let dbus = DBusBuilder::new()
+        .address(&address)
+        .hello(false)
+        .build_blocking()
+        .unwrap();
+    dbus.send(create_ping()).unwrap();
+    dbus.close().unwrap();
+    assert_eq!(server.join().unwrap().len(), 1);
+}
+
+#[test]
+fn call_timeout() {
+    let (address, _server) = start_server("blocking_call_timeout", Vec::new());
+    let timeout = Duration::from_millis(100);
+    let dbus = DBusBuilder::new()
+        .address(&address)
+        .hello(false)
+        .call_timeout(Some(timeout))
+        .build_blocking()
+        .unwrap();
+    match dbus.call(create_ping()) {
+        Err(DBusError::CallTimeout(duration)) => assert_eq!(duration, timeout),
+        result => panic!("{:?}", result),
+    }
+}
+
+#[test]
+fn signals() {
+    let signals = vec![create_signal(1), create_signal(2), create_signal(3)];
+    let (address, _server) = start_server("blocking_signals", signals);
+    let dbus = DBusBuilder::new()
+        .address(&address)
+        .hello(false)
+        .build_blocking()
+        .unwrap();
+    let signals = dbus
+        .add_signal("/org/example".try_into().unwrap(), None)
+        .unwrap();
+    // The server sends the signals after it received the first message.
+    dbus.send(create_ping()).unwrap();
+    let serials: Vec<u32> = signals
+        .take(3)
+        .map(|msg| {
+            assert_eq!(msg.get_type(), MessageType::Signal);
+            msg.get_serial()
+        })
+        .collect();
+    assert_eq!(serials, vec![1, 2, 3]);
+}
+
+#[test]
+fn connect_failed() {
+    let result = DBusBuilder::new()
+        .address("unix:path=/nonexistent/dbus-async")
+        .build_blocking();
+    assert!(matches!(result, Err(DBusError::StreamError(_))));
+}
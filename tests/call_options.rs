@@ -0,0 +1,24 @@
+use dbus_async::CallOptions;
+use dbus_message_parser::message::MessageFlags;
+
+#[test]
+fn call_options_default() {
+    let options = CallOptions::new();
+    assert_eq!(options.get_flags(), MessageFlags::empty());
+    assert_eq!(options, CallOptions::default());
+}
+
+#[test]
+fn call_options_flags() {
+    let options = CallOptions::new()
+        .no_reply_expected(true)
+        .no_auto_start(true)
+        .allow_interactive_authorization(true);
+    assert_eq!(options.get_flags().bits(), 0x07);
+
+    let options = options.no_auto_start(false);
+    assert_eq!(
+        options.get_flags(),
+        MessageFlags::NO_REPLY_EXPECTED | MessageFlags::ALLOW_INTERACTIVE_AUTHORIZATION
+    );
+}
@@ -0,0 +1,222 @@
+#![cfg(feature = "codegen")]
+mod common;
+
+use dbus_async::codegen::generate;
+
+const XML: &str = r#"<node>
+  <interface name="org.example.Sample">
+    <method name="GetIPAddress">
+      <arg name="index" type="u" direction="in"/>
+      <arg name="address" type="s" direction="out"/>
+      <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+    </method>
+    <method name="Fire">
+      <annotation name="org.freedesktop.DBus.Method.NoReply" value="true"/>
+    </method>
+    <signal name="Changed">
+      <arg name="type" type="b"/>
+    </signal>
+    <property name="Counter" type="t" access="readwrite">
+      <annotation name="org.freedesktop.DBus.Property.EmitsChangedSignal" value="false"/>
+    </property>
+    <property name="Name" type="s" access="readwrite"/>
+  </interface>
+  <interface name="org.freedesktop.DBus.Peer">
+    <method name="Ping"/>
+  </interface>
+</node>"#;
+
+#[test]
+fn codegen_proxy() {
+    let code = generate(XML).unwrap();
+    assert!(code.contains("pub struct SampleProxy {"));
+    assert!(code.contains("#[deprecated]\n    pub async fn get_ip_address(&self, index: u32)"));
+    assert!(code.contains("pub async fn fire(&self) -> dbus_async::DBusResult<()>"));
+    assert!(code.contains("CallOptions::new().no_reply_expected(true)"));
+    assert!(code.contains("self.dbus.send_with_options(msg, &options)"));
+    assert!(code.contains("pub async fn get_counter(&self) -> dbus_async::DBusResult<u64>"));
+    assert!(code.contains("pub async fn set_name(&self, value: String)"));
+    // The standard interfaces are skipped.
+    assert!(!code.contains("PeerProxy"));
+}
+
+#[test]
+fn codegen_server() {
+    let code = generate(XML).unwrap();
+    assert!(code.contains("pub trait Sample: Send {"));
+    assert!(code.contains("pub async fn handle_sample<T: Sample>("));
+    assert!(code.contains("pub struct SampleServer<T>(pub T);"));
+    assert!(code.contains("impl<T: Sample> dbus_async::Handler for SampleServer<T>"));
+    assert!(code.contains("pub async fn emit_sample_changed(dbus: &dbus_async::DBus, object_path: dbus_async::export::dbus_message_parser::value::ObjectPath, r#type: bool)"));
+    // The `Fire` method and the method calls, which do not expect a reply, are not replied.
+    assert!(code.contains("let no_reply = flags.contains(dbus_async::export::dbus_message_parser::message::MessageFlags::NO_REPLY_EXPECTED) || matches!((interface, member.as_ref()), (Some(\"org.example.Sample\") | None, \"Fire\"));"));
+    assert!(code
+        .contains("if no_reply {\n        return Ok(());\n    }\n    dbus.send(response).await"));
+    // Only the `Name` property emits the `PropertiesChanged` signal.
+    assert_eq!(code.matches("\"PropertiesChanged\"").count(), 1);
+    // The generated code only depends on `dbus-async`.
+    assert!(!code.contains(" dbus_message_parser::"));
+    assert!(code.contains("#[dbus_async::export::async_trait::async_trait]"));
+}
+
+/// The generated code of `codegen/collisions.xml`, which has arguments with the same names as the
+/// identifiers of the generated code and methods with the same names as the constructor and the
+/// property accessors.
+#[allow(dead_code)]
+mod collisions {
+    include!("codegen/collisions.rs");
+
+    use dbus_async::{
+        export::dbus_message_parser::value::{Error, Value},
+        DBus,
+    };
+
+    /// An object, which stores the values of the `Fire` method and the `Counter` property.
+    #[derive(Default)]
+    struct Object {
+        fired: String,
+        counter: u64,
+    }
+
+    #[dbus_async::export::async_trait::async_trait]
+    impl Collisions for Object {
+        #[allow(clippy::too_many_arguments)]
+        async fn call(
+            &mut self,
+            _dbus: &DBus,
+            object: String,
+            header: u32,
+            _body: bool,
+            _dbus_3: String,
+            _msg: String,
+            _reply: String,
+            _self_: String,
+        ) -> Result<(String, u32), (Error, String)> {
+            Ok((object, header))
+        }
+
+        async fn member(
+            &mut self,
+            _dbus: &DBus,
+            interface: String,
+            _member: String,
+            _value: Value,
+        ) -> Result<String, (Error, String)> {
+            Ok(interface)
+        }
+
+        async fn fire(&mut self, _dbus: &DBus, object: String) -> Result<(), (Error, String)> {
+            self.fired = object;
+            Ok(())
+        }
+
+        async fn get_devices(&mut self, _dbus: &DBus) -> Result<Value, (Error, String)> {
+            Ok(Value::Boolean(true))
+        }
+
+        async fn call_new(&mut self, _dbus: &DBus) -> Result<(), (Error, String)> {
+            Ok(())
+        }
+
+        async fn get_counter(&mut self, _dbus: &DBus) -> Result<u64, (Error, String)> {
+            Ok(self.counter)
+        }
+
+        async fn set_counter(&mut self, _dbus: &DBus, value: u64) -> Result<(), (Error, String)> {
+            self.counter = value;
+            Ok(())
+        }
+
+        async fn get_name(&mut self, _dbus: &DBus) -> Result<String, (Error, String)> {
+            Ok(String::new())
+        }
+
+        async fn set_name(&mut self, _dbus: &DBus, _value: String) -> Result<(), (Error, String)> {
+            Ok(())
+        }
+
+        async fn get_object(&mut self, _dbus: &DBus) -> Result<String, (Error, String)> {
+            Ok(self.fired.clone())
+        }
+
+        async fn get_property_devices(&mut self, _dbus: &DBus) -> Result<Value, (Error, String)> {
+            Ok(Value::Boolean(false))
+        }
+    }
+
+    pub(super) fn handler() -> impl dbus_async::Handler {
+        CollisionsServer(Object::default())
+    }
+}
+
+#[test]
+fn codegen_collisions() {
+    // The generated code is compiled as the `collisions` module.
+    let xml = include_str!("codegen/collisions.xml");
+    assert_eq!(
+        generate(xml).unwrap(),
+        include_str!("codegen/collisions.rs")
+    );
+    let _handler = collisions::handler();
+}
+
+#[cfg(feature = "test-support")]
+#[test]
+fn codegen_collisions_mock_bus() {
+    use common::block_on;
+    use dbus_async::{
+        export::dbus_message_parser::value::Value, test_support::MockBus, Binder, DBusNameFlag,
+    };
+    use std::convert::TryInto;
+
+    const NAME: &str = "org.example.Service";
+    const OBJECT_PATH: &str = "/org/example/Collisions";
+
+    block_on(async {
+        let bus = MockBus::new();
+        let (service, _service_handle) = bus.connect().await.unwrap();
+        let (client, _client_handle) = bus.connect().await.unwrap();
+        service
+            .request_name(NAME.try_into().unwrap(), &DBusNameFlag::empty())
+            .await
+            .unwrap();
+        let _binding = collisions::handler()
+            .spawn(service, OBJECT_PATH.try_into().unwrap())
+            .unwrap();
+
+        // The generated proxy calls the generated server.
+        let proxy = collisions::CollisionsProxy::new(
+            client,
+            NAME.try_into().unwrap(),
+            OBJECT_PATH.try_into().unwrap(),
+        );
+        let reply = proxy
+            .call(
+                "object".to_string(),
+                1,
+                true,
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply, ("object".to_string(), 1));
+        let reply = proxy
+            .member(
+                "interface".to_string(),
+                "member".to_string(),
+                Value::Uint32(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply, "interface");
+        proxy.set_counter(2).await.unwrap();
+        assert_eq!(proxy.get_counter().await.unwrap(), 2);
+        // There is no reply to the `Fire` method, but the object handles the method calls in
+        // order.
+        proxy.fire("fired".to_string()).await.unwrap();
+        assert_eq!(proxy.get_object().await.unwrap(), "fired");
+    });
+}
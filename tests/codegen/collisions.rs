@@ -0,0 +1,492 @@
+// This file was generated by dbus-async-codegen. Do not edit.
+#[allow(unused_imports)]
+use std::convert::TryInto;
+
+/// A proxy for the `org.example.Collisions` interface.
+#[derive(Clone)]
+pub struct CollisionsProxy {
+    dbus: dbus_async::DBus,
+    destination: dbus_async::export::dbus_message_parser::value::Bus,
+    object_path: dbus_async::export::dbus_message_parser::value::ObjectPath,
+}
+
+#[allow(deprecated)]
+impl CollisionsProxy {
+    pub const INTERFACE: &'static str = "org.example.Collisions";
+
+    /// Create a proxy for the object on the given destination.
+    pub fn new(
+        dbus: dbus_async::DBus,
+        destination: dbus_async::export::dbus_message_parser::value::Bus,
+        object_path: dbus_async::export::dbus_message_parser::value::ObjectPath,
+    ) -> CollisionsProxy {
+        CollisionsProxy {
+            dbus,
+            destination,
+            object_path,
+        }
+    }
+
+    /// Call the `Call` method.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn call(&self, object_0: String, header_1: u32, body_2: bool, dbus_3: String, msg_4: String, reply_5: String, self_: String) -> dbus_async::DBusResult<(String, u32)> {
+        #[allow(unused_mut)]
+        let mut msg = dbus_async::export::dbus_message_parser::message::Message::method_call(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "Call".try_into().unwrap(),
+        );
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::String(object_0));
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::Uint32(header_1));
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::Boolean(body_2));
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::String(dbus_3));
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::String(msg_4));
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::String(reply_5));
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::String(self_));
+        let reply = self.dbus.call_checked(msg).await?;
+        match reply.get_body() {
+            [dbus_async::export::dbus_message_parser::value::Value::String(value_0), dbus_async::export::dbus_message_parser::value::Value::Uint32(value_1)] => Ok((value_0.clone(), *value_1)),
+            _ => Err(dbus_async::DBusError::InvalidReply(Box::new(reply))),
+        }
+    }
+
+    /// Call the `Member` method.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn member(&self, interface_0: String, member_1: String, value_2: dbus_async::export::dbus_message_parser::value::Value) -> dbus_async::DBusResult<String> {
+        #[allow(unused_mut)]
+        let mut msg = dbus_async::export::dbus_message_parser::message::Message::method_call(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "Member".try_into().unwrap(),
+        );
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::String(interface_0));
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::String(member_1));
+        msg.add_value(value_2);
+        let reply = self.dbus.call_checked(msg).await?;
+        match reply.get_body() {
+            [dbus_async::export::dbus_message_parser::value::Value::String(value_0)] => Ok(value_0.clone()),
+            _ => Err(dbus_async::DBusError::InvalidReply(Box::new(reply))),
+        }
+    }
+
+    /// Call the `Fire` method.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn fire(&self, object_0: String) -> dbus_async::DBusResult<()> {
+        #[allow(unused_mut)]
+        let mut msg = dbus_async::export::dbus_message_parser::message::Message::method_call(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "Fire".try_into().unwrap(),
+        );
+        msg.add_value(dbus_async::export::dbus_message_parser::value::Value::String(object_0));
+        let options = dbus_async::CallOptions::new().no_reply_expected(true);
+        self.dbus.send_with_options(msg, &options).await
+    }
+
+    /// Call the `GetDevices` method.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn get_devices(&self) -> dbus_async::DBusResult<dbus_async::export::dbus_message_parser::value::Value> {
+        #[allow(unused_mut)]
+        let mut msg = dbus_async::export::dbus_message_parser::message::Message::method_call(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "GetDevices".try_into().unwrap(),
+        );
+        let reply = self.dbus.call_checked(msg).await?;
+        match reply.get_body() {
+            [value_0] => Ok(value_0.clone()),
+            _ => Err(dbus_async::DBusError::InvalidReply(Box::new(reply))),
+        }
+    }
+
+    /// Call the `New` method.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn call_new(&self) -> dbus_async::DBusResult<()> {
+        #[allow(unused_mut)]
+        let mut msg = dbus_async::export::dbus_message_parser::message::Message::method_call(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "New".try_into().unwrap(),
+        );
+        let reply = self.dbus.call_checked(msg).await?;
+        match reply.get_body() {
+            [] => Ok(()),
+            _ => Err(dbus_async::DBusError::InvalidReply(Box::new(reply))),
+        }
+    }
+
+    /// Get the `Counter` property.
+    pub async fn get_counter(&self) -> dbus_async::DBusResult<u64> {
+        let msg = dbus_async::export::dbus_message_parser::message::Message::property_get(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "Counter",
+        );
+        let reply = self.dbus.call_checked(msg).await?;
+        if let [dbus_async::export::dbus_message_parser::value::Value::Variant(value)] = reply.get_body() {
+            if let dbus_async::export::dbus_message_parser::value::Value::Uint64(value) = value.as_ref() {
+                return Ok(*value);
+            }
+        }
+        Err(dbus_async::DBusError::InvalidReply(Box::new(reply)))
+    }
+    /// Set the `Counter` property.
+    pub async fn set_counter(&self, value: u64) -> dbus_async::DBusResult<()> {
+        let msg = dbus_async::export::dbus_message_parser::message::Message::property_set(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "Counter",
+            dbus_async::export::dbus_message_parser::value::Value::Uint64(value),
+        );
+        self.dbus.call_checked(msg).await?;
+        Ok(())
+    }
+
+    /// Get the `Name` property.
+    pub async fn get_name(&self) -> dbus_async::DBusResult<String> {
+        let msg = dbus_async::export::dbus_message_parser::message::Message::property_get(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "Name",
+        );
+        let reply = self.dbus.call_checked(msg).await?;
+        if let [dbus_async::export::dbus_message_parser::value::Value::Variant(value)] = reply.get_body() {
+            if let dbus_async::export::dbus_message_parser::value::Value::String(value) = value.as_ref() {
+                return Ok(value.clone());
+            }
+        }
+        Err(dbus_async::DBusError::InvalidReply(Box::new(reply)))
+    }
+    /// Set the `Name` property.
+    pub async fn set_name(&self, value: String) -> dbus_async::DBusResult<()> {
+        let msg = dbus_async::export::dbus_message_parser::message::Message::property_set(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "Name",
+            dbus_async::export::dbus_message_parser::value::Value::String(value),
+        );
+        self.dbus.call_checked(msg).await?;
+        Ok(())
+    }
+
+    /// Get the `Object` property.
+    pub async fn get_object(&self) -> dbus_async::DBusResult<String> {
+        let msg = dbus_async::export::dbus_message_parser::message::Message::property_get(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "Object",
+        );
+        let reply = self.dbus.call_checked(msg).await?;
+        if let [dbus_async::export::dbus_message_parser::value::Value::Variant(value)] = reply.get_body() {
+            if let dbus_async::export::dbus_message_parser::value::Value::String(value) = value.as_ref() {
+                return Ok(value.clone());
+            }
+        }
+        Err(dbus_async::DBusError::InvalidReply(Box::new(reply)))
+    }
+
+    /// Get the `Devices` property.
+    pub async fn get_property_devices(&self) -> dbus_async::DBusResult<dbus_async::export::dbus_message_parser::value::Value> {
+        let msg = dbus_async::export::dbus_message_parser::message::Message::property_get(
+            self.destination.clone(),
+            self.object_path.clone(),
+            Self::INTERFACE.try_into().unwrap(),
+            "Devices",
+        );
+        let reply = self.dbus.call_checked(msg).await?;
+        if let [dbus_async::export::dbus_message_parser::value::Value::Variant(value)] = reply.get_body() {
+            return Ok(value.as_ref().clone());
+        }
+        Err(dbus_async::DBusError::InvalidReply(Box::new(reply)))
+    }
+}
+
+/// The server side of the `org.example.Collisions` interface.
+#[dbus_async::export::async_trait::async_trait]
+pub trait Collisions: Send {
+    /// Handle the `Call` method.
+    #[allow(clippy::too_many_arguments)]
+    async fn call(&mut self, dbus: &dbus_async::DBus, object_0: String, header_1: u32, body_2: bool, dbus_3: String, msg_4: String, reply_5: String, self_: String) -> Result<(String, u32), (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Handle the `Member` method.
+    #[allow(clippy::too_many_arguments)]
+    async fn member(&mut self, dbus: &dbus_async::DBus, interface_0: String, member_1: String, value_2: dbus_async::export::dbus_message_parser::value::Value) -> Result<String, (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Handle the `Fire` method.
+    #[allow(clippy::too_many_arguments)]
+    async fn fire(&mut self, dbus: &dbus_async::DBus, object_0: String) -> Result<(), (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Handle the `GetDevices` method.
+    #[allow(clippy::too_many_arguments)]
+    async fn get_devices(&mut self, dbus: &dbus_async::DBus) -> Result<dbus_async::export::dbus_message_parser::value::Value, (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Handle the `New` method.
+    #[allow(clippy::too_many_arguments)]
+    async fn call_new(&mut self, dbus: &dbus_async::DBus) -> Result<(), (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Get the `Counter` property.
+    async fn get_counter(&mut self, dbus: &dbus_async::DBus) -> Result<u64, (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Set the `Counter` property.
+    async fn set_counter(&mut self, dbus: &dbus_async::DBus, value: u64) -> Result<(), (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Get the `Name` property.
+    async fn get_name(&mut self, dbus: &dbus_async::DBus) -> Result<String, (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Set the `Name` property.
+    async fn set_name(&mut self, dbus: &dbus_async::DBus, value: String) -> Result<(), (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Get the `Object` property.
+    async fn get_object(&mut self, dbus: &dbus_async::DBus) -> Result<String, (dbus_async::export::dbus_message_parser::value::Error, String)>;
+    /// Get the `Devices` property.
+    async fn get_property_devices(&mut self, dbus: &dbus_async::DBus) -> Result<dbus_async::export::dbus_message_parser::value::Value, (dbus_async::export::dbus_message_parser::value::Error, String)>;
+}
+
+/// Handle a method call of the `org.example.Collisions` interface.
+#[allow(deprecated, unused_variables)]
+pub async fn handle_collisions<T: Collisions>(object: &mut T, dbus: &dbus_async::DBus, header: dbus_async::export::dbus_message_parser::message::MessageHeader, body: Vec<dbus_async::export::dbus_message_parser::value::Value>, flags: dbus_async::export::dbus_message_parser::message::MessageFlags) -> dbus_async::DBusResult<()> {
+    fn unknown_interface(header: &dbus_async::export::dbus_message_parser::message::MessageHeader) -> dbus_async::export::dbus_message_parser::message::Message {
+        header.error(
+            "org.freedesktop.DBus.Error.UnknownInterface".try_into().unwrap(),
+            "Unknown interface".to_string(),
+        )
+    }
+
+    let member = if let Some(member) = header.get_member() {
+        member.clone()
+    } else {
+        return Ok(());
+    };
+    let interface = header.get_interface().map(|interface| interface.as_ref());
+    let no_reply = flags.contains(dbus_async::export::dbus_message_parser::message::MessageFlags::NO_REPLY_EXPECTED) || matches!((interface, member.as_ref()), (Some("org.example.Collisions") | None, "Fire"));
+    let response = match (interface, member.as_ref()) {
+        (Some("org.freedesktop.DBus.Properties"), "Get") => match body.as_slice() {
+            [dbus_async::export::dbus_message_parser::value::Value::String(interface), dbus_async::export::dbus_message_parser::value::Value::String(property)]
+            if interface == "org.example.Collisions" =>
+            {
+                match property.as_str() {
+                    "Counter" => match object.get_counter(dbus).await {
+                        Ok(value) => {
+                            let mut response = header.method_return().unwrap();
+                            response.add_value(dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new(dbus_async::export::dbus_message_parser::value::Value::Uint64(value))));
+                            response
+                        }
+                        Err((name, message)) => header.error(name, message),
+                    },
+                    "Name" => match object.get_name(dbus).await {
+                        Ok(value) => {
+                            let mut response = header.method_return().unwrap();
+                            response.add_value(dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new(dbus_async::export::dbus_message_parser::value::Value::String(value))));
+                            response
+                        }
+                        Err((name, message)) => header.error(name, message),
+                    },
+                    "Object" => match object.get_object(dbus).await {
+                        Ok(value) => {
+                            let mut response = header.method_return().unwrap();
+                            response.add_value(dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new(dbus_async::export::dbus_message_parser::value::Value::String(value))));
+                            response
+                        }
+                        Err((name, message)) => header.error(name, message),
+                    },
+                    "Devices" => match object.get_property_devices(dbus).await {
+                        Ok(value) => {
+                            let mut response = header.method_return().unwrap();
+                            response.add_value(dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new(value)));
+                            response
+                        }
+                        Err((name, message)) => header.error(name, message),
+                    },
+                    _ => header.unknown_property(property),
+                }
+            }
+            _ => unknown_interface(&header),
+        },
+        (Some("org.freedesktop.DBus.Properties"), "GetAll") => match body.as_slice() {
+            [dbus_async::export::dbus_message_parser::value::Value::String(interface)] if interface == "org.example.Collisions" => {
+                match collisions_get_all(object, dbus).await {
+                    Ok(properties) => {
+                        let mut response = header.method_return().unwrap();
+                        response.add_value(properties);
+                        response
+                    }
+                    Err((name, message)) => header.error(name, message),
+                }
+            }
+            _ => unknown_interface(&header),
+        },
+        (Some("org.freedesktop.DBus.Properties"), "Set") => match body.as_slice() {
+            [dbus_async::export::dbus_message_parser::value::Value::String(interface), dbus_async::export::dbus_message_parser::value::Value::String(property), dbus_async::export::dbus_message_parser::value::Value::Variant(value)]
+            if interface == "org.example.Collisions" =>
+            {
+                match (property.as_str(), value.as_ref()) {
+                    ("Counter", dbus_async::export::dbus_message_parser::value::Value::Uint64(value)) => match object.set_counter(dbus, *value).await {
+                        Ok(()) => {
+                            header.method_return().unwrap()
+                        }
+                        Err((name, message)) => header.error(name, message),
+                    },
+                    ("Counter", _) => header.invalid_args("Expected type: t".to_string()),
+                    ("Name", dbus_async::export::dbus_message_parser::value::Value::String(value)) => match object.set_name(dbus, value.clone()).await {
+                        Ok(()) => {
+                            let mut signal = dbus_async::export::dbus_message_parser::message::Message::signal(
+                                header.get_path().unwrap().clone(),
+                                "org.freedesktop.DBus.Properties".try_into().unwrap(),
+                                "PropertiesChanged".try_into().unwrap(),
+                            );
+                            signal.add_value(dbus_async::export::dbus_message_parser::value::Value::String("org.example.Collisions".to_string()));
+                            let changed = dbus_async::export::dbus_message_parser::value::Array::new(vec![dbus_async::export::dbus_message_parser::value::Value::DictEntry(Box::new((dbus_async::export::dbus_message_parser::value::Value::String("Name".to_string()), dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new(dbus_async::export::dbus_message_parser::value::Value::String(value.clone()))))))], dbus_async::export::dbus_message_parser::value::Type::DictEntry(Box::new((dbus_async::export::dbus_message_parser::value::Type::String, dbus_async::export::dbus_message_parser::value::Type::Variant)))).unwrap();
+                            signal.add_value(dbus_async::export::dbus_message_parser::value::Value::Array(changed));
+                            let invalidated = dbus_async::export::dbus_message_parser::value::Array::new(Vec::new(), dbus_async::export::dbus_message_parser::value::Type::String).unwrap();
+                            signal.add_value(dbus_async::export::dbus_message_parser::value::Value::Array(invalidated));
+                            dbus.send(signal).await?;
+                            header.method_return().unwrap()
+                        }
+                        Err((name, message)) => header.error(name, message),
+                    },
+                    ("Name", _) => header.invalid_args("Expected type: s".to_string()),
+                    ("Object", _) => header.error("org.freedesktop.DBus.Error.PropertyReadOnly".try_into().unwrap(), "Property is read-only".to_string()),
+                    ("Devices", _) => header.error("org.freedesktop.DBus.Error.PropertyReadOnly".try_into().unwrap(), "Property is read-only".to_string()),
+                    _ => header.unknown_property(property),
+                }
+            }
+            _ => unknown_interface(&header),
+        },
+        (Some("org.example.Collisions") | None, "Call") => match body.as_slice() {
+            [dbus_async::export::dbus_message_parser::value::Value::String(object_0), dbus_async::export::dbus_message_parser::value::Value::Uint32(header_1), dbus_async::export::dbus_message_parser::value::Value::Boolean(body_2), dbus_async::export::dbus_message_parser::value::Value::String(dbus_3), dbus_async::export::dbus_message_parser::value::Value::String(msg_4), dbus_async::export::dbus_message_parser::value::Value::String(reply_5), dbus_async::export::dbus_message_parser::value::Value::String(self_)] => {
+                match object.call(dbus, object_0.clone(), *header_1, *body_2, dbus_3.clone(), msg_4.clone(), reply_5.clone(), self_.clone()).await {
+                    Ok((response_7, header_8)) => {
+                        let mut response = header.method_return().unwrap();
+                        response.add_value(dbus_async::export::dbus_message_parser::value::Value::String(response_7));
+                        response.add_value(dbus_async::export::dbus_message_parser::value::Value::Uint32(header_8));
+                        response
+                    }
+                    Err((name, message)) => header.error(name, message),
+                }
+            }
+            _ => header.invalid_args("Expected arguments: subssss".to_string()),
+        },
+        (Some("org.example.Collisions") | None, "Member") => match body.as_slice() {
+            [dbus_async::export::dbus_message_parser::value::Value::String(interface_0), dbus_async::export::dbus_message_parser::value::Value::String(member_1), value_2] => {
+                match object.member(dbus, interface_0.clone(), member_1.clone(), value_2.clone()).await {
+                    Ok(r#type) => {
+                        let mut response = header.method_return().unwrap();
+                        response.add_value(dbus_async::export::dbus_message_parser::value::Value::String(r#type));
+                        response
+                    }
+                    Err((name, message)) => header.error(name, message),
+                }
+            }
+            _ => header.invalid_args("Expected arguments: ssv".to_string()),
+        },
+        (Some("org.example.Collisions") | None, "Fire") => match body.as_slice() {
+            [dbus_async::export::dbus_message_parser::value::Value::String(object_0)] => {
+                match object.fire(dbus, object_0.clone()).await {
+                    Ok(()) => {
+                        header.method_return().unwrap()
+                    }
+                    Err((name, message)) => header.error(name, message),
+                }
+            }
+            _ => header.invalid_args("Expected arguments: s".to_string()),
+        },
+        (Some("org.example.Collisions") | None, "GetDevices") => match body.as_slice() {
+            [] => {
+                match object.get_devices(dbus).await {
+                    Ok(devices) => {
+                        let mut response = header.method_return().unwrap();
+                        response.add_value(devices);
+                        response
+                    }
+                    Err((name, message)) => header.error(name, message),
+                }
+            }
+            _ => header.invalid_args("Expected arguments: ".to_string()),
+        },
+        (Some("org.example.Collisions") | None, "New") => match body.as_slice() {
+            [] => {
+                match object.call_new(dbus).await {
+                    Ok(()) => {
+                        header.method_return().unwrap()
+                    }
+                    Err((name, message)) => header.error(name, message),
+                }
+            }
+            _ => header.invalid_args("Expected arguments: ".to_string()),
+        },
+        (Some("org.example.Collisions") | None, _) => match header.unknown_member() {
+            Some(msg) => msg,
+            None => return Ok(()),
+        },
+        _ => unknown_interface(&header),
+    };
+    if no_reply {
+        return Ok(());
+    }
+    dbus.send(response).await
+}
+
+#[allow(deprecated)]
+async fn collisions_get_all<T: Collisions>(object: &mut T, dbus: &dbus_async::DBus) -> Result<dbus_async::export::dbus_message_parser::value::Value, (dbus_async::export::dbus_message_parser::value::Error, String)> {
+    #[allow(unused_mut)]
+    let mut properties = Vec::new();
+    let value = object.get_counter(dbus).await?;
+    properties.push(dbus_async::export::dbus_message_parser::value::Value::DictEntry(Box::new((
+        dbus_async::export::dbus_message_parser::value::Value::String("Counter".to_string()),
+        dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new(dbus_async::export::dbus_message_parser::value::Value::Uint64(value))),
+    ))));
+    let value = object.get_name(dbus).await?;
+    properties.push(dbus_async::export::dbus_message_parser::value::Value::DictEntry(Box::new((
+        dbus_async::export::dbus_message_parser::value::Value::String("Name".to_string()),
+        dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new(dbus_async::export::dbus_message_parser::value::Value::String(value))),
+    ))));
+    let value = object.get_object(dbus).await?;
+    properties.push(dbus_async::export::dbus_message_parser::value::Value::DictEntry(Box::new((
+        dbus_async::export::dbus_message_parser::value::Value::String("Object".to_string()),
+        dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new(dbus_async::export::dbus_message_parser::value::Value::String(value))),
+    ))));
+    let value = object.get_property_devices(dbus).await?;
+    properties.push(dbus_async::export::dbus_message_parser::value::Value::DictEntry(Box::new((
+        dbus_async::export::dbus_message_parser::value::Value::String("Devices".to_string()),
+        dbus_async::export::dbus_message_parser::value::Value::Variant(Box::new(value)),
+    ))));
+    let properties = dbus_async::export::dbus_message_parser::value::Array::new(properties, dbus_async::export::dbus_message_parser::value::Type::DictEntry(Box::new((dbus_async::export::dbus_message_parser::value::Type::String, dbus_async::export::dbus_message_parser::value::Type::Variant)))).unwrap();
+    Ok(dbus_async::export::dbus_message_parser::value::Value::Array(properties))
+}
+
+/// A [`Handler`](dbus_async::Handler) for an object, which implements the `org.example.Collisions` interface.
+pub struct CollisionsServer<T>(pub T);
+
+#[allow(deprecated)]
+#[dbus_async::export::async_trait::async_trait]
+impl<T: Collisions> dbus_async::Handler for CollisionsServer<T> {
+    async fn handle(&mut self, dbus: &dbus_async::DBus, msg: dbus_async::export::dbus_message_parser::message::Message) -> dbus_async::DBusResult<()> {
+        self.handle_with_flags(dbus, msg, dbus_async::export::dbus_message_parser::message::MessageFlags::empty()).await
+    }
+
+    async fn handle_with_flags(&mut self, dbus: &dbus_async::DBus, msg: dbus_async::export::dbus_message_parser::message::Message, flags: dbus_async::export::dbus_message_parser::message::MessageFlags) -> dbus_async::DBusResult<()> {
+        if dbus_async::export::dbus_message_parser::message::MessageType::MethodCall != msg.get_type() {
+            return Ok(());
+        }
+        match msg.split() {
+            Ok((header, body)) => handle_collisions(&mut self.0, dbus, header, body, flags).await,
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Emit the `Changed` signal.
+#[allow(clippy::too_many_arguments)]
+pub async fn emit_collisions_changed(dbus: &dbus_async::DBus, object_path: dbus_async::export::dbus_message_parser::value::ObjectPath, signal_0: String, object_path_1: dbus_async::export::dbus_message_parser::value::ObjectPath, dbus_2: u32) -> dbus_async::DBusResult<()> {
+    #[allow(unused_mut)]
+    let mut signal = dbus_async::export::dbus_message_parser::message::Message::signal(
+        object_path,
+        "org.example.Collisions".try_into().unwrap(),
+        "Changed".try_into().unwrap(),
+    );
+    signal.add_value(dbus_async::export::dbus_message_parser::value::Value::String(signal_0));
+    signal.add_value(dbus_async::export::dbus_message_parser::value::Value::ObjectPath(object_path_1));
+    signal.add_value(dbus_async::export::dbus_message_parser::value::Value::Uint32(dbus_2));
+    dbus.send(signal).await
+}
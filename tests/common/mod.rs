@@ -0,0 +1,135 @@
+#![allow(dead_code)]
+use bytes::Bytes;
+use dbus_message_parser::message::Message;
+use std::{
+    convert::TryInto,
+    fs::remove_file,
+    future::Future,
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::net::UnixListener,
+    process::id,
+    thread::{spawn, JoinHandle},
+};
+
+/// Create a call of the `Ping` method, which is sent to the servers of the tests.
+pub fn create_ping() -> Message {
+    Message::method_call(
+        "org.example.Peer".try_into().unwrap(),
+        "/org/example".try_into().unwrap(),
+        "org.freedesktop.DBus.Peer".try_into().unwrap(),
+        "Ping".try_into().unwrap(),
+    )
+}
+
+/// Create a signal with the given serial, which is sent by the servers of the tests.
+pub fn create_signal(serial: u32) -> Message {
+    let mut signal = Message::signal(
+        "/org/example".try_into().unwrap(),
+        "org.example.Interface".try_into().unwrap(),
+        "Changed".try_into().unwrap(),
+    );
+    signal.set_serial(serial);
+    signal
+}
+
+/// Run the future to completion on the runtime, which is selected by the cargo features.
+#[cfg(feature = "runtime-tokio")]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Runtime::new().unwrap().block_on(future)
+}
+
+/// Run the future to completion on the runtime, which is selected by the cargo features.
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    async_std::task::block_on(future)
+}
+
+/// Run the future to completion on the runtime, which is selected by the cargo features.
+#[cfg(all(
+    feature = "runtime-smol",
+    not(any(feature = "runtime-tokio", feature = "runtime-async-std"))
+))]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    smol::block_on(future)
+}
+
+/// Connect to the Unix Domain Stream socket with the runtime, which is selected by the cargo
+/// features.
+#[cfg(feature = "runtime-tokio")]
+pub async fn connect_unix(path: &str) -> dbus_async::TokioCompat<tokio::net::UnixStream> {
+    let stream = tokio::net::UnixStream::connect(path).await.unwrap();
+    dbus_async::TokioCompat::new(stream)
+}
+
+/// Connect to the Unix Domain Stream socket with the runtime, which is selected by the cargo
+/// features.
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-tokio")))]
+pub async fn connect_unix(path: &str) -> async_std::os::unix::net::UnixStream {
+    async_std::os::unix::net::UnixStream::connect(path)
+        .await
+        .unwrap()
+}
+
+/// Connect to the Unix Domain Stream socket with the runtime, which is selected by the cargo
+/// features.
+#[cfg(all(
+    feature = "runtime-smol",
+    not(any(feature = "runtime-tokio", feature = "runtime-async-std"))
+))]
+pub async fn connect_unix(path: &str) -> smol::net::unix::UnixStream {
+    smol::net::unix::UnixStream::connect(path).await.unwrap()
+}
+
+/// Start a server, which accepts one connection and answers the authentication, but never replies
+/// to a message. After the first bytes are received, the given messages are sent to the client.
+/// The thread returns the messages, which were received after the authentication.
+pub fn start_server(name: &str, messages: Vec<Message>) -> (String, JoinHandle<Vec<Message>>) {
+    let path = std::env::temp_dir().join(format!("dbus-async-{}-{}", name, id()));
+    let _ = remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let address = format!("unix:path={}", path.display());
+    let server = spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let _ = remove_file(&path);
+        let mut writer = stream.try_clone().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut zero = [0; 1];
+        reader.read_exact(&mut zero).unwrap();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let response = match line.trim_end() {
+                "AUTH" => "REJECTED EXTERNAL ANONYMOUS",
+                "NEGOTIATE_UNIX_FD" => "AGREE_UNIX_FD",
+                "BEGIN" => break,
+                _ => "OK 0123456789abcdef0123456789abcdef",
+            };
+            writer
+                .write_all(format!("{}\r\n", response).as_bytes())
+                .unwrap();
+        }
+        let mut received = Vec::new();
+        let mut first = [0; 1];
+        if reader.read(&mut first).unwrap() == 0 {
+            return Vec::new();
+        }
+        received.extend_from_slice(&first);
+        for msg in messages {
+            writer.write_all(&msg.encode().unwrap()).unwrap();
+        }
+        reader.read_to_end(&mut received).unwrap();
+        decode_messages(received)
+    });
+    (address, server)
+}
+
+fn decode_messages(received: Vec<u8>) -> Vec<Message> {
+    let mut bytes = Bytes::from(received);
+    let mut messages = Vec::new();
+    while !bytes.is_empty() {
+        let (msg, offset) = Message::decode(bytes.clone()).unwrap();
+        messages.push(msg);
+        bytes = bytes.slice(offset..);
+    }
+    messages
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::block_on;
+use dbus_async::{
+    daemon::BusDaemon, marshal::FromArgs, DBus, DBusBuilder, DBusError, DBusNameFlag, StandardError,
+};
+use dbus_message_parser::{message::Message, value::Value};
+use futures::{channel::mpsc::channel, future::join, StreamExt};
+use std::{
+    convert::TryInto,
+    fs::{metadata, remove_file},
+    os::unix::{fs::MetadataExt, net::UnixListener},
+    path::Path,
+    process::id,
+};
+
+const BUS_NAME: &str = "org.freedesktop.DBus";
+const NAME: &str = "org.example.Service";
+
+fn socket_path(name: &str) -> String {
+    format!("/tmp/dbus-async-daemon-{}-{}", id(), name)
+}
+
+async fn request_name(dbus: &DBus) {
+    dbus.request_name(NAME.try_into().unwrap(), &DBusNameFlag::empty())
+        .await
+        .unwrap();
+}
+
+async fn call_bus<R: FromArgs>(dbus: &DBus, member: &str, name: &str) -> Result<R, DBusError> {
+    dbus.call_method(
+        BUS_NAME.try_into().unwrap(),
+        "/org/freedesktop/DBus".try_into().unwrap(),
+        BUS_NAME.try_into().unwrap(),
+        member.try_into().unwrap(),
+        (name.to_string(),),
+    )
+    .await
+}
+
+async fn get_connection_unix_user(dbus: &DBus) -> Result<u32, DBusError> {
+    let (uid,): (u32,) = call_bus(dbus, "GetConnectionUnixUser", NAME).await?;
+    Ok(uid)
+}
+
+#[test]
+fn unix() {
+    block_on(async {
+        let path = socket_path("unix");
+        let mut daemon = BusDaemon::new();
+        let address = daemon.listen(&format!("unix:path={}", path)).await.unwrap();
+        assert!(address.starts_with(&format!("unix:path={},guid=", path)));
+        let (service, _service_handle) =
+            DBusBuilder::new().address(&address).build().await.unwrap();
+        let (client, _client_handle) = DBusBuilder::new().address(&address).build().await.unwrap();
+
+        // The socket was created by this process, so the owner of the socket is the UID of the
+        // connections.
+        request_name(&service).await;
+        let uid = metadata(&path).unwrap().uid();
+        assert_eq!(get_connection_unix_user(&client).await.unwrap(), uid);
+
+        let (sender, mut receiver) = channel(8);
+        service
+            .add_method_call("/org/example".try_into().unwrap(), sender)
+            .unwrap();
+        let mut msg = Message::method_call(
+            NAME.try_into().unwrap(),
+            "/org/example".try_into().unwrap(),
+            "org.example.Interface".try_into().unwrap(),
+            "Echo".try_into().unwrap(),
+        );
+        msg.add_value(Value::String("Hello".to_string()));
+        let serve = async {
+            let msg = receiver.next().await.unwrap();
+            let mut reply = msg.method_return().unwrap();
+            reply.add_value(msg.get_body()[0].clone());
+            service.send(reply).await.unwrap();
+        };
+        let (reply, _) = join(client.call(msg), serve).await;
+        assert_eq!(
+            reply.unwrap().get_body(),
+            &[Value::String("Hello".to_string())]
+        );
+
+        drop(daemon);
+        assert!(!Path::new(&path).exists());
+    });
+}
+
+#[test]
+fn tcp() {
+    block_on(async {
+        let mut daemon = BusDaemon::new();
+        let address = daemon.listen("tcp:host=127.0.0.1,port=0").await.unwrap();
+        assert!(address.starts_with("tcp:host=127.0.0.1,port="));
+        assert!(!address.contains("port=0,"));
+        let (dbus, _connection_handle) =
+            DBusBuilder::new().address(&address).build().await.unwrap();
+
+        // The UID of a TCP connection is unknown, because the connection is anonymous.
+        request_name(&dbus).await;
+        let error = get_connection_unix_user(&dbus).await.unwrap_err();
+        assert!(matches!(error.to_typed(), Some(StandardError::Failed(_))));
+    });
+}
+
+#[test]
+fn unix_listener() {
+    block_on(async {
+        let path = socket_path("unix_listener");
+        let unix_listener = UnixListener::bind(&path).unwrap();
+        let mut daemon = BusDaemon::new();
+        daemon.listen_with_unix_listener(unix_listener).unwrap();
+        let address = format!("unix:path={}", path);
+        let (dbus, _connection_handle) =
+            DBusBuilder::new().address(&address).build().await.unwrap();
+        let (owner,): (String,) = call_bus(&dbus, "GetNameOwner", BUS_NAME).await.unwrap();
+        assert_eq!(owner, BUS_NAME);
+        remove_file(&path).unwrap();
+    });
+}
+
+#[test]
+fn bus_name() {
+    block_on(async {
+        let mut daemon = BusDaemon::new();
+        let address = daemon.listen("tcp:host=127.0.0.1,port=0").await.unwrap();
+        let (dbus, _connection_handle) =
+            DBusBuilder::new().address(&address).build().await.unwrap();
+
+        // The name of the bus is always owned by the bus itself.
+        let (has_owner,): (bool,) = call_bus(&dbus, "NameHasOwner", BUS_NAME).await.unwrap();
+        assert!(has_owner);
+        let (owner,): (String,) = call_bus(&dbus, "GetNameOwner", BUS_NAME).await.unwrap();
+        assert_eq!(owner, BUS_NAME);
+    });
+}
+
+#[test]
+fn not_supported() {
+    block_on(async {
+        let mut daemon = BusDaemon::new();
+        assert!(daemon.listen("unix:abstract=dbus-async").await.is_err());
+        assert!(daemon.listen("invalid").await.is_err());
+    });
+}
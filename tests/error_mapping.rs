@@ -0,0 +1,87 @@
+use dbus_async::{DBusError, DBusErrorReply, ErrorMapping, StandardError};
+use dbus_message_parser::value::Error as ErrorName;
+use std::convert::TryInto;
+
+#[derive(Debug, PartialEq, Eq)]
+enum CalculatorError {
+    Overflow,
+    DivisionByZero(String),
+}
+
+impl ErrorMapping for CalculatorError {
+    fn from_error(name: &ErrorName, message: Option<&str>) -> Option<Self> {
+        match name.as_ref() {
+            "org.example.Calculator.Overflow" => Some(CalculatorError::Overflow),
+            "org.example.Calculator.DivisionByZero" => Some(CalculatorError::DivisionByZero(
+                message.unwrap_or_default().to_string(),
+            )),
+            _ => None,
+        }
+    }
+
+    fn to_error(&self) -> (ErrorName, String) {
+        match self {
+            CalculatorError::Overflow => (
+                "org.example.Calculator.Overflow".try_into().unwrap(),
+                "The result is too large".to_string(),
+            ),
+            CalculatorError::DivisionByZero(message) => (
+                "org.example.Calculator.DivisionByZero".try_into().unwrap(),
+                message.clone(),
+            ),
+        }
+    }
+}
+
+fn method_error(name: &str, message: Option<&str>) -> DBusError {
+    DBusError::MethodError {
+        name: name.try_into().unwrap(),
+        message: message.map(|message| message.to_string()),
+    }
+}
+
+#[test]
+fn standard_error() {
+    let error = method_error(
+        "org.freedesktop.DBus.Error.ServiceUnknown",
+        Some("The name is not activatable"),
+    );
+    assert_eq!(
+        error.to_typed::<StandardError>(),
+        Some(StandardError::ServiceUnknown(
+            "The name is not activatable".to_string()
+        ))
+    );
+    let error = method_error("org.freedesktop.DBus.Error.AccessDenied", None);
+    assert_eq!(
+        error.to_typed::<StandardError>(),
+        Some(StandardError::AccessDenied(String::new()))
+    );
+    let error = method_error("org.example.Calculator.Overflow", None);
+    assert_eq!(error.to_typed::<StandardError>(), None);
+    assert_eq!(DBusError::Close.to_typed::<StandardError>(), None);
+
+    let (name, message) = StandardError::UnknownMethod("No such method".to_string()).to_error();
+    assert_eq!(name.as_ref(), "org.freedesktop.DBus.Error.UnknownMethod");
+    assert_eq!(message, "No such method");
+}
+
+#[test]
+fn application_error() {
+    let error = method_error("org.example.Calculator.DivisionByZero", Some("1 / 0"));
+    let typed = error.to_typed::<CalculatorError>();
+    assert_eq!(
+        typed,
+        Some(CalculatorError::DivisionByZero("1 / 0".to_string()))
+    );
+
+    let reply = DBusErrorReply::from_typed(&CalculatorError::Overflow);
+    assert_eq!(reply.get_name().as_ref(), "org.example.Calculator.Overflow");
+    assert_eq!(reply.get_message(), "The result is too large");
+
+    let reply: DBusErrorReply = StandardError::InvalidArgs("Expected a string".to_string()).into();
+    assert_eq!(
+        reply,
+        DBusErrorReply::invalid_args("Expected a string".to_string())
+    );
+}
@@ -0,0 +1,508 @@
+mod common;
+
+use async_trait::async_trait;
+use common::{block_on, start_server};
+use dbus_async::{
+    BindOptions, Binder, CallOrdering, ConcurrentBinder, DBus, DBusBuilder, DBusError,
+    DBusErrorReply, DBusResult, ErrorPolicy, Handler, Reply, ReplyHandler, ReplyResult,
+    SharedHandler,
+};
+use dbus_message_parser::{
+    message::{Message, MessageFlags, MessageHeader, MessageHeaderFields, MessageType},
+    value::Value,
+};
+use futures::{
+    channel::{
+        mpsc::{channel, unbounded, Receiver, UnboundedSender},
+        oneshot::{channel as oneshot_channel, Receiver as OneshotReceiver},
+    },
+    future::{join, poll_fn, select, Either, Shared},
+    FutureExt, StreamExt,
+};
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::Poll,
+};
+
+/// A handler, which replies to `Ok`, replies to `ReplyAndFail` and returns an error, replies to
+/// another peer with the same serial on `ReplyOtherAndFail` and returns an error and panics on
+/// every other method call.
+struct TestHandler;
+
+#[async_trait]
+impl Handler for TestHandler {
+    async fn handle(&mut self, dbus: &DBus, msg: Message) -> DBusResult<()> {
+        match msg.get_member().unwrap().as_ref() {
+            "Ok" => dbus.send(msg.method_return().unwrap()).await,
+            "ReplyAndFail" => {
+                dbus.send(msg.method_return().unwrap()).await?;
+                Err(DBusError::Close)
+            }
+            "ReplyOtherAndFail" => {
+                let serial = msg.get_serial();
+                let other =
+                    create_method_call_with_flags(":1.99", "Ok", serial, MessageFlags::empty());
+                dbus.send(other.method_return().unwrap()).await?;
+                Err(DBusError::Close)
+            }
+            member => panic!("{}", member),
+        }
+    }
+}
+
+fn create_method_call(member: &str, serial: u32) -> Message {
+    let mut msg = Message::method_call(
+        "org.example.Service".try_into().unwrap(),
+        "/org/example".try_into().unwrap(),
+        "org.example.Interface".try_into().unwrap(),
+        member.try_into().unwrap(),
+    );
+    msg.set_serial(serial);
+    msg
+}
+
+/// Connect to a test server, run the function with the connection and return the messages, which
+/// were received by the server.
+fn run<F, R>(name: &str, f: F) -> Vec<Message>
+where
+    F: FnOnce(DBus) -> R,
+    R: Future<Output = ()>,
+{
+    let (address, server) = start_server(name, Vec::new());
+    block_on(async {
+        let (dbus, connection_handle) = DBusBuilder::new()
+            .address(&address)
+            .hello(false)
+            .build()
+            .await
+            .unwrap();
+        f(dbus.clone()).await;
+        dbus.close().unwrap();
+        connection_handle.await.unwrap();
+    });
+    server.join().unwrap()
+}
+
+/// Create a closed channel, which contains the messages and their flags.
+fn create_receiver_with_flags(
+    msgs: Vec<(Message, MessageFlags)>,
+) -> Receiver<(Message, MessageFlags)> {
+    let (mut sender, receiver) = channel(msgs.len());
+    for msg in msgs {
+        sender.try_send(msg).unwrap();
+    }
+    receiver
+}
+
+/// Create a closed channel, which contains the messages.
+fn create_receiver(msgs: Vec<Message>) -> Receiver<Message> {
+    let (mut sender, receiver) = channel(msgs.len());
+    for msg in msgs {
+        sender.try_send(msg).unwrap();
+    }
+    receiver
+}
+
+/// Handle the method calls with the [`TestHandler`] and return the result of the binding and the
+/// messages, which were sent by the handler or by the binding.
+fn handle(name: &str, msgs: Vec<Message>, options: BindOptions) -> (DBusResult<()>, Vec<Message>) {
+    let mut result = None;
+    let received = run(name, |dbus| async {
+        let receiver = create_receiver(msgs);
+        result = Some(
+            TestHandler
+                .bind_by_receiver_with_options(dbus, receiver, options)
+                .await,
+        );
+    });
+    (result.unwrap(), received)
+}
+
+#[test]
+fn reply_and_fail() {
+    let options = BindOptions::new().error_policy(ErrorPolicy::Reply);
+    let msgs = vec![create_method_call("ReplyAndFail", 1)];
+    let (result, received) = handle("handler_reply_and_fail", msgs, options);
+    assert!(result.is_ok());
+    // The handler already replied, so there is no error reply.
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].get_type(), MessageType::MethodReturn);
+    assert_eq!(received[0].get_reply_serial(), Some(1));
+}
+
+#[test]
+fn reply_other_and_fail() {
+    let options = BindOptions::new().error_policy(ErrorPolicy::Reply);
+    let msgs = vec![create_method_call("ReplyOtherAndFail", 1)];
+    let (result, received) = handle("handler_reply_other_and_fail", msgs, options);
+    assert!(result.is_ok());
+    // The reply to the other peer is not a reply to the caller, so the caller gets an error reply.
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].get_type(), MessageType::MethodReturn);
+    assert_eq!(received[0].get_destination().unwrap().as_ref(), ":1.99");
+    assert_eq!(received[1].get_type(), MessageType::Error);
+    assert_eq!(received[1].get_reply_serial(), Some(1));
+    assert!(received[1].get_destination().is_none());
+}
+
+#[test]
+fn panic_reply() {
+    let options = BindOptions::new().error_policy(ErrorPolicy::Reply);
+    let msgs = vec![create_method_call("Panic", 1), create_method_call("Ok", 2)];
+    let (result, received) = handle("handler_panic_reply", msgs, options);
+    assert!(result.is_ok());
+    // The binding replies with an error to the panic and handles the next message.
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].get_type(), MessageType::Error);
+    assert_eq!(received[0].get_reply_serial(), Some(1));
+    assert_eq!(
+        received[0].get_error_name().unwrap().as_ref(),
+        "org.freedesktop.DBus.Error.Failed"
+    );
+    assert_eq!(received[1].get_type(), MessageType::MethodReturn);
+    assert_eq!(received[1].get_reply_serial(), Some(2));
+}
+
+#[test]
+fn panic_stop() {
+    let options = BindOptions::new().error_policy(ErrorPolicy::Stop);
+    let msgs = vec![create_method_call("Panic", 1), create_method_call("Ok", 2)];
+    let (result, received) = handle("handler_panic_stop", msgs, options);
+    match result {
+        Err(DBusError::HandlerPanic(message)) => assert_eq!(message, "Panic"),
+        result => panic!("{:?}", result),
+    }
+    // The binding stops without a reply and the next message is not handled.
+    assert!(received.is_empty());
+}
+
+#[test]
+fn shutdown_handle() {
+    run("handler_shutdown_handle", |dbus| async move {
+        let mut options = BindOptions::new();
+        let shutdown_handle = options.shutdown_handle();
+        // The sender is kept, so the binding only ends through the handle.
+        let (mut sender, receiver) = channel(1);
+        sender.try_send(create_method_call("Ok", 1)).unwrap();
+        let binding = TestHandler.bind_by_receiver_with_options(dbus, receiver, options);
+        shutdown_handle.shutdown();
+        assert!(binding.await.is_ok());
+    });
+}
+
+/// List the names of the bound objects under `/org/example`.
+async fn list_objects(dbus: &DBus) -> HashSet<String> {
+    dbus.list_method_call("/org/example".try_into().unwrap())
+        .await
+        .unwrap()
+}
+
+#[test]
+fn binding_drop() {
+    run("handler_binding_drop", |dbus| async move {
+        let binding = TestHandler
+            .spawn(dbus.clone(), "/org/example/Object".try_into().unwrap())
+            .unwrap();
+        assert!(list_objects(&dbus).await.contains("Object"));
+        drop(binding);
+        assert!(list_objects(&dbus).await.is_empty());
+    });
+}
+
+#[test]
+fn binding_join() {
+    run("handler_binding_join", |dbus| async move {
+        let mut options = BindOptions::new();
+        let shutdown_handle = options.shutdown_handle();
+        let binding = TestHandler
+            .spawn_with_options(
+                dbus.clone(),
+                "/org/example/Object".try_into().unwrap(),
+                options,
+            )
+            .unwrap();
+        assert!(list_objects(&dbus).await.contains("Object"));
+        shutdown_handle.shutdown();
+        assert!(binding.join().await.is_ok());
+        assert!(list_objects(&dbus).await.is_empty());
+
+        // Dropping the future of `join` stops the binding.
+        let binding = TestHandler
+            .spawn(dbus.clone(), "/org/example/Object".try_into().unwrap())
+            .unwrap();
+        assert!(list_objects(&dbus).await.contains("Object"));
+        assert!(binding.join().now_or_never().is_none());
+        assert!(list_objects(&dbus).await.is_empty());
+    });
+}
+
+/// Yield once to the runtime.
+async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|context| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            context.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// A handler, which counts the messages, which are handled at the same time. The messages are
+/// finished, after the gate is opened, except `Fail`, which fails immediately.
+struct GateHandler {
+    running: AtomicUsize,
+    max_running: AtomicUsize,
+    handled: AtomicUsize,
+    started: UnboundedSender<()>,
+    gate: Shared<OneshotReceiver<()>>,
+}
+
+#[async_trait]
+impl SharedHandler for GateHandler {
+    async fn handle(&self, _dbus: &DBus, msg: Message) -> DBusResult<()> {
+        if msg.get_member().unwrap().as_ref() == "Fail" {
+            return Err(DBusError::Close);
+        }
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_running.fetch_max(running, Ordering::SeqCst);
+        self.started.unbounded_send(()).unwrap();
+        self.gate.clone().await.unwrap();
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        self.handled.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[test]
+fn concurrency_limit() {
+    let (started, mut started_receiver) = unbounded();
+    let (gate_sender, gate) = oneshot_channel();
+    let handler = Arc::new(GateHandler {
+        running: AtomicUsize::new(0),
+        max_running: AtomicUsize::new(0),
+        handled: AtomicUsize::new(0),
+        started,
+        gate: gate.shared(),
+    });
+    run("handler_concurrency_limit", |dbus| async {
+        let msgs = (1..=5).map(|serial| create_method_call("Ok", serial));
+        let receiver = create_receiver(msgs.collect());
+        let options = BindOptions::new().concurrency_limit(2);
+        let binding = handler
+            .clone()
+            .bind_concurrent_by_receiver_with_options(dbus, receiver, options);
+        let release = async {
+            // The binding does not receive more messages, until one of them is finished.
+            started_receiver.next().await.unwrap();
+            started_receiver.next().await.unwrap();
+            for _ in 0..16 {
+                yield_now().await;
+            }
+            assert!(started_receiver.try_recv().is_err());
+            gate_sender.send(()).unwrap();
+        };
+        let (result, ()) = join(binding, release).await;
+        assert!(result.is_ok());
+    });
+    assert_eq!(handler.max_running.load(Ordering::SeqCst), 2);
+    assert_eq!(handler.handled.load(Ordering::SeqCst), 5);
+}
+
+#[test]
+fn concurrent_stop() {
+    let (started, mut started_receiver) = unbounded();
+    let (gate_sender, gate) = oneshot_channel();
+    let handler = Arc::new(GateHandler {
+        running: AtomicUsize::new(0),
+        max_running: AtomicUsize::new(0),
+        handled: AtomicUsize::new(0),
+        started,
+        gate: gate.shared(),
+    });
+    run("handler_concurrent_stop", |dbus| async {
+        let msgs = vec![create_method_call("Ok", 1), create_method_call("Fail", 2)];
+        let receiver = create_receiver(msgs);
+        let options = BindOptions::new().error_policy(ErrorPolicy::Stop);
+        let binding = handler
+            .clone()
+            .bind_concurrent_by_receiver_with_options(dbus, receiver, options)
+            .map(|result| (result, handler.handled.load(Ordering::SeqCst)));
+        let release = async {
+            started_receiver.next().await.unwrap();
+            for _ in 0..16 {
+                yield_now().await;
+            }
+            gate_sender.send(()).unwrap();
+        };
+        let ((result, handled), ()) = join(binding, release).await;
+        assert!(matches!(result, Err(DBusError::Close)));
+        // The binding waits until the other method call is handled.
+        assert_eq!(handled, 1);
+    });
+}
+
+#[test]
+fn concurrent_cancel() {
+    let (started, mut started_receiver) = unbounded();
+    let (_gate_sender, gate) = oneshot_channel();
+    let handler = Arc::new(GateHandler {
+        running: AtomicUsize::new(0),
+        max_running: AtomicUsize::new(0),
+        handled: AtomicUsize::new(0),
+        started,
+        gate: gate.shared(),
+    });
+    run("handler_concurrent_cancel", |dbus| async {
+        let msgs = vec![create_method_call("Ok", 1), create_method_call("Ok", 2)];
+        let receiver = create_receiver(msgs);
+        let binding = handler.clone().bind_concurrent_by_receiver_with_options(
+            dbus,
+            receiver,
+            BindOptions::new(),
+        );
+        let started = async {
+            started_receiver.next().await.unwrap();
+            started_receiver.next().await.unwrap();
+        };
+        match select(binding, started.boxed()).await {
+            Either::Left(_) => panic!("the binding is finished"),
+            Either::Right(((), binding)) => drop(binding),
+        }
+        // Dropping the binding cancels the method calls, which are handled.
+        assert_eq!(Arc::strong_count(&handler), 1);
+    });
+    assert_eq!(handler.running.load(Ordering::SeqCst), 2);
+    assert_eq!(handler.handled.load(Ordering::SeqCst), 0);
+}
+
+/// A handler, which records the order of the messages and checks that the messages of the same
+/// sender are not handled at the same time.
+#[derive(Default)]
+struct OrderHandler {
+    running: Mutex<HashSet<String>>,
+    handled: Mutex<Vec<(String, u32)>>,
+}
+
+#[async_trait]
+impl SharedHandler for OrderHandler {
+    async fn handle(&self, _dbus: &DBus, msg: Message) -> DBusResult<()> {
+        let sender = msg.get_sender().unwrap().to_string();
+        assert!(self.running.lock().unwrap().insert(sender.clone()));
+        for _ in 0..4 {
+            yield_now().await;
+        }
+        assert!(self.running.lock().unwrap().remove(&sender));
+        let serial = msg.get_serial();
+        self.handled.lock().unwrap().push((sender, serial));
+        Ok(())
+    }
+}
+
+fn create_method_call_with_flags(
+    sender: &str,
+    member: &str,
+    serial: u32,
+    flags: MessageFlags,
+) -> Message {
+    let fields = MessageHeaderFields {
+        path: Some("/org/example".try_into().unwrap()),
+        member: Some(member.try_into().unwrap()),
+        sender: Some(sender.try_into().unwrap()),
+        ..Default::default()
+    };
+    let header =
+        MessageHeader::new(true, MessageType::MethodCall, flags, 1, serial, fields).unwrap();
+    Message::new(header, Vec::new())
+}
+
+fn create_method_call_from(sender: &str, serial: u32) -> Message {
+    create_method_call_with_flags(sender, "Ok", serial, MessageFlags::empty())
+}
+
+#[test]
+fn per_sender_ordering() {
+    let handler = Arc::new(OrderHandler::default());
+    run("handler_per_sender_ordering", |dbus| async {
+        let msgs = (1..=8).flat_map(|serial| {
+            vec![
+                create_method_call_from(":1.1", serial),
+                create_method_call_from(":1.2", serial),
+            ]
+        });
+        let receiver = create_receiver(msgs.collect());
+        let options = BindOptions::new().ordering(CallOrdering::PerSender);
+        let result = handler
+            .clone()
+            .bind_concurrent_by_receiver_with_options(dbus, receiver, options)
+            .await;
+        assert!(result.is_ok());
+    });
+    let handled = handler.handled.lock().unwrap();
+    assert_eq!(handled.len(), 16);
+    for sender in [":1.1", ":1.2"].iter() {
+        let serials: Vec<u32> = handled
+            .iter()
+            .filter(|(s, _)| s == sender)
+            .map(|(_, serial)| *serial)
+            .collect();
+        assert_eq!(serials, (1..=8).collect::<Vec<u32>>());
+    }
+}
+
+/// A reply handler, which fails on `Fail` and replies to every other method call.
+struct TestReplyHandler;
+
+#[async_trait]
+impl ReplyHandler for TestReplyHandler {
+    async fn handle(
+        &mut self,
+        _dbus: &DBus,
+        header: &MessageHeader,
+        body: Vec<Value>,
+    ) -> ReplyResult {
+        match header.get_member().unwrap().as_ref() {
+            "Fail" => Err(DBusErrorReply::failed("Fail".to_string())),
+            _ => Ok(Reply::Body(body)),
+        }
+    }
+}
+
+#[test]
+fn reply_handler_no_reply_expected() {
+    let mut result = None;
+    let received = run("handler_reply_handler_no_reply_expected", |dbus| async {
+        let msgs = vec![
+            ("Ok", 1, MessageFlags::NO_REPLY_EXPECTED),
+            ("Fail", 2, MessageFlags::NO_REPLY_EXPECTED),
+            ("Ok", 3, MessageFlags::empty()),
+            ("Fail", 4, MessageFlags::empty()),
+        ];
+        let msgs = msgs.into_iter().map(|(member, serial, flags)| {
+            let msg = create_method_call_with_flags(":1.1", member, serial, flags);
+            (msg, flags)
+        });
+        let receiver = create_receiver_with_flags(msgs.collect());
+        result = Some(
+            TestReplyHandler
+                .bind_by_stream_with_options(dbus, receiver, BindOptions::new())
+                .await,
+        );
+    });
+    assert!(result.unwrap().is_ok());
+    // Only the method calls, which expect a reply, get a reply.
+    assert_eq!(received.len(), 2);
+    assert_eq!(received[0].get_type(), MessageType::MethodReturn);
+    assert_eq!(received[0].get_reply_serial(), Some(3));
+    assert_eq!(received[1].get_type(), MessageType::Error);
+    assert_eq!(received[1].get_reply_serial(), Some(4));
+}
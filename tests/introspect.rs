@@ -0,0 +1,66 @@
+use dbus_async::introspect::{Access, Direction, Node};
+
+const XML: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<node name="/org/example/Object">
+  <interface name="org.example.Interface">
+    <method name="Frobate">
+      <arg name="foo" type="i" direction="in"/>
+      <arg name="bar" type="s" direction="out"/>
+      <arg name="baz" type="a{us}" direction="out"/>
+      <annotation name="org.freedesktop.DBus.Deprecated" value="true"/>
+    </method>
+    <method name="Bazify">
+      <arg name="bar" type="(iiu)" direction="in"/>
+      <arg name="bar" type="v" direction="out"/>
+    </method>
+    <signal name="Changed">
+      <arg name="new_value" type="b"/>
+    </signal>
+    <property name="Bar" type="y" access="readwrite">
+      <annotation name="org.freedesktop.DBus.Property.EmitsChangedSignal" value="false"/>
+    </property>
+  </interface>
+  <node name="child_of_sample_object"/>
+  <node name="another_child_of_sample_object"/>
+</node>"#;
+
+#[test]
+fn introspect_decode() {
+    let node = Node::decode(XML).unwrap();
+    assert_eq!(node.name.as_deref(), Some("/org/example/Object"));
+    assert_eq!(node.nodes.len(), 2);
+    assert!(node.get_node("child_of_sample_object").is_some());
+
+    let interface = node.get_interface("org.example.Interface").unwrap();
+    assert_eq!(interface.methods.len(), 2);
+    let method = &interface.methods[0];
+    assert_eq!(method.name, "Frobate");
+    assert_eq!(method.args[2].r#type, "a{us}");
+    assert_eq!(method.args[2].direction, Some(Direction::Out));
+    assert_eq!(
+        method.annotations[0].name,
+        "org.freedesktop.DBus.Deprecated"
+    );
+
+    let signal = &interface.signals[0];
+    assert_eq!(signal.args[0].direction, None);
+
+    let property = &interface.properties[0];
+    assert_eq!(property.access, Access::ReadWrite);
+    assert_eq!(property.annotations[0].value, "false");
+}
+
+#[test]
+fn introspect_encode_decode() {
+    let node = Node::decode(XML).unwrap();
+    let xml = node.encode();
+    assert_eq!(Node::decode(&xml).unwrap(), node);
+}
+
+#[test]
+fn introspect_decode_error() {
+    assert!(Node::decode("<interface name=\"org.example\"/>").is_err());
+    assert!(Node::decode("<node><interface/></node>").is_err());
+    assert!(Node::decode("<node><interface name=\"a\">").is_err());
+}
@@ -0,0 +1,77 @@
+use dbus_async::marshal::{FromArgs, FromDBus, MarshalError, ToArgs, ToDBus, Variant};
+use dbus_message_parser::value::{ObjectPath, Type, Value};
+use std::{collections::HashMap, convert::TryInto};
+
+#[test]
+fn marshal_basic() {
+    assert_eq!(1u8.to_dbus().unwrap(), Value::Byte(1));
+    assert_eq!("text".to_dbus().unwrap(), Value::String("text".to_string()));
+    assert_eq!(i32::from_dbus(Value::Int32(-1)).unwrap(), -1);
+    let object_path: ObjectPath = "/org/example".try_into().unwrap();
+    let value = object_path.clone().to_dbus().unwrap();
+    assert_eq!(ObjectPath::from_dbus(value).unwrap(), object_path);
+    assert!(matches!(
+        u32::from_dbus(Value::Int32(1)),
+        Err(MarshalError::TypeMismatch(Type::Uint32, Value::Int32(1)))
+    ));
+}
+
+#[test]
+fn marshal_container() {
+    let array = vec![1u32, 2, 3];
+    let value = array.clone().to_dbus().unwrap();
+    assert_eq!(value.get_type().unwrap(), "au".try_into().unwrap());
+    assert_eq!(Vec::<u32>::from_dbus(value.clone()).unwrap(), array);
+    assert!(Vec::<i32>::from_dbus(value).is_err());
+
+    let empty: Vec<String> = Vec::new();
+    let value = empty.to_dbus().unwrap();
+    assert_eq!(value.get_type().unwrap(), "as".try_into().unwrap());
+
+    let mut map = HashMap::new();
+    map.insert("a".to_string(), 1i64);
+    map.insert("b".to_string(), 2i64);
+    let value = map.clone().to_dbus().unwrap();
+    assert_eq!(value.get_type().unwrap(), "a{sx}".try_into().unwrap());
+    assert_eq!(HashMap::<String, i64>::from_dbus(value).unwrap(), map);
+
+    let tuple = (1u8, "text".to_string(), vec![true]);
+    let value = tuple.clone().to_dbus().unwrap();
+    assert_eq!(value.get_type().unwrap(), "(ysab)".try_into().unwrap());
+    assert_eq!(
+        <(u8, String, Vec<bool>)>::from_dbus(value.clone()).unwrap(),
+        tuple
+    );
+    assert!(matches!(
+        <(u8, String)>::from_dbus(value),
+        Err(MarshalError::StructFieldCount(2, 3))
+    ));
+
+    let value = Some(1.5f64).to_dbus().unwrap();
+    assert_eq!(Option::<f64>::from_dbus(value).unwrap(), Some(1.5));
+    let value = None::<f64>.to_dbus().unwrap();
+    assert_eq!(Option::<f64>::from_dbus(value).unwrap(), None);
+
+    let value = Variant::new(42u16).unwrap().to_dbus().unwrap();
+    assert_eq!(value, Value::Variant(Box::new(Value::Uint16(42))));
+    let variant = Variant::from_dbus(value).unwrap();
+    assert_eq!(variant.get::<u16>().unwrap(), 42);
+}
+
+#[test]
+fn marshal_args() {
+    let body = (1u32, "text").to_args().unwrap();
+    assert_eq!(
+        body,
+        vec![Value::Uint32(1), Value::String("text".to_string())]
+    );
+    let (number, text) = <(u32, String)>::from_args(body.clone()).unwrap();
+    assert_eq!(number, 1);
+    assert_eq!(text, "text");
+    assert!(matches!(
+        <(u32,)>::from_args(body.clone()),
+        Err(MarshalError::ArgumentCount(1, 2))
+    ));
+    assert!(<(String, u32)>::from_args(body).is_err());
+    assert!(<()>::from_args(Vec::new()).is_ok());
+}
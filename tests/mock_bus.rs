@@ -0,0 +1,389 @@
+mod common;
+
+use common::block_on;
+use dbus_async::{
+    test_support::MockBus, Binder, Binding, CallOptions, DBus, DBusError, DBusNameFlag,
+    ObjectServer, StandardError,
+};
+use dbus_message_parser::{
+    match_rule::MatchRule,
+    message::{Message, MessageFlags},
+    value::{Bus, Value},
+};
+use futures::{channel::mpsc::channel, future::join, StreamExt};
+use std::convert::TryInto;
+
+const NAME: &str = "org.example.Service";
+
+async fn call_bus<A, R>(dbus: &DBus, member: &str, args: A) -> Result<R, DBusError>
+where
+    A: dbus_async::marshal::ToArgs,
+    R: dbus_async::marshal::FromArgs,
+{
+    dbus.call_method(
+        "org.freedesktop.DBus".try_into().unwrap(),
+        "/org/freedesktop/DBus".try_into().unwrap(),
+        "org.freedesktop.DBus".try_into().unwrap(),
+        member.try_into().unwrap(),
+        args,
+    )
+    .await
+}
+
+/// Bind an object to the service, which emits the signals.
+fn bind(service: &DBus) -> Binding {
+    ObjectServer::new()
+        .spawn(service.clone(), "/org/example".try_into().unwrap())
+        .unwrap()
+}
+
+async fn request_name(dbus: &DBus, flags: DBusNameFlag) -> u32 {
+    let reply = dbus
+        .request_name(NAME.try_into().unwrap(), &flags)
+        .await
+        .unwrap();
+    match reply.get_body() {
+        [Value::Uint32(reply)] => *reply,
+        body => panic!("{:?}", body),
+    }
+}
+
+async fn get_name_owner(dbus: &DBus, name: &str) -> Result<String, DBusError> {
+    let (owner,): (String,) = call_bus(dbus, "GetNameOwner", (name.to_string(),)).await?;
+    Ok(owner)
+}
+
+#[test]
+fn hello() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (dbus_1, _connection_handle_1) = bus.connect().await.unwrap();
+        let (dbus_2, _connection_handle_2) = bus.connect().await.unwrap();
+        let (names,): (Vec<String>,) = call_bus(&dbus_1, "ListNames", ()).await.unwrap();
+        assert!(names.contains(&"org.freedesktop.DBus".to_string()));
+        assert_eq!(names.iter().filter(|name| name.starts_with(':')).count(), 2);
+        let (id_1,): (String,) = call_bus(&dbus_1, "GetId", ()).await.unwrap();
+        let (id_2,): (String,) = call_bus(&dbus_2, "GetId", ()).await.unwrap();
+        assert_eq!(id_1, id_2);
+    });
+}
+
+#[test]
+fn name_ownership() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (dbus_1, connection_handle_1) = bus.connect().await.unwrap();
+        let (dbus_2, _connection_handle_2) = bus.connect().await.unwrap();
+
+        let error = get_name_owner(&dbus_2, NAME).await.unwrap_err();
+        assert!(matches!(
+            error.to_typed(),
+            Some(StandardError::NameHasNoOwner(_))
+        ));
+        assert_eq!(request_name(&dbus_1, DBusNameFlag::empty()).await, 1);
+        assert_eq!(request_name(&dbus_1, DBusNameFlag::empty()).await, 4);
+        assert_eq!(request_name(&dbus_2, DBusNameFlag::DO_NOT_QUEUE).await, 3);
+        assert_eq!(request_name(&dbus_2, DBusNameFlag::empty()).await, 2);
+        let owner_1 = get_name_owner(&dbus_2, NAME).await.unwrap();
+
+        // The name is passed to the next connection in the queue.
+        let (sender, mut receiver) = channel(8);
+        let match_rule = format!("type='signal',member='NameAcquired',arg0='{}'", NAME);
+        dbus_2
+            .add_match_rules(MatchRule::decode(&match_rule).unwrap(), sender)
+            .unwrap();
+        dbus_1.close().unwrap();
+        connection_handle_1.await.unwrap();
+        receiver.next().await.unwrap();
+        let owner_2 = get_name_owner(&dbus_2, NAME).await.unwrap();
+        assert_ne!(owner_1, owner_2);
+        let (released,): (u32,) = call_bus(&dbus_2, "ReleaseName", (NAME.to_string(),))
+            .await
+            .unwrap();
+        assert_eq!(released, 1);
+        let (has_owner,): (bool,) = call_bus(&dbus_2, "NameHasOwner", (NAME.to_string(),))
+            .await
+            .unwrap();
+        assert!(!has_owner);
+    });
+}
+
+#[test]
+fn method_call() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (service, _service_handle) = bus.connect().await.unwrap();
+        let (client, _client_handle) = bus.connect().await.unwrap();
+        assert_eq!(request_name(&service, DBusNameFlag::empty()).await, 1);
+
+        let (sender, mut receiver) = channel(8);
+        service
+            .add_method_call("/org/example".try_into().unwrap(), sender)
+            .unwrap();
+        let mut msg = Message::method_call(
+            NAME.try_into().unwrap(),
+            "/org/example".try_into().unwrap(),
+            "org.example.Interface".try_into().unwrap(),
+            "Echo".try_into().unwrap(),
+        );
+        msg.add_value(Value::String("Hello".to_string()));
+        let serve = async {
+            let msg = receiver.next().await.unwrap();
+            let mut reply = msg.method_return().unwrap();
+            reply.add_value(msg.get_body()[0].clone());
+            service.send(reply).await.unwrap();
+        };
+        let (reply, _) = join(client.call(msg), serve).await;
+        let reply = reply.unwrap();
+        assert_eq!(reply.get_body(), &[Value::String("Hello".to_string())]);
+        assert_eq!(
+            reply.get_sender().unwrap().as_ref(),
+            get_name_owner(&client, NAME).await.unwrap()
+        );
+
+        // There is no owner of the name.
+        let msg = Message::method_call(
+            "org.example.Unknown".try_into().unwrap(),
+            "/org/example".try_into().unwrap(),
+            "org.example.Interface".try_into().unwrap(),
+            "Echo".try_into().unwrap(),
+        );
+        let error = client.call_checked(msg).await.unwrap_err();
+        assert!(matches!(
+            error.to_typed(),
+            Some(StandardError::ServiceUnknown(_))
+        ));
+    });
+}
+
+#[test]
+fn method_call_flags() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (service, _service_handle) = bus.connect().await.unwrap();
+        let (client, _client_handle) = bus.connect().await.unwrap();
+        assert_eq!(request_name(&service, DBusNameFlag::empty()).await, 1);
+
+        let (sender, mut receiver) = channel(8);
+        service
+            .add_method_call_with_flags("/org/example".try_into().unwrap(), sender)
+            .unwrap();
+        let msg = Message::method_call(
+            NAME.try_into().unwrap(),
+            "/org/example".try_into().unwrap(),
+            "org.example.Interface".try_into().unwrap(),
+            "Fire".try_into().unwrap(),
+        );
+        let options = CallOptions::new()
+            .no_reply_expected(true)
+            .allow_interactive_authorization(true);
+        client.send_with_options(msg, &options).await.unwrap();
+        // The flags are received together with the method call.
+        let (msg, flags) = receiver.next().await.unwrap();
+        assert_eq!(msg.get_member().unwrap().as_ref(), "Fire");
+        assert_eq!(
+            flags,
+            MessageFlags::NO_REPLY_EXPECTED | MessageFlags::ALLOW_INTERACTIVE_AUTHORIZATION
+        );
+    });
+}
+
+#[test]
+fn add_match() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (service, _service_handle) = bus.connect().await.unwrap();
+        let (client, _client_handle) = bus.connect().await.unwrap();
+        assert_eq!(request_name(&service, DBusNameFlag::empty()).await, 1);
+
+        // The bus resolves the well-known name of the sender, the connection sees only the
+        // unique name of the sender.
+        let match_rule = format!("type='signal',sender='{}',member='Changed'", NAME);
+        let (sender, mut receiver) = channel(8);
+        client
+            .add_match_rules(MatchRule::decode("type='signal'").unwrap(), sender)
+            .unwrap();
+        let () = call_bus(&client, "AddMatch", (match_rule.clone(),))
+            .await
+            .unwrap();
+        let binding = bind(&service);
+        let emitter = binding.signal_emitter("org.example.Interface".try_into().unwrap());
+        emitter
+            .emit("Ignored".try_into().unwrap(), ())
+            .await
+            .unwrap();
+        emitter
+            .emit("Changed".try_into().unwrap(), (1u32,))
+            .await
+            .unwrap();
+        let signal = receiver.next().await.unwrap();
+        assert_eq!(signal.get_body(), &[Value::Uint32(1)]);
+
+        let () = call_bus(&client, "RemoveMatch", (match_rule.clone(),))
+            .await
+            .unwrap();
+        let error = call_bus::<_, ()>(&client, "RemoveMatch", (match_rule,))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.to_typed(),
+            Some(StandardError::MatchRuleNotFound(_))
+        ));
+        let error = call_bus::<_, ()>(&client, "AddMatch", ("type=".to_string(),))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.to_typed(),
+            Some(StandardError::MatchRuleInvalid(_))
+        ));
+    });
+}
+
+#[test]
+fn name_owner_changed() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (dbus_1, _connection_handle_1) = bus.connect().await.unwrap();
+        let (dbus_2, _connection_handle_2) = bus.connect().await.unwrap();
+
+        let match_rule = "type='signal',sender='org.freedesktop.DBus',member='NameOwnerChanged'";
+        let (sender, mut receiver) = channel(8);
+        dbus_1
+            .add_match_rules(MatchRule::decode(match_rule).unwrap(), sender)
+            .unwrap();
+        let () = call_bus(&dbus_1, "AddMatch", (match_rule.to_string(),))
+            .await
+            .unwrap();
+        assert_eq!(request_name(&dbus_2, DBusNameFlag::empty()).await, 1);
+        let signal = receiver.next().await.unwrap();
+        let owner = get_name_owner(&dbus_1, NAME).await.unwrap();
+        assert_eq!(
+            signal.get_body(),
+            &[
+                Value::String(NAME.to_string()),
+                Value::String(String::new()),
+                Value::String(owner),
+            ]
+        );
+        let sender: Bus = "org.freedesktop.DBus".try_into().unwrap();
+        assert_eq!(signal.get_sender(), Some(&sender));
+    });
+}
+
+#[test]
+fn signal_emitter_unicast() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (service, _service_handle) = bus.connect().await.unwrap();
+        let (client, _client_handle) = bus.connect().await.unwrap();
+        let (other, _other_handle) = bus.connect().await.unwrap();
+        assert_eq!(request_name(&client, DBusNameFlag::empty()).await, 1);
+
+        // A unicast signal is routed without a match rule of the bus.
+        let match_rule = "type='signal',member='Changed'";
+        let (sender, mut receiver) = channel(8);
+        client
+            .add_match_rules(MatchRule::decode(match_rule).unwrap(), sender)
+            .unwrap();
+        let (other_sender, mut other_receiver) = channel(8);
+        other
+            .add_match_rules(MatchRule::decode(match_rule).unwrap(), other_sender)
+            .unwrap();
+        let binding = bind(&service);
+        let emitter = binding.signal_emitter("org.example.Interface".try_into().unwrap());
+        emitter
+            .emit_to(
+                NAME.try_into().unwrap(),
+                "Changed".try_into().unwrap(),
+                (1u32,),
+            )
+            .await
+            .unwrap();
+        let signal = receiver.next().await.unwrap();
+        assert_eq!(signal.get_body(), &[Value::Uint32(1)]);
+        assert_eq!(signal.get_destination().unwrap().as_ref(), NAME);
+
+        // The other connection does not receive the signal.
+        let _ = get_name_owner(&other, NAME).await.unwrap();
+        assert!(other_receiver.try_recv().is_err());
+    });
+}
+
+#[test]
+fn introspect() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (service, _service_handle) = bus.connect().await.unwrap();
+        let (client, _client_handle) = bus.connect().await.unwrap();
+        assert_eq!(request_name(&service, DBusNameFlag::empty()).await, 1);
+
+        // Bind two objects, which answer the introspection by themselves.
+        let (sender, mut receiver) = channel(8);
+        for object_path in ["/org/example/a/one", "/org/example/a/two"].iter() {
+            service
+                .add_method_call((*object_path).try_into().unwrap(), sender.clone())
+                .unwrap();
+        }
+        let server = async {
+            // The object `one` is introspected directly and by the tree, `two` only by the tree.
+            for _ in 0..3 {
+                let msg = receiver.next().await.unwrap();
+                assert_eq!(msg.get_member().unwrap().as_ref(), "Introspect");
+                let mut msg = msg.method_return().unwrap();
+                msg.add_value(Value::String(
+                    r#"<node><interface name="org.example.Interface"/></node>"#.to_string(),
+                ));
+                service.send(msg).await.unwrap();
+            }
+        };
+        let client = async {
+            let node = client
+                .introspect(
+                    NAME.try_into().unwrap(),
+                    "/org/example/a/one".try_into().unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(node.interfaces.len(), 1);
+            assert_eq!(node.interfaces[0].name, "org.example.Interface");
+            assert!(node.nodes.is_empty());
+
+            // The built-in introspection lists only the names of the child nodes.
+            let node = client
+                .introspect(
+                    NAME.try_into().unwrap(),
+                    "/org/example/a".try_into().unwrap(),
+                )
+                .await
+                .unwrap();
+            let mut names: Vec<_> = node.nodes.iter().map(|node| node.name.clone()).collect();
+            names.sort();
+            assert_eq!(
+                names,
+                vec![Some("one".to_string()), Some("two".to_string())]
+            );
+            assert!(node.nodes.iter().all(|node| node.interfaces.is_empty()));
+
+            let node = client
+                .introspect_tree(NAME.try_into().unwrap(), "/org".try_into().unwrap())
+                .await
+                .unwrap();
+            assert_eq!(node.nodes.len(), 1);
+            let example = &node.nodes[0];
+            assert_eq!(example.name.as_deref(), Some("example"));
+            assert_eq!(example.nodes.len(), 1);
+            let a = &example.nodes[0];
+            assert_eq!(a.name.as_deref(), Some("a"));
+            let mut objects: Vec<_> = a.nodes.iter().collect();
+            objects.sort_by(|x, y| x.name.cmp(&y.name));
+            assert_eq!(objects.len(), 2);
+            assert_eq!(objects[0].name.as_deref(), Some("one"));
+            assert_eq!(objects[1].name.as_deref(), Some("two"));
+            for object in objects {
+                assert_eq!(object.interfaces.len(), 1);
+                assert_eq!(object.interfaces[0].name, "org.example.Interface");
+            }
+        };
+        join(server, client).await;
+    });
+}
@@ -0,0 +1,253 @@
+mod common;
+
+use common::block_on;
+use dbus_async::{
+    test_support::MockBus, Binder, Binding, ConcurrentBinder, DBus, DBusError, DBusNameFlag,
+    MethodTable, ObjectServer, StandardError,
+};
+use dbus_message_parser::{
+    match_rule::MatchRule,
+    message::{Message, MessageFlags, MessageHeader, MessageHeaderFields, MessageType},
+    value::{Type, Value},
+};
+use futures::{channel::mpsc::channel, StreamExt};
+use std::{convert::TryInto, sync::Arc};
+
+const NAME: &str = "org.example.Service";
+const OBJECT_PATH: &str = "/org/example/Object";
+
+/// Create a table, where the method `Get` returns the given string.
+fn create_table(interface: &str, value: &'static str) -> MethodTable {
+    let mut table = MethodTable::new(interface.try_into().unwrap());
+    table.add_method(
+        "Get".try_into().unwrap(),
+        Vec::new(),
+        move |_dbus, _header, _body| async move { Ok(vec![Value::String(value.to_string())]) },
+    );
+    table.add_method(
+        "Echo".try_into().unwrap(),
+        Type::from_string_to_signature("s").unwrap(),
+        |_dbus, _header, body| async move { Ok(body) },
+    );
+    table
+}
+
+/// Create an object server with the interfaces `org.example.B` and `org.example.A` (in this
+/// order).
+fn create_object_server() -> ObjectServer {
+    let mut object_server = ObjectServer::new();
+    object_server.add_interface(create_table("org.example.B", "b"));
+    object_server.add_interface(create_table("org.example.A", "a"));
+    object_server
+}
+
+/// Connect the service and the client to a mock bus and request the name for the service.
+async fn connect(bus: &MockBus) -> (DBus, DBus) {
+    let (service, _service_handle) = bus.connect().await.unwrap();
+    let (client, _client_handle) = bus.connect().await.unwrap();
+    service
+        .request_name(NAME.try_into().unwrap(), &DBusNameFlag::empty())
+        .await
+        .unwrap();
+    (service, client)
+}
+
+/// Connect the service and the client to a mock bus and bind an object server to the service.
+async fn start_service(bus: &MockBus) -> (DBus, Binding) {
+    let (service, client) = connect(bus).await;
+    let binding = create_object_server()
+        .spawn(service, OBJECT_PATH.try_into().unwrap())
+        .unwrap();
+    (client, binding)
+}
+
+fn create_method_call(interface: &str, member: &str) -> Message {
+    Message::method_call(
+        NAME.try_into().unwrap(),
+        OBJECT_PATH.try_into().unwrap(),
+        interface.try_into().unwrap(),
+        member.try_into().unwrap(),
+    )
+}
+
+fn get_string(reply: Message) -> String {
+    assert_eq!(reply.get_type(), MessageType::MethodReturn);
+    match reply.get_body() {
+        [Value::String(value)] => value.clone(),
+        body => panic!("{:?}", body),
+    }
+}
+
+#[test]
+fn known_method() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (client, _binding) = start_service(&bus).await;
+        let reply = client
+            .call(create_method_call("org.example.B", "Get"))
+            .await
+            .unwrap();
+        assert_eq!(get_string(reply), "b");
+
+        let mut msg = create_method_call("org.example.A", "Echo");
+        msg.add_value(Value::String("Hello".to_string()));
+        let reply = client.call(msg).await.unwrap();
+        assert_eq!(get_string(reply), "Hello");
+    });
+}
+
+#[test]
+fn invalid_args() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (client, _binding) = start_service(&bus).await;
+        let mut msg = create_method_call("org.example.A", "Echo");
+        msg.add_value(Value::Uint32(1));
+        let error = client.call_checked(msg).await.unwrap_err();
+        assert!(matches!(
+            error.to_typed(),
+            Some(StandardError::InvalidArgs(_))
+        ));
+    });
+}
+
+#[test]
+fn unknown_member() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (client, _binding) = start_service(&bus).await;
+        let msg = create_method_call("org.example.A", "Unknown");
+        match client.call_checked(msg).await.unwrap_err() {
+            DBusError::MethodError { name, .. } => {
+                assert_eq!(name.as_ref(), "org.freedesktop.DBus.Error.UnknownMember")
+            }
+            e => panic!("{}", e),
+        }
+    });
+}
+
+#[test]
+fn unknown_interface() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (client, _binding) = start_service(&bus).await;
+        let msg = create_method_call("org.example.Unknown", "Get");
+        let error = client.call_checked(msg).await.unwrap_err();
+        assert!(matches!(
+            error.to_typed(),
+            Some(StandardError::UnknownInterface(_))
+        ));
+    });
+}
+
+#[test]
+fn no_interface() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (client, _binding) = start_service(&bus).await;
+        // Both interfaces have a method `Get`, so the method of the first interface in the
+        // lexicographical order is called and not the method of the first added interface.
+        let fields = MessageHeaderFields {
+            destination: Some(NAME.try_into().unwrap()),
+            path: Some(OBJECT_PATH.try_into().unwrap()),
+            member: Some("Get".try_into().unwrap()),
+            ..Default::default()
+        };
+        let header = MessageHeader::new(
+            true,
+            MessageType::MethodCall,
+            MessageFlags::empty(),
+            1,
+            0,
+            fields,
+        )
+        .unwrap();
+        let reply = client.call(Message::new(header, Vec::new())).await.unwrap();
+        assert_eq!(get_string(reply), "a");
+    });
+}
+
+#[test]
+fn concurrent() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (service, client) = connect(&bus).await;
+        let _binding = create_object_server()
+            .spawn_concurrent(service, OBJECT_PATH.try_into().unwrap())
+            .unwrap();
+        let reply = client
+            .call(create_method_call("org.example.B", "Get"))
+            .await
+            .unwrap();
+        assert_eq!(get_string(reply), "b");
+    });
+}
+
+#[test]
+fn signal_emitter() {
+    block_on(async {
+        let bus = MockBus::new();
+        let (service, client) = connect(&bus).await;
+        let match_rule = "type='signal',member='Changed'";
+        let (sender, mut receiver) = channel(8);
+        client
+            .add_match_rules(MatchRule::decode(match_rule).unwrap(), sender)
+            .unwrap();
+        let () = client
+            .call_method(
+                "org.freedesktop.DBus".try_into().unwrap(),
+                "/org/freedesktop/DBus".try_into().unwrap(),
+                "org.freedesktop.DBus".try_into().unwrap(),
+                "AddMatch".try_into().unwrap(),
+                (match_rule.to_string(),),
+            )
+            .await
+            .unwrap();
+
+        let mut table = create_table("org.example.A", "a");
+        table.add_signal(
+            "Changed".try_into().unwrap(),
+            Type::from_string_to_signature("u").unwrap(),
+        );
+        let mut object_server = create_object_server();
+        object_server.add_interface(table);
+        let object_server = Arc::new(object_server);
+        let binding = object_server
+            .clone()
+            .spawn_concurrent(service, OBJECT_PATH.try_into().unwrap())
+            .unwrap();
+
+        let error = object_server
+            .signal_emitter(&binding, "org.example.Unknown".try_into().unwrap())
+            .err()
+            .unwrap();
+        assert!(matches!(error, DBusError::InterfaceNotDeclared(_)));
+
+        let emitter = object_server
+            .signal_emitter(&binding, "org.example.A".try_into().unwrap())
+            .unwrap();
+        emitter
+            .emit("Changed".try_into().unwrap(), (1u32,))
+            .await
+            .unwrap();
+        let signal = receiver.next().await.unwrap();
+        assert_eq!(signal.get_body(), &[Value::Uint32(1)]);
+        assert_eq!(signal.get_path().unwrap().as_ref(), OBJECT_PATH);
+
+        let error = emitter
+            .emit("Unknown".try_into().unwrap(), (1u32,))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, DBusError::SignalNotDeclared(_, _)));
+        match emitter
+            .emit("Changed".try_into().unwrap(), ("1".to_string(),))
+            .await
+        {
+            Err(DBusError::SignalSignature { expected, got, .. }) => {
+                assert_eq!(expected, "u");
+                assert_eq!(got, "s");
+            }
+            result => panic!("{:?}", result),
+        }
+    });
+}
@@ -0,0 +1,261 @@
+mod common;
+
+use common::{block_on, create_ping, start_server};
+use dbus_async::{CallOptions, DBus, DBusBuilder, DBusError, PendingCallPolicy, ProtocolError};
+use dbus_message_parser::message::{MessageFlags, MessageType};
+use futures::{channel::mpsc::channel, future::join, StreamExt};
+use std::time::Duration;
+
+#[test]
+fn connect_failed() {
+    let result = block_on(
+        DBusBuilder::new()
+            .address("unix:path=/nonexistent/dbus-async")
+            .build(),
+    );
+    assert!(matches!(result, Err(DBusError::StreamError(_))));
+}
+
+#[test]
+fn send_and_close() {
+    let (address, server) = start_server("send_and_close", Vec::new());
+    block_on(async {
+        let (dbus, connection_handle) = DBusBuilder::new()
+            .address(&address)
+            .hello(false)
+            .build()
+            .await
+            .unwrap();
+        dbus.send(create_ping()).await.unwrap();
+        dbus.close().unwrap();
+        connection_handle.await.unwrap();
+    });
+    assert_eq!(server.join().unwrap().len(), 1);
+}
+
+#[test]
+fn send_with_options() {
+    let (address, server) = start_server("send_with_options", Vec::new());
+    block_on(async {
+        let (dbus, connection_handle) = DBusBuilder::new()
+            .address(&address)
+            .hello(false)
+            .build()
+            .await
+            .unwrap();
+        let options = CallOptions::new().no_auto_start(true);
+        dbus.send_with_options(create_ping(), &options)
+            .await
+            .unwrap();
+        // The connection does not wait for a reply.
+        let options = CallOptions::new().no_reply_expected(true);
+        let reply = dbus.call_with_options(create_ping(), &options).await;
+        assert!(reply.unwrap().is_none());
+        dbus.close().unwrap();
+        connection_handle.await.unwrap();
+    });
+    let flags: Vec<u8> = server
+        .join()
+        .unwrap()
+        .iter()
+        .map(|msg| msg.encode().unwrap()[2])
+        .collect();
+    assert_eq!(
+        flags,
+        vec![
+            MessageFlags::NO_AUTO_START.bits(),
+            MessageFlags::NO_REPLY_EXPECTED.bits()
+        ]
+    );
+}
+
+#[test]
+fn call_timeout() {
+    let (address, _server) = start_server("call_timeout", Vec::new());
+    block_on(async {
+        let timeout = Duration::from_millis(100);
+        let (dbus, _connection_handle) = DBusBuilder::new()
+            .address(&address)
+            .hello(false)
+            .call_timeout(Some(timeout))
+            .build()
+            .await
+            .unwrap();
+        match dbus.call(create_ping()).await {
+            Err(DBusError::CallTimeout(duration)) => assert_eq!(duration, timeout),
+            result => panic!("{:?}", result),
+        }
+    });
+}
+
+#[test]
+fn protocol_error() {
+    let mut reply = create_ping();
+    reply.set_serial(1);
+    let mut reply = reply.method_return().unwrap();
+    reply.set_serial(1);
+    let size = reply.encode().unwrap().len();
+    let (address, _server) = start_server("protocol_error", vec![reply]);
+    block_on(async {
+        let (dbus, connection_handle) = DBusBuilder::new()
+            .address(&address)
+            .hello(false)
+            .maximum_message_size(size - 1)
+            .build()
+            .await
+            .unwrap();
+        let error = ProtocolError::TooLarge {
+            size,
+            maximum: size - 1,
+        };
+        match dbus.call(create_ping()).await {
+            Err(DBusError::ProtocolError(e)) => assert_eq!(e, error),
+            result => panic!("{:?}", result),
+        }
+        connection_handle.await.unwrap();
+        assert_eq!(dbus.get_protocol_error(), Some(&error));
+        // The next calls return the error, which closed the connection.
+        match dbus.send(create_ping()).await {
+            Err(DBusError::ProtocolError(e)) => assert_eq!(e, error),
+            result => panic!("{:?}", result),
+        }
+        match dbus.call(create_ping()).await {
+            Err(DBusError::ProtocolError(e)) => assert_eq!(e, error),
+            result => panic!("{:?}", result),
+        }
+    });
+}
+
+#[test]
+fn abort_connection() {
+    let (address, _server) = start_server("abort_connection", Vec::new());
+    block_on(async {
+        let (_dbus, connection_handle) = DBusBuilder::new()
+            .address(&address)
+            .hello(false)
+            .build()
+            .await
+            .unwrap();
+        connection_handle.abort();
+        assert_eq!(
+            connection_handle.await,
+            Err(dbus_async::JoinError::Cancelled)
+        );
+    });
+}
+
+/// Connect to the server with the given limit and policy of the pending calls.
+async fn connect_pending_call_limit(address: &str, policy: PendingCallPolicy) -> DBus {
+    let (dbus, _connection_handle) = DBusBuilder::new()
+        .address(address)
+        .hello(false)
+        .pending_call_limit(1)
+        .pending_call_policy(policy)
+        .call_timeout(Some(Duration::from_millis(100)))
+        .build()
+        .await
+        .unwrap();
+    dbus
+}
+
+#[test]
+fn pending_call_evict_oldest() {
+    let (address, _server) = start_server("pending_call_evict_oldest", Vec::new());
+    block_on(async {
+        let dbus = connect_pending_call_limit(&address, PendingCallPolicy::EvictOldest).await;
+        let (sender, mut receiver) = channel(1);
+        let (first, second) = join(
+            dbus.call(create_ping()),
+            dbus.call_reply_serial(create_ping(), sender),
+        )
+        .await;
+        assert!(matches!(first, Err(DBusError::PendingCallEvicted(1))));
+        assert_eq!(second.unwrap(), 2);
+
+        // The caller with a channel gets an error reply.
+        let (sender, _receiver) = channel(1);
+        assert_eq!(
+            dbus.call_reply_serial(create_ping(), sender).await.unwrap(),
+            3
+        );
+        let reply = receiver.next().await.unwrap();
+        assert_eq!(reply.get_type(), MessageType::Error);
+        assert_eq!(reply.get_reply_serial(), Some(2));
+        assert_eq!(
+            reply.get_error_name().unwrap().as_ref(),
+            "org.freedesktop.DBus.Error.NoReply"
+        );
+    });
+}
+
+#[test]
+fn pending_call_reject_new() {
+    let (address, _server) = start_server("pending_call_reject_new", Vec::new());
+    block_on(async {
+        let dbus = connect_pending_call_limit(&address, PendingCallPolicy::RejectNew).await;
+        let (sender, _receiver) = channel(1);
+        assert_eq!(
+            dbus.call_reply_serial(create_ping(), sender).await.unwrap(),
+            1
+        );
+        assert!(matches!(
+            dbus.call(create_ping()).await,
+            Err(DBusError::PendingCallLimit(1))
+        ));
+        let (sender, _receiver) = channel(1);
+        assert!(matches!(
+            dbus.call_reply_serial(create_ping(), sender).await,
+            Err(DBusError::PendingCallLimit(1))
+        ));
+        assert_eq!(dbus.get_pending_calls(), 1);
+    });
+}
+
+#[test]
+fn pending_call_wait() {
+    let (address, server) = start_server("pending_call_wait", Vec::new());
+    block_on(async {
+        let dbus = connect_pending_call_limit(&address, PendingCallPolicy::Wait).await;
+        let (sender, _receiver) = channel(1);
+        assert_eq!(
+            dbus.call_reply_serial(create_ping(), sender).await.unwrap(),
+            1
+        );
+        // The call is not sent, because no reply of the pending call is received.
+        assert!(matches!(
+            dbus.call(create_ping()).await,
+            Err(DBusError::CallTimeout(_))
+        ));
+        assert_eq!(dbus.get_pending_calls(), 1);
+        dbus.close().unwrap();
+    });
+    assert_eq!(server.join().unwrap().len(), 1);
+}
+
+#[test]
+fn pending_call_wait_reply() {
+    let mut ping = create_ping();
+    ping.set_serial(1);
+    let mut reply = ping.method_return().unwrap();
+    reply.set_serial(1);
+    let (address, server) = start_server("pending_call_wait_reply", vec![reply]);
+    block_on(async {
+        let dbus = connect_pending_call_limit(&address, PendingCallPolicy::Wait).await;
+        let (sender, mut receiver) = channel(1);
+        assert_eq!(
+            dbus.call_reply_serial(create_ping(), sender).await.unwrap(),
+            1
+        );
+        // The call is sent, after the reply of the pending call is received.
+        let (sender, _receiver) = channel(1);
+        assert_eq!(
+            dbus.call_reply_serial(create_ping(), sender).await.unwrap(),
+            2
+        );
+        let reply = receiver.next().await.unwrap();
+        assert_eq!(reply.get_type(), MessageType::MethodReturn);
+        assert_eq!(reply.get_reply_serial(), Some(1));
+        dbus.close().unwrap();
+    });
+    assert_eq!(server.join().unwrap().len(), 2);
+}
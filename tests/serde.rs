@@ -0,0 +1,115 @@
+#![cfg(feature = "serde")]
+use dbus_async::serde::{
+    decode_body, encode_body, from_body, from_value, signature, to_body, to_value, SerdeError,
+};
+use dbus_message_parser::{
+    message::Message,
+    value::{Type, Value},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, convert::TryInto};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rectangle(u32, u32),
+    Polygon { points: Vec<(i32, i32)> },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Object {
+    name: String,
+    id: u64,
+    tags: Vec<String>,
+    properties: HashMap<String, i16>,
+    parent: Option<String>,
+    shapes: Vec<Shape>,
+}
+
+fn object() -> Object {
+    let mut properties = HashMap::new();
+    properties.insert("size".to_string(), -3);
+    Object {
+        name: "object".to_string(),
+        id: 42,
+        tags: Vec::new(),
+        properties,
+        parent: None,
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(1.5),
+            Shape::Rectangle(2, 3),
+            Shape::Polygon {
+                points: vec![(0, 0), (1, 1)],
+            },
+        ],
+    }
+}
+
+#[test]
+fn serde_signature() {
+    assert_eq!(
+        signature::<Object>().unwrap(),
+        Type::from_string_to_signature("(stasa{sn}asa(sv))").unwrap()
+    );
+    assert_eq!(
+        signature::<(u8, String)>().unwrap(),
+        Type::from_string_to_signature("ys").unwrap()
+    );
+    assert!(signature::<()>().unwrap().is_empty());
+    assert!(matches!(
+        signature::<HashMap<Vec<u8>, u8>>(),
+        Err(SerdeError::MapKey(_))
+    ));
+}
+
+#[test]
+fn serde_roundtrip() {
+    let body = to_body(&object()).unwrap();
+    assert_eq!(body.len(), 1);
+    assert_eq!(
+        body[0].get_type().unwrap(),
+        "(stasa{sn}asa(sv))".try_into().unwrap()
+    );
+    let decoded: Object = from_body(body).unwrap();
+    assert_eq!(decoded, object());
+
+    let body = to_body(&(1u32, "text".to_string())).unwrap();
+    assert_eq!(
+        body,
+        vec![Value::Uint32(1), Value::String("text".to_string())]
+    );
+    let decoded: (u32, String) = from_body(body).unwrap();
+    assert_eq!(decoded, (1, "text".to_string()));
+
+    let value = to_value(&Shape::Rectangle(4, 5)).unwrap();
+    assert_eq!(from_value::<Shape>(value).unwrap(), Shape::Rectangle(4, 5));
+}
+
+#[test]
+fn serde_message() {
+    let mut msg = Message::method_call(
+        "org.example".try_into().unwrap(),
+        "/org/example".try_into().unwrap(),
+        "org.example.Interface".try_into().unwrap(),
+        "Method".try_into().unwrap(),
+    );
+    encode_body(&mut msg, &(object(), true)).unwrap();
+    let bytes = msg.encode().unwrap().freeze();
+    let (msg, _) = Message::decode(bytes).unwrap();
+    let (decoded, flag): (Object, bool) = decode_body(msg).unwrap();
+    assert_eq!(decoded, object());
+    assert!(flag);
+}
+
+#[test]
+fn serde_errors() {
+    let body = vec![Value::Uint32(1)];
+    assert!(matches!(
+        from_body::<(u32, u32)>(body.clone()),
+        Err(SerdeError::ArgumentCount(2, 1))
+    ));
+    assert!(from_body::<String>(body.clone()).is_err());
+    assert!(from_body::<Object>(body).is_err());
+}
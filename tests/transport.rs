@@ -0,0 +1,280 @@
+mod common;
+
+use common::{block_on, connect_unix, create_ping, start_server};
+use dbus_async::DBusBuilder;
+use std::{
+    fs::remove_file,
+    io::{BufRead, BufReader, Read, Write},
+    os::unix::net::UnixListener,
+    process::id,
+    thread::spawn,
+};
+
+#[test]
+fn build_with_stream() {
+    let (address, server) = start_server("transport_build_with_stream", Vec::new());
+    let path = address.strip_prefix("unix:path=").unwrap().to_string();
+    block_on(async {
+        let stream = connect_unix(&path).await;
+        let (dbus, connection_handle) = DBusBuilder::new()
+            .hello(false)
+            .build_with_stream(stream)
+            .await
+            .unwrap();
+        assert!(dbus.try_get_address().is_none());
+        dbus.send(create_ping()).await.unwrap();
+        dbus.close().unwrap();
+        connection_handle.await.unwrap();
+    });
+    let received = server.join().unwrap();
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].get_member(), create_ping().get_member());
+}
+
+/// Connect with the builder and return the commands of the client during the authentication.
+fn handshake_commands(name: &str, builder: DBusBuilder) -> Vec<String> {
+    let path = std::env::temp_dir().join(format!("dbus-async-{}-{}", name, id()));
+    let _ = remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let address = format!("unix:path={}", path.display());
+    let server = spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let _ = remove_file(&path);
+        let mut writer = stream.try_clone().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut zero = [0; 1];
+        reader.read_exact(&mut zero).unwrap();
+        let mut commands = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let command = line
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let response = match line.trim_end() {
+                "AUTH" => "REJECTED EXTERNAL ANONYMOUS",
+                "NEGOTIATE_UNIX_FD" => "AGREE_UNIX_FD",
+                "BEGIN" => break,
+                _ => "OK 0123456789abcdef0123456789abcdef",
+            };
+            commands.push(command);
+            writer
+                .write_all(format!("{}\r\n", response).as_bytes())
+                .unwrap();
+        }
+        // Keep the connection open until the client closes it.
+        reader.read_to_end(&mut Vec::new()).unwrap();
+        commands
+    });
+    block_on(async {
+        let (dbus, connection_handle) = builder
+            .address(&address)
+            .hello(false)
+            .build()
+            .await
+            .unwrap();
+        dbus.close().unwrap();
+        connection_handle.await.unwrap();
+    });
+    server.join().unwrap()
+}
+
+#[test]
+fn negotiate_unix_fd() {
+    let commands = handshake_commands("transport_negotiate_unix_fd_default", DBusBuilder::new());
+    assert!(!commands
+        .iter()
+        .any(|command| command == "NEGOTIATE_UNIX_FD"));
+    let builder = DBusBuilder::new().negotiate_unix_fd(true);
+    let commands = handshake_commands("transport_negotiate_unix_fd", builder);
+    assert_eq!(commands.last().unwrap(), "NEGOTIATE_UNIX_FD");
+}
+
+#[cfg(feature = "runtime-tokio")]
+mod duplex {
+    use crate::common::{create_ping, create_signal};
+    use bytes::{Bytes, BytesMut};
+    use dbus_async::{DBusBuilder, DBusError, ReceivePolicy, TokioCompat};
+    use dbus_message_parser::{decode::DecodeError, message::Message};
+    use futures::{
+        channel::{mpsc::channel, oneshot},
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter},
+        StreamExt,
+    };
+    use std::{convert::TryInto, time::Duration};
+    use tokio::{
+        io::{duplex, DuplexStream},
+        time::timeout,
+    };
+
+    /// The number of signals, which are sent at once.
+    const SIGNALS: usize = 50;
+
+    /// Answer the authentication.
+    async fn authenticate(stream: DuplexStream) -> BufReader<TokioCompat<DuplexStream>> {
+        let mut stream = BufReader::new(TokioCompat::new(stream));
+        let mut zero = [0; 1];
+        stream.read_exact(&mut zero).await.unwrap();
+        loop {
+            let mut line = String::new();
+            stream.read_line(&mut line).await.unwrap();
+            let response = match line.trim_end() {
+                "AUTH" => "REJECTED EXTERNAL ANONYMOUS",
+                "BEGIN" => break,
+                _ => "OK 0123456789abcdef0123456789abcdef",
+            };
+            let response = format!("{}\r\n", response);
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+        stream
+    }
+
+    /// Answer the authentication and reply to every method call.
+    async fn serve(stream: DuplexStream) {
+        let mut stream = authenticate(stream).await;
+        let mut buffer = BytesMut::new();
+        let mut chunk = [0; 1024];
+        loop {
+            let size = stream.read(&mut chunk).await.unwrap();
+            if size == 0 {
+                return;
+            }
+            buffer.extend_from_slice(&chunk[..size]);
+            loop {
+                let (msg, offset) = match Message::decode(buffer.clone().freeze()) {
+                    Ok(result) => result,
+                    Err(DecodeError::NotEnoughBytes(_, _)) => break,
+                    Err(e) => panic!("{:?}", e),
+                };
+                let _ = buffer.split_to(offset);
+                let mut reply = msg.method_return().unwrap();
+                reply.set_serial(1);
+                stream.write_all(&reply.encode().unwrap()).await.unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn call() {
+        let (client, server) = duplex(4096);
+        let server = tokio::spawn(serve(server));
+        let (dbus, connection_handle) = DBusBuilder::new()
+            .hello(false)
+            .build_with_stream(TokioCompat::new(client))
+            .await
+            .unwrap();
+        let msg = create_ping();
+        let reply = dbus.call(msg).await.unwrap();
+        assert_eq!(reply.get_reply_serial(), Some(1));
+        dbus.close().unwrap();
+        connection_handle.await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn call_buffered() {
+        let (client, server) = duplex(4096);
+        let server = tokio::spawn(serve(server));
+        // The transport holds back the written bytes until it is flushed.
+        let (dbus, connection_handle) = DBusBuilder::new()
+            .hello(false)
+            .call_timeout(Some(Duration::from_secs(1)))
+            .build_with_stream(BufWriter::new(TokioCompat::new(client)))
+            .await
+            .unwrap();
+        let reply = dbus.call(create_ping()).await.unwrap();
+        assert_eq!(reply.get_reply_serial(), Some(1));
+        dbus.close().unwrap();
+        connection_handle.await.unwrap();
+        server.await.unwrap();
+    }
+
+    /// Count the messages, which are received until the stream is closed.
+    async fn count_messages(stream: &mut BufReader<TokioCompat<DuplexStream>>) -> usize {
+        let mut received = Vec::new();
+        stream.read_to_end(&mut received).await.unwrap();
+        let mut bytes = Bytes::from(received);
+        let mut count = 0;
+        while !bytes.is_empty() {
+            let (_, offset) = Message::decode(bytes.clone()).unwrap();
+            bytes = bytes.slice(offset..);
+            count += 1;
+        }
+        count
+    }
+
+    #[tokio::test]
+    async fn send_backpressure() {
+        let (client, server) = duplex(64);
+        let (start_sender, start_receiver) = oneshot::channel();
+        let server = tokio::spawn(async move {
+            let mut stream = authenticate(server).await;
+            start_receiver.await.unwrap();
+            count_messages(&mut stream).await
+        });
+        let (dbus, connection_handle) = DBusBuilder::new()
+            .hello(false)
+            .send_queue_size(1)
+            .build_with_stream(TokioCompat::new(client))
+            .await
+            .unwrap();
+
+        // The server does not read, so the queue is full after a few messages.
+        let mut sent = 0;
+        while let Ok(result) = timeout(Duration::from_millis(100), dbus.send(create_ping())).await {
+            result.unwrap();
+            sent += 1;
+            assert!(sent < 100);
+        }
+        assert!(matches!(
+            dbus.try_send(create_ping()),
+            Err(DBusError::SendMessage(_))
+        ));
+
+        // The message is sent, after the server reads.
+        start_sender.send(()).unwrap();
+        dbus.send(create_ping()).await.unwrap();
+        sent += 1;
+        dbus.close().unwrap();
+        connection_handle.await.unwrap();
+        assert_eq!(server.await.unwrap(), sent);
+    }
+
+    #[tokio::test]
+    async fn drop_signals() {
+        let (client, server) = duplex(1 << 16);
+        let server = tokio::spawn(async move {
+            let mut stream = authenticate(server).await;
+            // Send all signals at once, so they are received faster than they are handled.
+            let mut signals = Vec::new();
+            for serial in 1..=SIGNALS as u32 {
+                signals.extend_from_slice(&create_signal(serial).encode().unwrap());
+            }
+            stream.write_all(&signals).await.unwrap();
+            count_messages(&mut stream).await
+        });
+        let (dbus, connection_handle) = DBusBuilder::new()
+            .hello(false)
+            .receive_queue_size(1)
+            .receive_policy(ReceivePolicy::DropSignals)
+            .build_with_stream(TokioCompat::new(client))
+            .await
+            .unwrap();
+        let (sender, mut receiver) = channel(SIGNALS);
+        dbus.add_signal("/org/example".try_into().unwrap(), None, sender)
+            .unwrap();
+
+        let mut received = 0;
+        while let Ok(Some(_)) = timeout(Duration::from_millis(100), receiver.next()).await {
+            received += 1;
+        }
+        let dropped = dbus.get_dropped_signals();
+        assert!(0 < dropped);
+        assert_eq!(received + dropped, SIGNALS);
+        dbus.close().unwrap();
+        connection_handle.await.unwrap();
+        assert_eq!(server.await.unwrap(), 0);
+    }
+}